@@ -0,0 +1,23 @@
+//! Request body compression for `compress(gzip)` and `compress(deflate)`.
+
+use std::io::Write;
+
+use flate2::{
+    write::{GzEncoder, ZlibEncoder},
+    Compression,
+};
+
+/// Compresses a serialized body for `Content-Encoding: gzip`.
+pub fn gzip(body: impl AsRef<[u8]>) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(body.as_ref()).unwrap();
+    encoder.finish().unwrap()
+}
+
+/// Compresses a serialized body for `Content-Encoding: deflate`, which is
+/// zlib framed despite the name.
+pub fn deflate(body: impl AsRef<[u8]>) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(body.as_ref()).unwrap();
+    encoder.finish().unwrap()
+}
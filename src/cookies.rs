@@ -0,0 +1,31 @@
+//! The `reqwest` clients of handlers using `cookies`. They're shared, so
+//! cookies set by one response are sent along with every later request.
+
+use std::sync::OnceLock;
+
+/// The client with the shared cookie store.
+pub fn client() -> reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT
+        .get_or_init(|| {
+            reqwest::Client::builder()
+                .cookie_store(true)
+                .build()
+                .expect("failed to build the reqwest client")
+        })
+        .clone()
+}
+
+/// The blocking client with the shared cookie store.
+#[cfg(feature = "blocking")]
+pub fn blocking_client() -> reqwest::blocking::Client {
+    static CLIENT: OnceLock<reqwest::blocking::Client> = OnceLock::new();
+    CLIENT
+        .get_or_init(|| {
+            reqwest::blocking::Client::builder()
+                .cookie_store(true)
+                .build()
+                .expect("failed to build the reqwest client")
+        })
+        .clone()
+}
@@ -0,0 +1,123 @@
+use std::{
+    convert::Infallible,
+    fmt::{self, Debug, Display},
+};
+
+use crate::ClientError;
+
+/// Error returned by client functions that do more than send the request and
+/// read the body, i.e. decoding binary formats or checking the API version.
+///
+/// `E` is the error body decoded for `error(ErrType)`. It's uninhabited by
+/// default, since other client functions never return [`Error::Api`].
+#[derive(Debug)]
+pub enum Error<E = Infallible> {
+    /// Sending the request or reading the response failed.
+    Request(ClientError),
+    /// The response body couldn't be decoded into the expected type.
+    Decode(Box<dyn std::error::Error + Send + Sync>),
+    /// The server responded with a different API version than expected.
+    Version {
+        expected: String,
+        found: Option<String>,
+    },
+    /// The server responded with a non-2xx status and this error body.
+    Api(E),
+    /// Sending the request with `ureq` failed, or the server responded with a
+    /// non-2xx status, for `native_client(ureq)`.
+    #[cfg(all(feature = "ureq", not(target_arch = "wasm32")))]
+    Ureq(Box<::ureq::Error>),
+}
+
+impl<E> Error<E> {
+    pub fn decode(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Self {
+        Error::Decode(err.into())
+    }
+}
+
+impl<E: Display> Display for Error<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Request(err) => write!(f, "request failed: {err}"),
+            Error::Decode(err) => write!(f, "failed to decode response: {err}"),
+            Error::Version {
+                expected,
+                found: Some(found),
+            } => write!(f, "expected API version {expected}, found {found}"),
+            Error::Version {
+                expected,
+                found: None,
+            } => write!(f, "expected API version {expected}, found none"),
+            Error::Api(err) => write!(f, "API error: {err}"),
+            #[cfg(all(feature = "ureq", not(target_arch = "wasm32")))]
+            Error::Ureq(err) => write!(f, "request failed: {err}"),
+        }
+    }
+}
+
+impl<E: Debug + Display> std::error::Error for Error<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Request(err) => Some(err),
+            Error::Decode(err) => Some(err.as_ref()),
+            Error::Version { .. } | Error::Api(_) => None,
+            #[cfg(all(feature = "ureq", not(target_arch = "wasm32")))]
+            Error::Ureq(err) => Some(err.as_ref()),
+        }
+    }
+}
+
+impl<E> From<ClientError> for Error<E> {
+    fn from(err: ClientError) -> Self {
+        Error::Request(err)
+    }
+}
+
+#[cfg(all(feature = "ureq", not(target_arch = "wasm32")))]
+impl<E> From<::ureq::Error> for Error<E> {
+    fn from(err: ::ureq::Error) -> Self {
+        Error::Ureq(Box::new(err))
+    }
+}
+
+/// A JSON response body that couldn't be decoded, along with the start of the
+/// body for context. It's the source of [`Error::Decode`] with the
+/// `debug_bodies` feature.
+#[cfg(feature = "debug_bodies")]
+#[derive(Debug)]
+pub struct BodyError {
+    pub source: serde_json::Error,
+    /// The body, cut off after [`BodyError::SNIPPET_LEN`] characters.
+    pub body: String,
+}
+
+#[cfg(feature = "debug_bodies")]
+impl BodyError {
+    pub const SNIPPET_LEN: usize = 256;
+
+    pub fn new(source: serde_json::Error, body: &[u8]) -> Self {
+        let body = String::from_utf8_lossy(body);
+        let mut snippet = body.chars().take(Self::SNIPPET_LEN).collect::<String>();
+        if snippet.len() < body.len() {
+            snippet.push_str("...");
+        }
+        BodyError {
+            source,
+            body: snippet,
+        }
+    }
+}
+
+#[cfg(feature = "debug_bodies")]
+impl Display for BodyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} in body `{}`", self.source, self.body)
+    }
+}
+
+#[cfg(feature = "debug_bodies")]
+impl std::error::Error for BodyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
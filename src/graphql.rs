@@ -0,0 +1,17 @@
+//! The GraphQL-over-HTTP envelope used by `graphql` clients.
+
+use serde::{Deserialize, Serialize};
+
+/// The request body, i.e. `{"query": ..., "variables": ...}`.
+#[derive(Serialize)]
+pub struct Request<'a, V> {
+    pub query: &'a str,
+    pub variables: &'a V,
+}
+
+/// The response body. Only `data` is read, so a response carrying `errors`
+/// without `data` fails to decode.
+#[derive(Deserialize)]
+pub struct Response<T> {
+    pub data: T,
+}
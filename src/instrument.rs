@@ -0,0 +1,62 @@
+//! Spans around the generated client functions, for the `tracing` feature.
+
+use std::{fmt::Debug, future::Future};
+
+#[doc(hidden)]
+pub use tracing::{field, info_span};
+use tracing::{Instrument, Span};
+
+use crate::ClientResponse;
+
+/// Runs `call` inside `span` and logs its outcome once it completes, along
+/// with how long it took.
+pub async fn call<T, E: Debug>(
+    span: Span,
+    call: impl Future<Output = Result<T, E>>,
+) -> Result<T, E> {
+    let timer = Timer::start();
+    let result = call.instrument(span.clone()).await;
+    let elapsed_ms = timer.elapsed_ms();
+    span.in_scope(|| match &result {
+        Ok(_) => tracing::info!(elapsed_ms, "request succeeded"),
+        Err(error) => tracing::warn!(elapsed_ms, ?error, "request failed"),
+    });
+    result
+}
+
+/// Records the status of `response` on the current span, which is the one
+/// opened by [`call`].
+pub fn status(response: ClientResponse) -> ClientResponse {
+    let status = crate::response::status(&response);
+    Span::current().record("status", status.as_u16());
+    response
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+struct Timer(std::time::Instant);
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Timer {
+    fn start() -> Self {
+        Self(std::time::Instant::now())
+    }
+
+    fn elapsed_ms(&self) -> f64 {
+        self.0.elapsed().as_secs_f64() * 1000.0
+    }
+}
+
+/// `Instant` panics on WASM, the browser's clock stands in for it.
+#[cfg(target_arch = "wasm32")]
+struct Timer(f64);
+
+#[cfg(target_arch = "wasm32")]
+impl Timer {
+    fn start() -> Self {
+        Self(js_sys::Date::now())
+    }
+
+    fn elapsed_ms(&self) -> f64 {
+        js_sys::Date::now() - self.0
+    }
+}
@@ -37,24 +37,416 @@
 //! # Configuration
 //!
 //! * `get(path)` - Specifiy this handler's path relative to the root of your
-//! API. Extracted path segments are prefixed with `:`, i.e. `:id`.
+//!   API. Extracted path segments are prefixed with `:`, i.e. `:id`. The client
+//!   function takes `String` segments as `&str`, so callers needn't allocate.
+//!   Segments may declare their type inline, i.e. `:id<u32>`, which fails to
+//!   compile unless the handler's `Path` extracts that type for `id`. The type is
+//!   stripped from the path the client requests.
+//!   Malformed paths fail to compile: relative ones without `prefix(...)`, empty
+//!   segments like in `/api//user` and `:`/`*` markers without a name.
+//!   A handler extracting `Path<HashMap<String, String>>` gets a client function
+//!   taking a reference to the map, which looks up every segment by name and
+//!   panics naming the first missing segment.
+//!   `post`, `put`, `delete` and `patch` work the same way, as do `trace` and
+//!   `connect`, which the clients send through their generic request
+//!   constructor. Browsers refuse to send the latter two, so their WASM
+//!   clients fail to compile with a `compile_error!`. `connect` handlers aren't
+//!   registered for `inventory` or `openapi`, since axum can't route them by
+//!   path.
+//! * `ws(path)` - Mark this handler as a WebSocket endpoint, taking a
+//!   `WebSocketUpgrade`. Instead of a client function, `connect_<handler>(...)`
+//!   is generated, taking the path and query arguments and returning an open
+//!   `wrpc::ws::WebSocket`: a `gloo-net` socket on WASM and a `tokio-tungstenite`
+//!   stream natively. The URL's scheme becomes `ws://` or `wss://`. Relative paths
+//!   only work on WASM, where they're resolved against the page's origin.
+//!   Requires the `ws` feature.
 //! * `returns(Type)` - Specify an overriding return type for your client side
-//! function. This must be either `String` or a deserializable type. It's mostly
-//! useful for handlers that return status codes or have an otherwise more
-//! complex return type.
+//!   function. This must be either `String` or a deserializable type. It's mostly
+//!   useful for handlers that return status codes or have an otherwise more
+//!   complex return type. `returns(json(Type))` spells out that `Type` is decoded
+//!   as JSON, for handlers whose `Json` is imported under a different name.
+//!   `returns(text(Type))` reads the body as text instead and parses it with
+//!   `FromStr`, for handlers returning a `String` the client wants as a number
+//!   or another parseable type. Parse errors are `wrpc::Error::Decode`.
+//!   `returns(status_json(Type))` does the same for handlers returning a
+//!   `(StatusCode, Json<Type>)` tuple, making the intent clear at a glance.
+//!   Generic types like `returns(Vec<User>)` or `returns(Option<User>)` work too.
+//!   The override always wins over the handler's return type, but overriding a
+//!   `String` or `&str` return with a JSON type is an error.
+//!   Tuple responses like `(StatusCode, Json<Type>)` or `(StatusCode, String)`
+//!   don't need an override, their single `Json`, `String` or `Bytes` element is
+//!   the body. Tuples with several of them need one.
+//! * `path_default(name = value, ...)` - Make the named path segments optional on
+//!   the client side. When `None` is passed, `value` is substituted in the URL.
+//! * `trailing_slash(keep|strip|add)` - Normalize a trailing `/` on the path to
+//!   match your router. Defaults to `keep`. The root path `/` is left untouched.
+//! * `leptos` / `leptos(ErrorType)` - Make the client function return
+//!   `Result<T, leptos::ServerFnError>` (or `Result<T, ErrorType>`) so it can be
+//!   used wherever a server function is expected. The error type must implement
+//!   `From` for the underlying client's error, and `Debug` with the `tracing`
+//!   feature.
+//! * `named_lifetimes` - Give all borrowed client arguments an explicit, shared
+//!   `'wrpc` lifetime instead of relying on elision.
+//! * `query(owned)` - Take the `Query` argument by value instead of by reference.
+//! * `body(owned)` - Take the `Json` body argument by value instead of by
+//!   reference, i.e. to pass a struct built inline. `Vec<T>` bodies are taken as
+//!   they are rather than as slices.
+//! * `returns_bincode(Type)` - Decode the response body with `bincode` instead of
+//!   JSON. The client function returns `Result<Type, wrpc::Error>`, since decoding
+//!   errors aren't covered by the HTTP client's error type.
+//! * `format(json|cbor|msgpack)` - Select the serialization format for structured
+//!   bodies and responses. Defaults to `json`. `cbor` and `msgpack` require the
+//!   feature of the same name, send `application/cbor` or `application/msgpack`
+//!   bodies and make the client function return `Result<T, wrpc::Error>`.
+//!   `Cbor<T>` and `MsgPack<T>` extractors are recognized like `Json<T>`.
+//!   Structured responses are requested with a matching `Accept` header, so
+//!   servers negotiating the content type answer in a format the client can
+//!   parse.
+//! * `expect_version("2")` - Fail the call with `wrpc::Error::Version` unless the
+//!   response carries a matching `X-Api-Version` header. Guards against clients
+//!   talking to a server built from a different version of the API.
+//! * `pretty_body` or `json(pretty)` - Serialize JSON request bodies with
+//!   `serde_json::to_string_pretty` instead of compactly, i.e. for readable request
+//!   logs during development. Other formats are unaffected.
+//! * `path_helper` - Also generate `<handler>_path(...)`, taking the path and
+//!   query arguments and returning the relative path the client would request.
+//!   Useful for links in WASM UIs.
+//! * `builder` - Also generate a `<Handler>Request` builder, i.e. `GetUserRequest`
+//!   for `get_user`, and an async `send()` calling the client function. `new(...)`
+//!   takes the required path and query arguments, the optional ones, i.e. an
+//!   `Option<Query<T>>` or segments with a `path_default`, have a setter each.
+//!   Only handlers whose client function takes nothing but path and `Query`
+//!   arguments are supported.
+//! * `request_helper` - Also generate `call_<handler>_request(...)`, taking the
+//!   same arguments as the client function and returning a
+//!   `wrpc::RequestDescription` of the method, URL, headers and serialized body
+//!   it would send, without sending anything. Meant for logging and replaying
+//!   requests. Multipart bodies aren't supported.
+//! * `raw` - Also generate `call_<handler>_raw(...)`, taking the same arguments as
+//!   the client function but returning a `wrpc::Response` as soon as it's
+//!   received, without reading the body. It exposes `.status()`, `.header()`,
+//!   `.json()`, `.text()` and `.bytes()` the same way on both targets. Not
+//!   supported by `native_client(ureq)`.
+//! * `allow_unknown_extractors` - Skip arguments that aren't a known extractor
+//!   instead of failing to compile. Useful for custom extractors that only make
+//!   sense on the server.
+//! * `extractors(json = Name, query = Name, path = Name)` - Recognize `Json`,
+//!   `Query` and `Path` under the names they're imported as, i.e.
+//!   `extractors(json = AxJson)` after `use axum::Json as AxJson`. Extractors
+//!   are otherwise detected by their own name, so aliases would be unknown.
+//! * `returns_headers` - Return the response headers alongside the body, as
+//!   `(wrpc::ClientHeaders, T)`. Handlers returning `(HeaderMap, T)` have their
+//!   body type detected from `T`.
+//! * `returns(headers(Type, ...))` - Only decode the listed response headers,
+//!   ignoring the body. The client function returns a generated
+//!   `<Name>Headers` struct with an `Option` field per header, named after
+//!   the snake cased type, i.e. `etag` and `last_modified` for
+//!   `returns(headers(ETag, LastModified))`. The types implement
+//!   `headers::Header`, so the crate needs the `headers` crate as a dependency.
+//!   Can't be combined with `returns_with_status`, `returns_headers` or
+//!   `returns_final_url`.
+//! * `fire_and_forget` - Return `()` as soon as the response status and headers
+//!   arrive, whatever the handler returns. The body is never read, which suits
+//!   ping and telemetry endpoints.
+//! * `retry(count, backoff_ms)` - Resend the request up to `count` times on
+//!   connection errors and 5xx responses, doubling the wait after each attempt.
+//!   Requires the `retry` feature. Only `get`, `put` and `delete` handlers are
+//!   retried unless `retry(count, backoff_ms, always)` is used.
+//! * `wasm_bindgen` - Additionally export the WASM client to JS as
+//!   `<handler>_js`, converting arguments and the result with
+//!   `serde_wasm_bindgen` instead of JSON strings. Requires `wasm-bindgen`,
+//!   `wasm-bindgen-futures` and `serde-wasm-bindgen` dependencies. Header
+//!   arguments and `returns_headers` aren't supported.
+//! * `#[path_fmt("{:x}")]` - An attribute on a single segment `Path` argument
+//!   rather than an `rpc` option. The client interpolates the segment with the
+//!   given format string, i.e. as hex.
+//! * `compress(gzip|deflate)` - Compress `String` and structured request bodies
+//!   and set the matching `Content-Encoding` header. Requires the `compress`
+//!   feature. The server has to decompress them itself, i.e. with
+//!   `tower-http`'s `RequestDecompressionLayer`. Multipart bodies aren't supported.
+//! * `raw_bytes_return` - Return the raw response body as `wrpc::ClientBytes`
+//!   (`bytes::Bytes` natively, `Vec<u8>` on WASM) instead of decoding it, whatever
+//!   the handler returns. Saves allocating a `String` for callers that parse the
+//!   body themselves.
+//! * `query_static("key" => value, ...)` - Append fixed query parameters after
+//!   the `Query` argument, if any. Values may be any expression implementing
+//!   `Display`, including client arguments, and are percent-encoded.
+//! * `cookies` - Send cookies along with the request. The client function takes
+//!   an extra `cookie: Option<&str>` argument that's sent as the `Cookie` header
+//!   natively. Without one, the cookies set by earlier responses are sent, since
+//!   these handlers share a client with a cookie store. Requires the `cookies`
+//!   feature. On WASM the argument is ignored and the browser's cookies
+//!   are sent with `credentials: include` instead.
+//! * `etag` - Make conditional requests. The client function takes an extra
+//!   `etag: Option<&str>` argument that's sent as the `If-None-Match` header and
+//!   returns `Option<T>`, which is `None` for a `304 Not Modified` response, i.e.
+//!   when the caller's cached copy is still current.
+//! * `header_map` - Add an `extra_headers: &wrpc::ClientHeaders` argument whose
+//!   headers are merged into the request, i.e. for headers shared by many calls.
+//!   Not to be confused with handlers taking a `HeaderMap`, whose client functions
+//!   take a `&[(&str, &str)]` list instead.
+//! * `locale` - Add a `locale: &str` argument that's sent as the
+//!   `Accept-Language` header, i.e. `"de-CH, de;q=0.9"`, for handlers that
+//!   localize their response.
+//! * `host` - Add a leading `host: &str` argument and request
+//!   `https://{host}{path}`, for APIs served under several hosts, i.e. one per
+//!   tenant. Handlers can read the host with `axum::extract::Host`, which isn't a
+//!   client argument. Can't be combined with `prefix(...)`, `ws` or absolute
+//!   URLs.
+//! * `extra_query` - Add an `extra_query: &[(&str, &str)]` argument whose pairs are
+//!   percent-encoded and appended to the query, i.e. for parameters the handler
+//!   doesn't extract, like tracking or feature flags. They follow the `Query`
+//!   argument's pairs, if there are any.
+//! * `credentials(include|same_origin|omit)` - Set the WASM request's
+//!   `credentials` mode, i.e. `include` to send cookies to a cross-origin API.
+//!   Unset by default, leaving the browser's default. Ignored natively. Takes
+//!   precedence over the `include` implied by `cookies`.
+//! * `returns_final_url` - Return the URL the response came from after following
+//!   redirects alongside the body, as `(String, T)`. Combined with
+//!   `returns_headers` the client function returns
+//!   `(wrpc::ClientHeaders, String, T)`.
+//! * `returns_with_status` - Return the response's status code alongside the
+//!   body, as `(wrpc::StatusCode, T)`, i.e. to tell `201 Created` from `200 OK`.
+//!   It comes first when combined with `returns_headers` or `returns_final_url`.
+//! * `error(ErrType)` - Decode the JSON body of non-2xx responses as `ErrType` and
+//!   fail with `wrpc::Error::Api` carrying it. The client function returns
+//!   `Result<T, wrpc::Error<ErrType>>`. Without this option, `wrpc::Error` never
+//!   holds an API error. With the `tracing` feature, `ErrType` must implement
+//!   `Debug` as well, failed calls are logged with their error.
+//! * `graphql` - Generate a GraphQL-over-HTTP client. It takes a `query: &str`
+//!   and `variables: &impl Serialize` instead of the handler's body, sends them
+//!   as `{"query": ..., "variables": ...}` and decodes the `data` field of the
+//!   response into the return type, usually set with `returns(T)`. Requires the
+//!   `graphql` feature.
+//! * `query_encoding(qs|urlencoded)` - Serialize the `Query` argument with
+//!   `serde_qs` or `serde_urlencoded`. Defaults to `urlencoded`, which is what
+//!   axum's `Query` extractor parses, and the crate must be a dependency of the
+//!   calling crate. Neither works for nested structs with axum's `Query`:
+//!   `serde_urlencoded` panics serializing them, while `serde_qs` encodes them as
+//!   `outer[inner]=value`, which only parses with a `serde_qs` based extractor
+//!   on the server.
+//! * `query_array(repeat|brackets)` - Serialize sequence fields of the `Query`
+//!   argument, e.g. `ids: Vec<u32>`, as repeated keys instead, i.e. `ids=1&ids=2`
+//!   for `repeat` and `ids[]=1&ids[]=2` for `brackets`. Takes precedence over
+//!   `query_encoding(...)`. axum's own `Query` can't deserialize either, but
+//!   axum-extra's `Query` parses `repeat` and a `serde_qs` based one `brackets`.
+//! * `query_key("filter")` - Nest the serialized `Query` argument under a key, i.e.
+//!   `filter[page]=1` instead of `page=1`, for handlers extracting the query as a
+//!   field of a larger struct. Needs a `serde_qs` style extractor on the server.
+//! * `prefix(CONST)` - Prepend a shared prefix, i.e. a `&str` const like
+//!   `API_PREFIX = "/api/v1"`, to the path of the client function. They're
+//!   joined with exactly one `/`. The path itself must not be an absolute URL,
+//!   and `inventory` routes and the OpenAPI description keep the unprefixed path,
+//!   so nest those routes under the prefix on the server.
+//! * `default_headers("Name=value", ...)` - Send fixed headers, like
+//!   `"X-Client=wrpc"`, with every request. They're set before any header
+//!   arguments.
+//! * `returns(stream)` - Return the response body as a stream of chunks instead
+//!   of buffering it, as
+//!   `impl wrpc::Stream<Item = wrpc::Result<bytes::Bytes>> + Send`. Requires the
+//!   `stream` feature and is only supported natively for now, the
+//!   WASM build fails with a compile error. Stream clients aren't replaced by
+//!   `mock`.
+//! * `returns(ndjson(T))` - Decode a newline delimited JSON body line by line, as
+//!   `impl wrpc::Stream<Item = Result<T, wrpc::Error>> + Send`. Sends
+//!   `Accept: application/x-ndjson`. A line that fails to decode is yielded as
+//!   `wrpc::Error::Decode` without ending the stream. Same restrictions as
+//!   `returns(stream)`.
+//! * `content_type("...")` - Send the body with this `Content-Type`, e.g.
+//!   `text/csv` for a `String` body. Overrides the type implied by `format(...)`
+//!   for structured bodies. Only allowed with `post`, `put`, `patch` and `delete`, and not
+//!   with multipart bodies.
+//! * `merge_patch` - Send the body of a `patch` handler as a JSON Merge Patch
+//!   (RFC 7396), with `Content-Type: application/merge-patch+json`. Only allowed
+//!   with `patch`, and not together with `content_type("...")`.
+//! * `accept("...")` - Send this `Accept` header instead of the one implied by
+//!   `format(...)`, or for responses that don't imply one, like text. An `Accept`
+//!   in `default_headers(...)` takes precedence over both.
+//! * `vis(pub)` - Give the client functions and the items generated alongside
+//!   them this visibility instead of the handler's, i.e. a `pub(crate)` handler
+//!   with a `pub` client function for use from other crates.
+//! * `path_struct(Params { name: Type, ... })` - Declare the fields of a
+//!   `Path<Params>` struct argument, whose definition the macro can't see. The
+//!   client function takes one argument per field, which is interpolated into
+//!   the path segment of the same name. Every field must be a segment and every
+//!   segment a field.
+//! * `native_client(reqwest|ureq)` - Select the HTTP client of the native client
+//!   function. Defaults to `reqwest`. With `ureq`, the native client function
+//!   is synchronous and returns `Result<T, wrpc::Error>`, while the WASM one is
+//!   unchanged. Requires the `ureq` feature and doesn't support `retry`,
+//!   `error(...)`, `expect_version`, `returns_with_status`, `returns_headers`,
+//!   `returns_final_url`, `returns(stream)`, `returns(ndjson(T))`,
+//!   `returns(headers(...))`, `cookies`, `etag`, `header_map`, `builder`,
+//!   `raw`, `leptos` or multipart bodies.
+//!   Unlike `reqwest`, `ureq` fails with `wrpc::Error::Ureq` on non-2xx
+//!   responses.
+//! * `propagate_trace` - Inject the trace context of the current `tracing` span
+//!   into the request headers of the native client functions, e.g. `traceparent`
+//!   with the W3C Trace Context propagator. Uses whatever propagator is
+//!   registered with `opentelemetry::global`. Requires the `trace` feature and is
+//!   ignored on WASM.
+//! * `client_only` - Omit the handler entirely and only generate the client
+//!   functions, whatever the target. For handlers shared with client crates that
+//!   shouldn't depend on `axum` or the handler's other server side dependencies.
+//!   Without a handler, nothing is registered by `inventory`, `openapi` or
+//!   `ts-export`.
+//!
+//! # Features
+//!
+//! * `probe` - Additionally generates a `<handler>_probe(base, ...)` function in
+//!   test builds that sends a single request to a server running at `base` and
+//!   returns the round trip latency. The response is ignored.
+//! * `inventory` - Registers every handler's method, path and route with
+//!   `inventory` on non-WASM targets. `wrpc::routes::router()` assembles all
+//!   registered routes into an `axum::Router`. Only stateless routers are
+//!   supported for now.
+//! * `cbor`, `msgpack` - Pull in `ciborium` and `rmp-serde` respectively, as
+//!   needed by `format(cbor)` and `format(msgpack)`.
+//! * `multipart` - Required for handlers taking `Multipart`. Enables
+//!   `reqwest`'s `multipart` feature natively and uses the browser's `FormData`
+//!   on WASM.
+//! * `retry` - Required by `retry(...)`. Waits with `tokio::time` natively and
+//!   `gloo-timers` on WASM.
+//! * `compress` - Required by `compress(...)`. Compresses bodies with `flate2`.
+//! * `openapi` - Registers a description of every handler's method, path and
+//!   parameters on non-WASM targets. `wrpc::openapi::collect()` assembles them
+//!   into an OpenAPI `paths` object. Types are only named, under `x-rust-type`.
+//! * `ts-export` - Registers how every handler's request is made on non-WASM
+//!   targets. `wrpc::ts::emit()` assembles them into a `.ts` module of `fetch`
+//!   wrappers taking the client functions' arguments, i.e. from a build script.
+//!   Typed headers are passed as their encoded value. Named types are declared
+//!   as `unknown`, or from their JSON schema with the `schemars` feature, which
+//!   requires them to implement `schemars::JsonSchema`. Handlers whose request
+//!   depends on Rust code are skipped, i.e. generic ones and those using a
+//!   binary `format`, `compress`, `prefix`, `query_static`, `path_default`,
+//!   `#[path_fmt]`, `query_key`, `query_encoding(qs)` or `query_array(brackets)`.
+//! * `cookies` - Required by `cookies`. Enables `reqwest`'s `cookies` feature.
+//! * `mock` - Replaces every client function with a stub in test builds, which
+//!   returns the response registered with `wrpc::mock::set_response` or
+//!   `wrpc::mock::set_error` for its handler instead of sending a request. The
+//!   error has to be the client function's error type, so mocked errors are
+//!   easiest with functions returning `wrpc::Error`.
+//! * `spawn` - Additionally generates `spawn_call_<handler>(..., on_result)` on
+//!   WASM, which runs the client function with `wasm_bindgen_futures::spawn_local`
+//!   and passes its result to `on_result`. Borrowed arguments must be `'static`,
+//!   since the call outlives the helper.
+//! * `prefetch` - Additionally generates `prefetch_<handler>(...)` on WASM for `get`
+//!   handlers, taking the path and query arguments and returning a
+//!   `<link rel="prefetch">` element for the URL the client function would
+//!   request. Append it to `<head>` to have the browser fetch the response ahead
+//!   of time, i.e. while hydrating a server rendered page.
+//! * `debug_bodies` - Decode JSON responses by hand instead of leaving it to the
+//!   HTTP client, so a body that fails to decode ends up in the error. The
+//!   source of `wrpc::Error::Decode` is a `wrpc::BodyError` with the first 256
+//!   characters of the body. Client functions returning JSON return
+//!   `Result<T, wrpc::Error>` instead of `wrpc::Result<T>`.
+//! * `graphql` - Required by `graphql`. Provides the request and response
+//!   envelopes in `wrpc::graphql`.
+//! * `stream` - Required by `returns(stream)` and `returns(ndjson(T))`. Enables
+//!   `reqwest`'s `stream` feature and re-exports `futures_core::Stream` as
+//!   `wrpc::Stream`.
+//! * `blocking` - Additionally generates a synchronous `call_<name>_blocking`
+//!   client function using `reqwest::blocking`, for native callers outside an
+//!   async runtime. It's skipped for handlers using `retry`, `error(...)`,
+//!   `expect_version`, `returns_with_status`, `returns_headers`,
+//!   `returns_final_url`, `returns(headers(...))`, `etag`, multipart bodies or
+//!   `returns(stream)` and `returns(ndjson(T))`, and isn't replaced by `mock`.
+//! * `client_only` - Makes the `client_only` option the default for every
+//!   handler. Cargo unifies features, so this also drops the handlers of a server
+//!   built alongside the client, i.e. in the same workspace. The option itself
+//!   doesn't have that problem.
+//! * `ureq` - Required by `native_client(ureq)`. Pulls in `ureq` with its `json`
+//!   feature on non-WASM targets.
+//! * `trace` - Required by `propagate_trace`. Pulls in `opentelemetry`, `tracing`
+//!   and `tracing-opentelemetry` on non-WASM targets.
+//! * `tracing` - Runs every async client function in an `info` span named
+//!   `call_<handler>`, with the method, the path and the response status as
+//!   fields, and logs whether the call succeeded and how long it took. Failed
+//!   calls are logged with their error, so the error types of `error(...)` and
+//!   `leptos(...)` must implement `Debug`. The `ureq` and blocking clients
+//!   aren't instrumented.
+//! * `ws` - Required by `ws(path)`. Pulls in `gloo-net` on WASM and
+//!   `tokio-tungstenite` on other targets.
+//!
+//! # Targets
+//!
+//! The handler and the two client functions are gated so that exactly one
+//! client function and at most one handler exist for every build. The handler
+//! is only removed when building for WASM with `--cfg client`, or everywhere
+//! with `client_only`.
+//!
+//! | `wasm32` | `--cfg client` | handler | client function |
+//! |----------|----------------|---------|-----------------|
+//! | no       | no             | yes     | `reqwest`       |
+//! | no       | yes            | yes     | `reqwest`       |
+//! | yes      | no             | yes     | `reqwasm`       |
+//! | yes      | yes            | no      | `reqwasm`       |
+//!
+//! The `reqwasm` client function is `#[doc(hidden)]` under `--cfg docsrs`. docs.rs
+//! builds for a native target, so it documents the `reqwest` client instead.
+//!
+//! Any `#[cfg(...)]` attributes on the handler itself are copied onto the
+//! generated client code as well, so a handler behind `#[cfg(feature = "admin")]`
+//! only gets a client function when that feature is enabled.
+//!
+//! Both clients follow redirects, `reqwest` up to 10 of them and the browser's
+//! `fetch` by its own rules, and decode the final response. A handler
+//! redirecting to a JSON resource only needs `returns(T)` for its client to
+//! return the resource.
+//!
+//! The native client futures are `Send` whenever their arguments are, so they
+//! can be spawned on multi-threaded runtimes.
+//!
+//! # Debugging
+//!
+//! * `WRPC_DUMP_DIR` - When set at build time, the generated client code of every
+//!   handler is formatted with `prettyplease` and written to
+//!   `<dir>/<handler>.rs`. Handlers with the same name overwrite each other.
 //!
 //! # Requirements
 //!
 //! * Path inputs with multiple segments must be destructured. This is because
-//! the macro separates these parameters into separate arguments to the client
-//! side function and needs their names.
+//!   the macro separates these parameters into separate arguments to the client
+//!   side function and needs their names.
+//! * The `Path` argument must extract exactly as many values as the path has
+//!   `:param` and `*wildcard` segments, which are substituted by name.
 //! * Text body inputs must be `String`s
-//! * All request-derived inputs must be `Json`, `Query`, `Path` or `String`.
-//! Any other arguments are assumed to be state derived and skipped.
-//! * The return type must be `Json` or `String`/`&str`. `&str` will be turned
-//! into `String` on the client side.
+//! * `Json<Vec<T>>` inputs are taken as `&[T]` on the client side.
+//! * `Json` and `Query` input types must implement `Serialize` and `Json` return
+//!   types `DeserializeOwned`, for the client. Missing impls are reported at the
+//!   type in the handler's signature, except in generic handlers.
+//! * `Option<Query<T>>` and `Option<Json<T>>` inputs become `Option<&T>`. The
+//!   query string or body is only sent for `Some`, and a query without any pairs
+//!   doesn't leave a trailing `?` either.
+//! * `RawQuery` inputs become `Option<&str>`, appended to the URL verbatim after a
+//!   `?` for `Some`. A handler can't take both `Query` and `RawQuery`.
+//! * All request-derived inputs must be `Json`, `Query`, `RawQuery`, `Path`, `String`,
+//!   `TypedHeader`, `HeaderMap` or `Multipart`. `State` and `Extension` arguments are server
+//!   side only and skipped. Any other argument is an error, unless the
+//!   `allow_unknown_extractors` flag is set, in which case it's skipped too. A
+//!   `HeaderMap` becomes a list of `(name, value)` pairs on the client side, a
+//!   `Multipart` a list of `wrpc::multipart::Part`s, i.e. `(name, contents)`.
+//!   A `TypedHeader<T>` becomes `&T`, encoded with `Header::encode`.
+//!   `reqwasm` and `ureq` only take string values, so calls with a value that
+//!   isn't valid UTF-8 fail instead of sending it.
+//! * The return type must be `Json`, `String`/`&str`, `StatusCode` or `()`.
+//!   Extractors and return types are detected by the last segment of their path,
+//!   so `axum::Json<T>` works. Aliased imports like `use axum::Json as J` need
+//!   `extractors(json = J)`.
+//!   `&str` will be turned into `String` on the client side. `StatusCode` and
+//!   `()` produce a client returning `()` that discards the response body. Use
+//!   `returns(())` for other body-less responses. Any other return type, like
+//!   `Response` or `impl IntoResponse`, is an error unless `returns(...)` names
+//!   the type the client gets.
 //! * The full path to the API handler must be specified. wrpc currently can't
-//! have access to your Router, so paths are unknown to the macro.
+//!   have access to your Router, so paths are unknown to the macro.
+//! * Generic parameters and where clauses of the handler are kept on the client
+//!   function, so any bounds the body needs (e.g. `Serialize`) must be spelled
+//!   out there. Generic handlers can't be used with `wasm_bindgen` or
+//!   `inventory`.
+//! * Inputs may appear in any order in the handler. The client function always
+//!   takes path segments first, then the query, the body and finally headers, so
+//!   its parameter order doesn't depend on the handler's.
 //!
 //! # Kitchen Sink Example
 //!
@@ -80,9 +472,153 @@
 //! ```
 //!
 
+use serde::{Deserialize, Serialize};
 pub use wrpc_macro::rpc;
 
+#[cfg(feature = "cbor")]
+#[doc(hidden)]
+pub use ciborium;
+#[cfg(all(
+    any(feature = "inventory", feature = "openapi", feature = "ts-export"),
+    not(target_arch = "wasm32")
+))]
+#[doc(hidden)]
+pub use inventory;
+#[cfg(feature = "msgpack")]
+#[doc(hidden)]
+pub use rmp_serde;
+#[cfg(all(feature = "spawn", target_arch = "wasm32"))]
+#[doc(hidden)]
+pub use wasm_bindgen_futures;
+#[cfg(all(feature = "openapi", not(target_arch = "wasm32")))]
+pub mod openapi;
+#[cfg(all(feature = "inventory", not(target_arch = "wasm32")))]
+pub mod routes;
+
+#[cfg(feature = "compress")]
+pub mod compress;
+#[cfg(all(feature = "cookies", not(target_arch = "wasm32")))]
+pub mod cookies;
+mod error;
+#[cfg(feature = "graphql")]
+pub mod graphql;
+#[cfg(feature = "tracing")]
+pub mod instrument;
+#[cfg(feature = "mock")]
+pub mod mock;
+#[cfg(feature = "multipart")]
+pub mod multipart;
+#[cfg(all(feature = "stream", not(target_arch = "wasm32")))]
+pub mod ndjson;
+#[cfg(all(feature = "prefetch", target_arch = "wasm32"))]
+pub mod prefetch;
+pub mod query;
+#[cfg(target_arch = "wasm32")]
+pub mod request;
+pub mod response;
+#[cfg(feature = "retry")]
+pub mod retry;
+#[cfg(all(feature = "trace", not(target_arch = "wasm32")))]
+pub mod trace;
+#[cfg(all(feature = "ts-export", not(target_arch = "wasm32")))]
+pub mod ts;
+#[cfg(all(feature = "ureq", not(target_arch = "wasm32")))]
+pub mod ureq;
+#[cfg(feature = "ws")]
+pub mod ws;
+
+#[cfg(feature = "debug_bodies")]
+pub use error::BodyError;
+pub use error::Error;
+/// The trait of streamed response bodies, for `returns(stream)`.
+#[cfg(all(feature = "stream", not(target_arch = "wasm32")))]
+pub use futures_core::Stream;
+pub use response::Response;
+
+/// The error type of the underlying HTTP client.
+#[cfg(target_arch = "wasm32")]
+pub type ClientError = ::reqwasm::Error;
+/// The error type of the underlying HTTP client.
+#[cfg(not(target_arch = "wasm32"))]
+pub type ClientError = ::reqwest::Error;
+
+/// The response type of the underlying HTTP client.
+#[cfg(target_arch = "wasm32")]
+pub type ClientResponse = ::reqwasm::http::Response;
+/// The response type of the underlying HTTP client.
+#[cfg(not(target_arch = "wasm32"))]
+pub type ClientResponse = ::reqwest::Response;
+
+/// The response headers type of the underlying HTTP client.
+#[cfg(target_arch = "wasm32")]
+pub type ClientHeaders = ::reqwasm::http::Headers;
+/// The response headers type of the underlying HTTP client.
+#[cfg(not(target_arch = "wasm32"))]
+pub type ClientHeaders = ::reqwest::header::HeaderMap;
+
+/// The raw response body type of the underlying HTTP client.
 #[cfg(target_arch = "wasm32")]
-pub type Result<T> = std::result::Result<T, ::reqwasm::Error>;
+pub type ClientBytes = ::std::vec::Vec<u8>;
+/// The raw response body type of the underlying HTTP client.
 #[cfg(not(target_arch = "wasm32"))]
-pub type Result<T> = std::result::Result<T, ::reqwest::Error>;
+pub type ClientBytes = ::bytes::Bytes;
+
+pub type Result<T> = std::result::Result<T, ClientError>;
+
+/// The status code of a response, returned by client functions of
+/// `returns_with_status`. `reqwasm` only exposes the number, so this wraps it
+/// the same way on both targets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct StatusCode(pub u16);
+
+impl StatusCode {
+    pub fn as_u16(self) -> u16 {
+        self.0
+    }
+
+    /// Whether the status is in the 2xx range.
+    pub fn is_success(self) -> bool {
+        (200..300).contains(&self.0)
+    }
+}
+
+impl std::fmt::Display for StatusCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A request as a client function would send it, returned by the
+/// `call_<handler>_request` helper of `request_helper` for logging and replay.
+/// Headers are in the order they're set and the body is already serialized.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RequestDescription {
+    /// The HTTP method, i.e. `GET`.
+    pub method: String,
+    /// The URL, relative unless the handler's path is absolute.
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<Vec<u8>>,
+}
+
+impl RequestDescription {
+    /// The caller supplied `extra_headers` of `header_map` as pairs.
+    #[doc(hidden)]
+    #[cfg(target_arch = "wasm32")]
+    pub fn header_pairs(headers: &ClientHeaders) -> Vec<(String, String)> {
+        headers.entries().collect()
+    }
+
+    /// The caller supplied `extra_headers` of `header_map` as pairs.
+    #[doc(hidden)]
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn header_pairs(headers: &ClientHeaders) -> Vec<(String, String)> {
+        headers
+            .iter()
+            .map(|(name, value)| {
+                let value = value.to_str().unwrap_or_default();
+                (name.to_string(), value.to_string())
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,64 @@
+//! Canned responses for client functions in test builds.
+//!
+//! With the `mock` feature enabled, every client function is replaced by a
+//! stub under `cfg(test)` that returns the response registered for its
+//! handler instead of sending a request. Each registered response is used by
+//! a single call.
+//!
+//! ```
+//! # #[derive(Debug, PartialEq)]
+//! # struct User;
+//! wrpc::mock::set_response("get_user", User);
+//! // `call_get_user(1).await` now returns `Ok(User)` in tests
+//! # assert_eq!(wrpc::mock::response::<User, wrpc::Error>("get_user").unwrap(), User);
+//! ```
+
+use std::{any::Any, cell::RefCell, collections::HashMap};
+
+enum Response {
+    Ok(Box<dyn Any>),
+    Err(Box<dyn Any>),
+}
+
+thread_local! {
+    static RESPONSES: RefCell<HashMap<String, Response>> = RefCell::new(HashMap::new());
+}
+
+/// Makes the next call of the client function for `handler` succeed with
+/// `value`.
+pub fn set_response<T: 'static>(handler: &str, value: T) {
+    RESPONSES.with(|responses| {
+        let response = Response::Ok(Box::new(value));
+        responses.borrow_mut().insert(handler.to_string(), response);
+    });
+}
+
+/// Makes the next call of the client function for `handler` fail with
+/// `error`, which must be the function's error type.
+pub fn set_error<E: 'static>(handler: &str, error: E) {
+    RESPONSES.with(|responses| {
+        let response = Response::Err(Box::new(error));
+        responses.borrow_mut().insert(handler.to_string(), response);
+    });
+}
+
+/// Removes all registered responses.
+pub fn clear() {
+    RESPONSES.with(|responses| responses.borrow_mut().clear());
+}
+
+/// Takes the response registered for `handler`. Used by the generated stubs.
+///
+/// # Panics
+///
+/// If no response is registered or it doesn't have the expected type.
+#[doc(hidden)]
+pub fn response<T: 'static, E: 'static>(handler: &str) -> Result<T, E> {
+    let response = RESPONSES.with(|responses| responses.borrow_mut().remove(handler));
+    let value = match response {
+        Some(Response::Ok(value)) => value.downcast().map(|value| Ok(*value)),
+        Some(Response::Err(error)) => error.downcast().map(|error| Err(*error)),
+        None => panic!("No mock response registered for `{handler}`"),
+    };
+    value.unwrap_or_else(|_| panic!("Mock response for `{handler}` has the wrong type"))
+}
@@ -0,0 +1,26 @@
+//! Form building for handlers taking `Multipart`. Native clients send a
+//! `reqwest::multipart::Form`, WASM clients let the browser encode `FormData`.
+
+/// A single `multipart/form-data` field, as `(name, contents)`.
+pub type Part = (String, Vec<u8>);
+
+/// Builds the form sent by a `Multipart` client function.
+#[cfg(target_arch = "wasm32")]
+pub fn form(parts: Vec<Part>) -> ::web_sys::FormData {
+    let form = ::web_sys::FormData::new().unwrap();
+    for (name, contents) in parts {
+        let contents = ::js_sys::Array::of1(&::js_sys::Uint8Array::from(&contents[..]));
+        let blob = ::web_sys::Blob::new_with_u8_array_sequence(&contents).unwrap();
+        form.append_with_blob(&name, &blob).unwrap();
+    }
+    form
+}
+
+/// Builds the form sent by a `Multipart` client function.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn form(parts: Vec<Part>) -> ::reqwest::multipart::Form {
+    parts.into_iter().fold(
+        ::reqwest::multipart::Form::new(),
+        |form, (name, contents)| form.part(name, ::reqwest::multipart::Part::bytes(contents)),
+    )
+}
@@ -0,0 +1,72 @@
+//! Newline delimited JSON bodies, for `returns(ndjson(T))`.
+
+use std::{
+    marker::PhantomData,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use futures_core::Stream;
+use serde::de::DeserializeOwned;
+
+use crate::{ClientBytes, ClientError, Error};
+
+type Chunks = Pin<Box<dyn Stream<Item = Result<ClientBytes, ClientError>> + Send>>;
+
+/// Decodes each line of `chunks` as a `T`. Lines may span chunks, and blank
+/// lines are skipped.
+pub fn lines<T: DeserializeOwned>(
+    chunks: impl Stream<Item = Result<ClientBytes, ClientError>> + Send + 'static,
+) -> Lines<T> {
+    Lines {
+        chunks: Box::pin(chunks),
+        buffer: Vec::new(),
+        done: false,
+        item: PhantomData,
+    }
+}
+
+/// The stream returned by [`lines`]. A line that fails to decode is yielded
+/// as `Error::Decode`, the lines after it are still read.
+pub struct Lines<T> {
+    chunks: Chunks,
+    /// The start of a line whose end hasn't arrived yet.
+    buffer: Vec<u8>,
+    done: bool,
+    item: PhantomData<fn() -> T>,
+}
+
+impl<T: DeserializeOwned> Stream for Lines<T> {
+    type Item = Result<T, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(end) = self.buffer.iter().position(|&byte| byte == b'\n') {
+                let line = self.buffer.drain(..=end).collect::<Vec<_>>();
+                match decode(&line) {
+                    Some(item) => return Poll::Ready(Some(item)),
+                    None => continue,
+                }
+            }
+            if self.done {
+                // The last line needn't end in a newline
+                let line = std::mem::take(&mut self.buffer);
+                return Poll::Ready(decode(&line));
+            }
+            match ready!(self.chunks.as_mut().poll_next(cx)) {
+                Some(Ok(chunk)) => self.buffer.extend_from_slice(&chunk),
+                Some(Err(err)) => return Poll::Ready(Some(Err(Error::Request(err)))),
+                None => self.done = true,
+            }
+        }
+    }
+}
+
+/// Decodes a single line, or `None` if it's blank.
+fn decode<T: DeserializeOwned>(line: &[u8]) -> Option<Result<T, Error>> {
+    let line = line.trim_ascii();
+    if line.is_empty() {
+        return None;
+    }
+    Some(serde_json::from_slice(line).map_err(Error::decode))
+}
@@ -0,0 +1,109 @@
+//! OpenAPI path entries for handlers annotated with `#[rpc]`.
+//!
+//! With the `openapi` feature enabled, every handler registers a description
+//! of its method, path and parameters here. Types are described by their
+//! Rust name under `x-rust-type`, since wrpc doesn't know their schema.
+//!
+//! ```
+//! # #[cfg(not(target_arch = "wasm32"))]
+//! let paths: serde_json::Value = wrpc::openapi::collect();
+//! ```
+
+use serde_json::{json, Map, Value};
+
+/// A single handler registered by `#[rpc]`.
+pub struct Operation {
+    pub name: &'static str,
+    pub method: &'static str,
+    pub path: &'static str,
+    pub parameters: &'static [Parameter],
+}
+
+/// An argument of the client function.
+pub struct Parameter {
+    pub name: &'static str,
+    pub location: Location,
+    pub ty: &'static str,
+    pub required: bool,
+}
+
+/// Where a parameter is sent.
+pub enum Location {
+    Path,
+    Query,
+    /// A header, with a function returning its name. The name isn't known at
+    /// compile time, so the Rust argument name can't stand in for it.
+    Header(fn() -> &'static str),
+    /// The request body, with its content type.
+    Body(&'static str),
+}
+
+inventory::collect!(Operation);
+
+/// Iterates over all registered operations.
+pub fn iter() -> impl Iterator<Item = &'static Operation> {
+    inventory::iter::<Operation>.into_iter()
+}
+
+/// Assembles all registered operations into an OpenAPI `paths` object.
+/// Path segments like `:id` are rewritten to `{id}`.
+pub fn collect() -> Value {
+    let mut paths = Map::new();
+    for operation in iter() {
+        let path = paths
+            .entry(template(operation.path))
+            .or_insert_with(|| Value::Object(Map::new()));
+        path[operation.method] = describe(operation);
+    }
+    Value::Object(paths)
+}
+
+fn describe(operation: &Operation) -> Value {
+    let mut parameters = Vec::new();
+    let mut request_body = None;
+    for parameter in operation.parameters {
+        let schema = json!({ "x-rust-type": parameter.ty });
+        let mut name = parameter.name;
+        let location = match parameter.location {
+            Location::Path => "path",
+            Location::Query => "query",
+            Location::Header(header_name) => {
+                name = header_name();
+                "header"
+            }
+            Location::Body(content_type) => {
+                request_body = Some(json!({
+                    "required": parameter.required,
+                    "content": { content_type: { "schema": schema } },
+                }));
+                continue;
+            }
+        };
+        parameters.push(json!({
+            "name": name,
+            "in": location,
+            "required": parameter.required,
+            "schema": schema,
+        }));
+    }
+
+    let mut description = json!({
+        "operationId": operation.name,
+        "parameters": parameters,
+    });
+    if let Some(request_body) = request_body {
+        description["requestBody"] = request_body;
+    }
+    description
+}
+
+/// Rewrites axum's `:name` and `*name` segments to OpenAPI's `{name}`.
+fn template(path: &str) -> String {
+    path.split('/')
+        .map(|segment| match segment.strip_prefix([':', '*']) {
+            Some(name) => format!("{{{name}}}"),
+            None => segment.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
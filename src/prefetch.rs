@@ -0,0 +1,20 @@
+//! `<link rel="prefetch">` elements for `GET` handlers, created by the
+//! `prefetch_<handler>` functions of the `prefetch` feature.
+
+use web_sys::wasm_bindgen::JsCast;
+pub use web_sys::HtmlLinkElement;
+
+/// Creates a `<link rel="prefetch">` element for `href`. It isn't attached to
+/// the document yet, the prefetch starts once it's appended to `<head>`.
+pub fn link(href: &str) -> HtmlLinkElement {
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .expect("`prefetch` needs a document");
+    let link = document
+        .create_element("link")
+        .expect("`link` is a valid element name")
+        .unchecked_into::<HtmlLinkElement>();
+    link.set_rel("prefetch");
+    link.set_href(href);
+    link
+}
@@ -0,0 +1,113 @@
+//! Encoding for the values of `query_static(...)`, optional queries,
+//! `query_array(...)`, `query_key(...)` and `extra_query`.
+
+use std::fmt::{Display, Write};
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// Percent-encodes the string form of `value` for use in a query string.
+/// Only ASCII letters, digits, `-`, `_`, `.` and `~` are left as they are.
+pub fn encode(value: &impl Display) -> String {
+    let mut encoded = String::new();
+    for byte in value.to_string().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            byte => write!(encoded, "%{byte:02X}").unwrap(),
+        }
+    }
+    encoded
+}
+
+/// The `?`-prefixed query string of an `Option<Query<T>>` argument. It's empty
+/// for `None` or a query without any pairs, so the URL never ends in a bare
+/// `?`. With `more`, `query_static(...)` pairs follow, which need a `&` after
+/// the query or a `?` of their own.
+pub fn optional(query: Option<String>, more: bool) -> String {
+    match (query.filter(|query| !query.is_empty()), more) {
+        (Some(query), false) => format!("?{query}"),
+        (Some(query), true) => format!("?{query}&"),
+        (None, false) => String::new(),
+        (None, true) => "?".to_string(),
+    }
+}
+
+/// Appends the caller supplied `extra_query` pairs to `path`, after a `&` if it
+/// already has a query or a `?` otherwise. Keys and values are percent-encoded.
+pub fn append(path: &str, extra: &[(&str, &str)]) -> String {
+    let mut path = path.to_string();
+    for (key, value) in extra {
+        path.push(if path.contains('?') { '&' } else { '?' });
+        path += &encode(key);
+        path.push('=');
+        path += &encode(value);
+    }
+    path
+}
+
+/// Nests every pair of a serialized query under `key`, for `query_key(...)`.
+/// `page=1` becomes `filter[page]=1` and `ids[]=1` becomes `filter[ids][]=1`.
+pub fn nest(key: &str, query: &str) -> String {
+    let key = encode(&key);
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, rest) = pair.split_at(pair.find(['[', '=']).unwrap_or(pair.len()));
+            format!("{key}[{name}]{rest}")
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// How `query_array(...)` spells the items of sequence fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arrays {
+    /// Repeats the key, as in `id=1&id=2`.
+    Repeat,
+    /// Repeats the key with empty brackets, as in `id[]=1&id[]=2`.
+    Brackets,
+}
+
+/// Serializes the `Query` argument of `query_array(...)`, with every item of a
+/// sequence field as a pair of its own, spelled as `arrays` says. `None` fields
+/// are skipped and nested structs become `outer[inner]=value`. Keys end up
+/// sorted rather than in field order.
+///
+/// # Panics
+///
+/// If `query` doesn't serialize to a map, like a struct or `HashMap` would.
+pub fn to_string(query: &impl Serialize, arrays: Arrays) -> String {
+    let Ok(Value::Object(fields)) = serde_json::to_value(query) else {
+        panic!("`query_array` queries must serialize to a map");
+    };
+    let mut pairs = Vec::new();
+    for (key, value) in fields {
+        push_pairs(&mut pairs, encode(&key), value, arrays);
+    }
+    pairs.join("&")
+}
+
+fn push_pairs(pairs: &mut Vec<String>, key: String, value: Value, arrays: Arrays) {
+    match value {
+        Value::Null => {}
+        Value::Array(items) => {
+            let key = match arrays {
+                Arrays::Repeat => key,
+                Arrays::Brackets => format!("{key}[]"),
+            };
+            for item in items {
+                push_pairs(pairs, key.clone(), item, arrays);
+            }
+        }
+        Value::Object(fields) => {
+            for (field, value) in fields {
+                push_pairs(pairs, format!("{key}[{}]", encode(&field)), value, arrays);
+            }
+        }
+        Value::String(value) => pairs.push(format!("{key}={}", encode(&value))),
+        value => pairs.push(format!("{key}={value}")),
+    }
+}
@@ -0,0 +1,18 @@
+//! Helpers used by generated `reqwasm` client functions to build requests.
+
+use crate::ClientError;
+
+/// Joins the encoded values of the typed header `name` into the string
+/// `reqwasm` takes. Values that aren't valid UTF-8 can't be passed to it, so
+/// they fail the request instead of being dropped.
+pub fn header_value(name: &str, values: &[impl AsRef<[u8]>]) -> Result<String, ClientError> {
+    let values = values
+        .iter()
+        .map(|value| std::str::from_utf8(value.as_ref()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| {
+            let message = format!("the value of the `{name}` header isn't valid UTF-8");
+            ClientError::JsError(::js_sys::Error::new(&message).into())
+        })?;
+    Ok(values.join(", "))
+}
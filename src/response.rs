@@ -0,0 +1,169 @@
+//! Helpers used by generated client functions to inspect responses before
+//! their body is read. `reqwasm` and `reqwest` expose headers differently, so
+//! these are implemented for whichever client is active.
+
+use serde::de::DeserializeOwned;
+
+use crate::{ClientBytes, ClientHeaders, ClientResponse, Error, StatusCode};
+
+/// The header checked by `expect_version(...)`.
+pub const VERSION_HEADER: &str = "X-Api-Version";
+
+/// Returns the value of the header `name`, if it's present and valid UTF-8.
+#[cfg(target_arch = "wasm32")]
+pub fn header(response: &ClientResponse, name: &str) -> Option<String> {
+    response.headers().get(name)
+}
+
+/// Returns the value of the header `name`, if it's present and valid UTF-8.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn header(response: &ClientResponse, name: &str) -> Option<String> {
+    response
+        .headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(ToString::to_string)
+}
+
+/// Returns all response headers, for `returns_headers`.
+#[cfg(target_arch = "wasm32")]
+pub fn headers(response: &ClientResponse) -> ClientHeaders {
+    response.headers()
+}
+
+/// Returns all response headers, for `returns_headers`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn headers(response: &ClientResponse) -> ClientHeaders {
+    response.headers().clone()
+}
+
+/// Returns the status code of the response, for `returns_with_status`.
+#[cfg(target_arch = "wasm32")]
+pub fn status(response: &ClientResponse) -> StatusCode {
+    StatusCode(response.status())
+}
+
+/// Returns the status code of the response, for `returns_with_status`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn status(response: &ClientResponse) -> StatusCode {
+    StatusCode(response.status().as_u16())
+}
+
+/// Whether the response is a `304 Not Modified`, for `etag`.
+pub fn not_modified(response: &ClientResponse) -> bool {
+    status(response) == StatusCode(304)
+}
+
+/// Returns the URL of the response after redirects, for `returns_final_url`.
+#[cfg(target_arch = "wasm32")]
+pub fn url(response: &ClientResponse) -> String {
+    response.url()
+}
+
+/// Returns the URL of the response after redirects, for `returns_final_url`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn url(response: &ClientResponse) -> String {
+    response.url().to_string()
+}
+
+/// Fails with [`Error::Version`] unless the response carries the expected API
+/// version.
+pub fn expect_version<E>(
+    response: ClientResponse,
+    expected: &str,
+) -> Result<ClientResponse, Error<E>> {
+    match header(&response, VERSION_HEADER) {
+        Some(found) if found == expected => Ok(response),
+        found => Err(Error::Version {
+            expected: expected.to_string(),
+            found,
+        }),
+    }
+}
+
+/// Fails with [`Error::Api`] carrying the JSON decoded error body unless the
+/// response status is 2xx or a 304 for `etag`, for `error(ErrType)`.
+#[cfg(target_arch = "wasm32")]
+pub async fn api_error<E: DeserializeOwned>(
+    response: Result<ClientResponse, impl Into<Error<E>>>,
+) -> Result<ClientResponse, Error<E>> {
+    let response = response.map_err(Into::into)?;
+    if response.ok() || response.status() == 304 {
+        return Ok(response);
+    }
+    let body = response.binary().await?;
+    let error = serde_json::from_slice(&body).map_err(Error::decode)?;
+    Err(Error::Api(error))
+}
+
+/// Fails with [`Error::Api`] carrying the JSON decoded error body unless the
+/// response status is 2xx or a 304 for `etag`, for `error(ErrType)`.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn api_error<E: DeserializeOwned>(
+    response: Result<ClientResponse, impl Into<Error<E>>>,
+) -> Result<ClientResponse, Error<E>> {
+    let response = response.map_err(Into::into)?;
+    if response.status().is_success() || response.status() == ::reqwest::StatusCode::NOT_MODIFIED {
+        return Ok(response);
+    }
+    let body = response.bytes().await?;
+    let error = serde_json::from_slice(&body).map_err(Error::decode)?;
+    Err(Error::Api(error))
+}
+
+/// Decodes a JSON response body, keeping the start of the body in the error
+/// if that fails, for the `debug_bodies` feature.
+#[cfg(feature = "debug_bodies")]
+pub fn json<T: DeserializeOwned, E>(body: &[u8]) -> Result<T, Error<E>> {
+    serde_json::from_slice(body).map_err(|err| Error::decode(crate::BodyError::new(err, body)))
+}
+
+/// A response as it was received, returned by the `call_<handler>_raw` client
+/// function of `raw`. `reqwasm` and `reqwest` responses differ, so this wraps
+/// whichever is active behind the same methods.
+pub struct Response(ClientResponse);
+
+impl Response {
+    pub fn status(&self) -> StatusCode {
+        status(&self.0)
+    }
+
+    /// Returns the value of the header `name`, if it's present and valid UTF-8.
+    pub fn header(&self, name: &str) -> Option<String> {
+        header(&self.0, name)
+    }
+
+    pub fn headers(&self) -> ClientHeaders {
+        headers(&self.0)
+    }
+
+    /// Decodes the body as JSON.
+    pub async fn json<T: DeserializeOwned>(self) -> crate::Result<T> {
+        self.0.json().await
+    }
+
+    pub async fn text(self) -> crate::Result<String> {
+        self.0.text().await
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub async fn bytes(self) -> crate::Result<ClientBytes> {
+        self.0.binary().await
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn bytes(self) -> crate::Result<ClientBytes> {
+        self.0.bytes().await
+    }
+
+    /// Returns the response of the underlying HTTP client.
+    pub fn into_inner(self) -> ClientResponse {
+        self.0
+    }
+}
+
+impl From<ClientResponse> for Response {
+    fn from(response: ClientResponse) -> Self {
+        Self(response)
+    }
+}
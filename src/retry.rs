@@ -0,0 +1,80 @@
+//! Sending with retries for `retry(count, backoff_ms)`. A request is retried
+//! on connection errors and 5xx responses, waiting `backoff_ms` before the
+//! first retry and doubling the wait after each one.
+
+use crate::{ClientError, ClientResponse};
+
+/// Sends the request built by `request` up to `retries + 1` times.
+#[cfg(target_arch = "wasm32")]
+pub async fn send(
+    retries: u32,
+    backoff_ms: u32,
+    request: impl Fn() -> ::reqwasm::http::Request,
+) -> Result<ClientResponse, ClientError> {
+    let mut attempt = 0;
+    loop {
+        let result = request().send().await;
+        let retry = match &result {
+            Ok(response) => response.status() >= 500,
+            Err(_) => true,
+        };
+        if !retry || attempt >= retries {
+            return result;
+        }
+        let backoff = u32::try_from(backoff(backoff_ms, attempt)).unwrap_or(u32::MAX);
+        ::gloo_timers::future::TimeoutFuture::new(backoff).await;
+        attempt += 1;
+    }
+}
+
+/// Sends the request built by `request` up to `retries + 1` times.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn send(
+    retries: u32,
+    backoff_ms: u32,
+    request: impl Fn() -> ::reqwest::RequestBuilder,
+) -> Result<ClientResponse, ClientError> {
+    let mut attempt = 0;
+    loop {
+        let result = request().send().await;
+        let retry = match &result {
+            Ok(response) => response.status().is_server_error(),
+            Err(err) => err.is_connect() || err.is_timeout(),
+        };
+        if !retry || attempt >= retries {
+            return result;
+        }
+        let backoff = backoff(backoff_ms, attempt);
+        ::tokio::time::sleep(::std::time::Duration::from_millis(backoff)).await;
+        attempt += 1;
+    }
+}
+
+/// The wait before retry number `attempt + 1`, saturating instead of
+/// overflowing for large retry counts.
+fn backoff(backoff_ms: u32, attempt: u32) -> u64 {
+    u64::from(backoff_ms).saturating_mul(1 << attempt.min(63))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::backoff;
+
+    #[test]
+    fn backoff_doubles() {
+        assert_eq!(backoff(100, 0), 100);
+        assert_eq!(backoff(100, 1), 200);
+        assert_eq!(backoff(100, 3), 800);
+    }
+
+    #[test]
+    fn backoff_saturates() {
+        assert_eq!(backoff(100, 63), u64::MAX);
+        assert_eq!(backoff(100, 64), u64::MAX);
+        assert_eq!(backoff(100, u32::MAX), u64::MAX);
+        assert_eq!(backoff(1, 40), 1 << 40);
+
+        let waits = (0..100).map(|attempt| backoff(1000, attempt));
+        assert!(waits.clone().zip(waits.skip(1)).all(|(a, b)| a <= b));
+    }
+}
@@ -0,0 +1,44 @@
+//! Automatic route collection for handlers annotated with `#[rpc]`.
+//!
+//! With the `inventory` feature enabled, every handler registers its method,
+//! path and route constructor here, so the router can be assembled without
+//! listing each route manually.
+//!
+//! ```
+//! # #[cfg(not(target_arch = "wasm32"))]
+//! let router: axum::Router = wrpc::routes::router();
+//! ```
+
+use axum::{routing::MethodRouter, Router};
+
+/// A single handler registered by `#[rpc]`.
+pub struct Route {
+    pub method: &'static str,
+    pub path: &'static str,
+    pub route: fn() -> MethodRouter,
+}
+
+inventory::collect!(Route);
+
+/// Iterates over all registered routes.
+pub fn iter() -> impl Iterator<Item = &'static Route> {
+    inventory::iter::<Route>.into_iter()
+}
+
+/// Builds a router containing all registered routes. Handlers sharing the
+/// same path are merged.
+pub fn router() -> Router {
+    let mut routes = iter().collect::<Vec<_>>();
+    routes.sort_by_key(|route| route.path);
+
+    let mut router = Router::new();
+    let mut routes = routes.into_iter().peekable();
+    while let Some(route) = routes.next() {
+        let mut method_router = (route.route)();
+        while let Some(next) = routes.next_if(|next| next.path == route.path) {
+            method_router = method_router.merge((next.route)());
+        }
+        router = router.route(route.path, method_router);
+    }
+    router
+}
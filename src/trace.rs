@@ -0,0 +1,24 @@
+//! Trace context propagation for `propagate_trace`.
+
+use opentelemetry::propagation::Injector;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// The headers carrying the trace context of the current `tracing` span, as
+/// injected by the globally configured propagator. That's `traceparent` and
+/// `tracestate` for W3C Trace Context, and nothing if none is configured.
+pub fn headers() -> Vec<(String, String)> {
+    let context = tracing::Span::current().context();
+    let mut headers = Headers(Vec::new());
+    opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut headers)
+    });
+    headers.0
+}
+
+struct Headers(Vec<(String, String)>);
+
+impl Injector for Headers {
+    fn set(&mut self, key: &str, value: String) {
+        self.0.push((key.to_string(), value));
+    }
+}
@@ -0,0 +1,452 @@
+//! TypeScript `fetch` wrappers for handlers annotated with `#[rpc]`.
+//!
+//! With the `ts-export` feature enabled, every handler registers how its
+//! request is made here. [`emit`] assembles them into the contents of a `.ts`
+//! module, i.e. from a build script or a test, with one `fetch` wrapper per
+//! handler taking the same arguments as its client function. Typed headers are
+//! passed as their encoded `string` value. The types the functions refer to
+//! are declared as `unknown`, unless the `schemars` feature is enabled and they
+//! implement `schemars::JsonSchema`.
+//!
+//! ```
+//! # #[cfg(not(target_arch = "wasm32"))]
+//! let module: String = wrpc::ts::emit();
+//! ```
+
+use std::collections::BTreeMap;
+
+/// The request of a single handler registered by `#[rpc]`.
+pub struct Function {
+    pub name: &'static str,
+    pub method: &'static str,
+    pub path: &'static str,
+    pub params: &'static [Param],
+    /// Headers sent with every request, i.e. `Accept` and `Content-Type`.
+    pub headers: &'static [(&'static str, &'static str)],
+    /// The `credentials` mode of the request, if it isn't the default.
+    pub credentials: Option<&'static str>,
+    /// The TypeScript type the returned promise resolves to.
+    pub returns: &'static str,
+    /// How the response is turned into `returns`.
+    pub response: Response,
+    /// Whether `304 Not Modified` resolves to `null`, for `etag`.
+    pub not_modified: bool,
+    /// The named types referred to by the parameters and the return type.
+    pub types: &'static [Type],
+}
+
+/// An argument of the wrapper.
+pub struct Param {
+    pub name: &'static str,
+    /// The TypeScript type of the argument.
+    pub ty: &'static str,
+    /// Where the argument goes in the request.
+    pub source: Source,
+}
+
+/// Where an argument goes in the request.
+pub enum Source {
+    /// The host the request is sent to, as `https://{host}{path}`.
+    Host,
+    /// The path segment of the same name.
+    Path,
+    /// Every path segment, looked up by name.
+    PathMap,
+    /// The fields of the query string.
+    Query,
+    /// Appended to the query string as it is, if it isn't `null`.
+    RawQuery,
+    /// Pairs appended to the query string.
+    QueryPairs,
+    /// The body, as text.
+    Text,
+    /// The body, serialized as JSON.
+    Json,
+    /// The body, as a multipart form of named parts.
+    Multipart,
+    /// The `query` of a GraphQL request.
+    GraphqlQuery,
+    /// The `variables` of a GraphQL request.
+    GraphqlVariables,
+    /// The header with the returned name, unless it's `null`.
+    Header(fn() -> &'static str),
+    /// Headers added to the request.
+    Headers,
+}
+
+/// How the response is turned into the returned value.
+pub enum Response {
+    /// Parsed as JSON.
+    Json,
+    /// The body as text, converted to a `number` or `boolean` if that's the
+    /// returned type.
+    Text,
+    /// The body as a `Uint8Array`.
+    Bytes,
+    /// The body as a stream.
+    Stream,
+    /// The response headers.
+    Headers,
+    /// Nothing, the body is ignored.
+    Empty,
+}
+
+/// A named Rust type, declared as a TypeScript type alias.
+pub struct Type {
+    pub name: &'static str,
+    /// Returns the declarations of this type under `name` and of the types it
+    /// refers to, as pairs of name and TypeScript type.
+    pub declarations: fn(&str) -> Vec<(String, String)>,
+}
+
+inventory::collect!(Function);
+
+/// Iterates over all registered functions.
+pub fn iter() -> impl Iterator<Item = &'static Function> {
+    inventory::iter::<Function>.into_iter()
+}
+
+/// Shared by all wrappers, emitted once ahead of them.
+const HELPERS: &str = r#"
+/** Appends the fields of `value` to `query`, repeating the key of arrays. */
+function wrpcQuery(query: URLSearchParams, value: unknown): void {
+  if (value === null || value === undefined) return;
+  for (const [key, field] of Object.entries(value as object)) {
+    for (const item of Array.isArray(field) ? field : [field]) {
+      if (item !== null && item !== undefined) query.append(key, String(item));
+    }
+  }
+}
+
+/** Sends the request, rejecting unless it succeeds. */
+async function wrpcFetch(method: string, url: string, search: string, init: RequestInit): Promise<Response> {
+  const response = await fetch(search ? `${url}?${search}` : url, { ...init, method });
+  if (!response.ok && response.status !== 304) {
+    throw new Error(`${method} ${url} failed with status ${response.status}`);
+  }
+  return response;
+}
+"#;
+
+/// Assembles all registered functions into a TypeScript module. Types come
+/// first, then the functions, both sorted by name so the output is stable.
+pub fn emit() -> String {
+    let mut functions = iter().collect::<Vec<_>>();
+    functions.sort_by_key(|function| function.name);
+
+    let mut types = BTreeMap::new();
+    for ty in functions.iter().flat_map(|function| function.types) {
+        types.extend((ty.declarations)(ty.name));
+    }
+
+    let mut output = String::new();
+    for (name, ty) in types {
+        output.push_str(&format!("export type {name} = {ty};\n"));
+    }
+    if !functions.is_empty() {
+        output.push_str(HELPERS);
+    }
+    for function in functions {
+        output.push_str(&wrapper(function));
+    }
+    output
+}
+
+/// The `fetch` wrapper of `function`.
+fn wrapper(function: &Function) -> String {
+    let name = camel_case(function.name);
+    let params = function
+        .params
+        .iter()
+        .map(|param| format!("{}: {}", camel_case(param.name), param.ty))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let method = function.method.to_uppercase();
+    let arg = |source: fn(&Source) -> bool| {
+        function
+            .params
+            .iter()
+            .find(|param| source(&param.source))
+            .map(|param| camel_case(param.name))
+    };
+
+    let mut url = String::new();
+    if let Some(host) = arg(|source| matches!(source, Source::Host)) {
+        url.push_str(&format!("https://${{{host}}}"));
+        if !function.path.starts_with('/') {
+            url.push('/');
+        }
+    }
+    let path_map = arg(|source| matches!(source, Source::PathMap));
+    let segments = function.path.split('/').map(|segment| {
+        let (encode, name) = match (segment.strip_prefix(':'), segment.strip_prefix('*')) {
+            (Some(name), _) => ("encodeURIComponent", name),
+            // Wildcards span several segments, so their `/` stay as they are
+            (_, Some(name)) => ("encodeURI", name),
+            _ => return segment.to_string(),
+        };
+        let value = match &path_map {
+            Some(map) => format!("{map}[\"{name}\"]"),
+            None => camel_case(name),
+        };
+        format!("${{{encode}(String({value}))}}")
+    });
+    url.push_str(&segments.collect::<Vec<_>>().join("/"));
+
+    let mut body = Vec::new();
+    let query_params = function
+        .params
+        .iter()
+        .filter(|param| matches!(param.source, Source::Query | Source::QueryPairs));
+    let search = if query_params.clone().next().is_some() {
+        body.push("const query = new URLSearchParams();".to_string());
+        for param in query_params {
+            let name = camel_case(param.name);
+            body.push(match param.source {
+                Source::Query => format!("wrpcQuery(query, {name});"),
+                _ => format!("for (const [key, value] of {name}) query.append(key, value);"),
+            });
+        }
+        "query.toString()".to_string()
+    } else {
+        "\"\"".to_string()
+    };
+    let search = match arg(|source| matches!(source, Source::RawQuery)) {
+        Some(raw) => format!("[{search}, {raw}].filter(Boolean).join(\"&\")"),
+        None => search,
+    };
+
+    let headers = function
+        .headers
+        .iter()
+        .map(|(name, value)| format!("[{name:?}, {value:?}]"))
+        .collect::<Vec<_>>();
+    body.push(format!(
+        "const headers = new Headers([{}]);",
+        headers.join(", ")
+    ));
+    for param in function.params {
+        let name = camel_case(param.name);
+        match param.source {
+            Source::Header(header) if param.ty.ends_with(" | null") => {
+                body.push(format!(
+                    "if ({name} !== null) headers.set({:?}, {name});",
+                    header()
+                ));
+            }
+            Source::Header(header) => {
+                body.push(format!("headers.set({:?}, {name});", header()));
+            }
+            Source::Headers => body.push(format!(
+                "new Headers({name}).forEach((value, key) => headers.set(key, value));"
+            )),
+            _ => {}
+        }
+    }
+
+    let mut init = vec!["headers".to_string()];
+    let request_body = function.params.iter().find_map(|param| {
+        let name = camel_case(param.name);
+        match param.source {
+            Source::Text => Some(name),
+            Source::Json if param.ty.ends_with(" | null") => Some(format!(
+                "{name} === null ? undefined : JSON.stringify({name})"
+            )),
+            Source::Json => Some(format!("JSON.stringify({name})")),
+            Source::Multipart => {
+                body.push("const form = new FormData();".to_string());
+                body.push(format!(
+                    "for (const [name, bytes] of {name}) form.append(name, new Blob([bytes]));"
+                ));
+                Some("form".to_string())
+            }
+            _ => None,
+        }
+    });
+    let graphql = arg(|source| matches!(source, Source::GraphqlQuery))
+        .zip(arg(|source| matches!(source, Source::GraphqlVariables)));
+    let request_body = request_body.or_else(|| {
+        graphql.map(|(query, variables)| {
+            format!("JSON.stringify({{ query: {query}, variables: {variables} }})")
+        })
+    });
+    if let Some(request_body) = request_body {
+        init.push(format!("body: {request_body}"));
+    }
+    if let Some(credentials) = function.credentials {
+        init.push(format!("credentials: {credentials:?}"));
+    }
+    body.push(format!(
+        "const response = await wrpcFetch({method:?}, `{url}`, {search}, {{ {} }});",
+        init.join(", ")
+    ));
+
+    if function.not_modified {
+        body.push("if (response.status === 304) return null;".to_string());
+    }
+    let returns = function.returns.trim_end_matches(" | null");
+    match function.response {
+        Response::Json => body.push("return await response.json();".to_string()),
+        Response::Text if returns == "number" => {
+            body.push("return Number(await response.text());".to_string());
+        }
+        Response::Text if returns == "boolean" => {
+            body.push("return (await response.text()) === \"true\";".to_string());
+        }
+        Response::Text => body.push("return await response.text();".to_string()),
+        Response::Bytes => {
+            body.push("return new Uint8Array(await response.arrayBuffer());".to_string());
+        }
+        Response::Stream => {
+            body.push("return response.body as ReadableStream<Uint8Array>;".to_string());
+        }
+        Response::Headers => {
+            body.push("return Object.fromEntries(response.headers.entries());".to_string());
+        }
+        Response::Empty => {}
+    }
+
+    let body = body
+        .iter()
+        .map(|line| format!("  {line}\n"))
+        .collect::<String>();
+    format!(
+        "\n/** `{method} {}` */\nexport async function {name}({params}): Promise<{}> {{\n{body}}}\n",
+        function.path, function.returns,
+    )
+}
+
+/// Declares the type as `unknown`, since its schema isn't known.
+#[cfg(not(feature = "schemars"))]
+pub fn declarations<T: ?Sized>(name: &str) -> Vec<(String, String)> {
+    vec![(name.to_string(), "unknown".to_string())]
+}
+
+/// Declares the type and its definitions from its JSON schema.
+#[cfg(feature = "schemars")]
+pub fn declarations<T: ?Sized + schemars::JsonSchema>(name: &str) -> Vec<(String, String)> {
+    let schema = serde_json::to_value(schemars::schema_for!(T)).unwrap_or_default();
+    let mut declarations = vec![(name.to_string(), schema_type(&schema))];
+    if let Some(definitions) = schema["definitions"].as_object() {
+        for (name, schema) in definitions {
+            declarations.push((name.clone(), schema_type(schema)));
+        }
+    }
+    declarations
+}
+
+/// The TypeScript spelling of a JSON schema. Anything it doesn't understand
+/// is `unknown`.
+#[cfg(feature = "schemars")]
+fn schema_type(schema: &serde_json::Value) -> String {
+    use serde_json::Value;
+
+    let union = |schemas: &Value, separator: &str| {
+        let types = schemas
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(schema_type)
+            .collect::<Vec<_>>();
+        if types.is_empty() {
+            "unknown".to_string()
+        } else {
+            types.join(separator)
+        }
+    };
+    // Arrays bind tighter than unions
+    let element = |schema: &Value| {
+        let ty = schema_type(schema);
+        if ty.contains(' ') {
+            format!("({ty})[]")
+        } else {
+            format!("{ty}[]")
+        }
+    };
+
+    match schema {
+        Value::Bool(true) => return "unknown".to_string(),
+        Value::Bool(false) => return "never".to_string(),
+        _ => {}
+    }
+    if let Some(reference) = schema["$ref"].as_str() {
+        return reference.rsplit('/').next().unwrap_or_default().to_string();
+    }
+    if let Some(value) = schema.get("const") {
+        return value.to_string();
+    }
+    if let Some(values) = schema["enum"].as_array() {
+        let values = values.iter().map(Value::to_string).collect::<Vec<_>>();
+        return values.join(" | ");
+    }
+    if schema.get("anyOf").is_some() {
+        return union(&schema["anyOf"], " | ");
+    }
+    if schema.get("oneOf").is_some() {
+        return union(&schema["oneOf"], " | ");
+    }
+    if schema.get("allOf").is_some() {
+        return union(&schema["allOf"], " & ");
+    }
+    // `Option<T>` of a primitive is a list of types, i.e. `["string", "null"]`
+    if let Some(types) = schema["type"].as_array() {
+        return types
+            .iter()
+            .map(|ty| {
+                let mut schema = schema.clone();
+                schema["type"] = ty.clone();
+                schema_type(&schema)
+            })
+            .collect::<Vec<_>>()
+            .join(" | ");
+    }
+
+    match schema["type"].as_str() {
+        Some("string") => "string".to_string(),
+        Some("integer" | "number") => "number".to_string(),
+        Some("boolean") => "boolean".to_string(),
+        Some("null") => "null".to_string(),
+        Some("array") => match &schema["items"] {
+            Value::Array(items) => {
+                let items = items.iter().map(schema_type).collect::<Vec<_>>();
+                format!("[{}]", items.join(", "))
+            }
+            Value::Null => "unknown[]".to_string(),
+            items => element(items),
+        },
+        Some("object") => match schema["properties"].as_object() {
+            Some(properties) => {
+                let required = schema["required"].as_array();
+                let properties = properties
+                    .iter()
+                    .map(|(name, property)| {
+                        let optional = !required
+                            .is_some_and(|required| required.iter().any(|field| field == name));
+                        let optional = if optional { "?" } else { "" };
+                        format!("{name}{optional}: {}", schema_type(property))
+                    })
+                    .collect::<Vec<_>>();
+                format!("{{ {} }}", properties.join("; "))
+            }
+            None => match schema.get("additionalProperties") {
+                Some(values) => format!("Record<string, {}>", schema_type(values)),
+                None => "Record<string, unknown>".to_string(),
+            },
+        },
+        _ => "unknown".to_string(),
+    }
+}
+
+/// `get_user` as `getUser`, the usual spelling of TypeScript functions.
+fn camel_case(name: &str) -> String {
+    let mut words = name.split('_');
+    let mut camel = words.next().unwrap_or_default().to_string();
+    for word in words {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            camel.extend(first.to_uppercase());
+            camel.push_str(chars.as_str());
+        }
+    }
+    camel
+}
@@ -0,0 +1,57 @@
+//! Helpers used by `native_client(ureq)` client functions to send requests
+//! and read responses with `ureq`.
+
+use std::io::{self, Read};
+
+use serde::de::DeserializeOwned;
+
+use crate::{ClientBytes, Error};
+
+pub use ::ureq::{request, Request, Response};
+
+/// Sends `request` without a body.
+pub fn call(request: Request) -> Result<Response, Error> {
+    request.call().map_err(Error::from)
+}
+
+/// Sends `request` with a serialized body.
+pub fn send(request: Request, body: impl AsRef<[u8]>) -> Result<Response, Error> {
+    request.send_bytes(body.as_ref()).map_err(Error::from)
+}
+
+/// Joins the encoded values of the typed header `name` into the string `ureq`
+/// takes, failing if they aren't valid UTF-8.
+pub fn header_value(name: &str, values: &[impl AsRef<[u8]>]) -> Result<String, Error> {
+    let values = values
+        .iter()
+        .map(|value| std::str::from_utf8(value.as_ref()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| {
+            let message = format!("the value of the `{name}` header isn't valid UTF-8");
+            Error::from(::ureq::Error::from(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                message,
+            )))
+        })?;
+    Ok(values.join(", "))
+}
+
+/// Reads the response body as text.
+pub fn text(response: Response) -> Result<String, Error> {
+    response.into_string().map_err(Error::decode)
+}
+
+/// Decodes the JSON response body.
+pub fn json<T: DeserializeOwned>(response: Response) -> Result<T, Error> {
+    response.into_json().map_err(Error::decode)
+}
+
+/// Reads the raw response body, i.e. for binary formats.
+pub fn bytes(response: Response) -> Result<ClientBytes, Error> {
+    let mut bytes = Vec::new();
+    response
+        .into_reader()
+        .read_to_end(&mut bytes)
+        .map_err(Error::decode)?;
+    Ok(bytes.into())
+}
@@ -0,0 +1,63 @@
+//! WebSocket connections for `ws(path)` handlers. The handler's URL is
+//! connected to with a `ws://` or `wss://` scheme in place of `http://` or
+//! `https://`.
+
+/// An open WebSocket connection.
+#[cfg(target_arch = "wasm32")]
+pub type WebSocket = ::gloo_net::websocket::futures::WebSocket;
+
+/// An open WebSocket connection.
+#[cfg(not(target_arch = "wasm32"))]
+pub type WebSocket = ::tokio_tungstenite::WebSocketStream<
+    ::tokio_tungstenite::MaybeTlsStream<::tokio::net::TcpStream>,
+>;
+
+/// A message sent or received over a [`WebSocket`].
+#[cfg(target_arch = "wasm32")]
+pub use ::gloo_net::websocket::Message;
+
+/// A message sent or received over a [`WebSocket`].
+#[cfg(not(target_arch = "wasm32"))]
+pub use ::tokio_tungstenite::tungstenite::Message;
+
+/// Error opening a WebSocket connection.
+#[cfg(target_arch = "wasm32")]
+pub type Error = ::gloo_utils::errors::JsError;
+
+/// Error opening a WebSocket connection.
+#[cfg(not(target_arch = "wasm32"))]
+pub type Error = ::tokio_tungstenite::tungstenite::Error;
+
+/// Opens a connection to `path`. Relative paths are resolved against the
+/// page's origin, since not every browser does so for WebSockets.
+#[cfg(target_arch = "wasm32")]
+pub async fn connect(path: &str) -> Result<WebSocket, Error> {
+    let url = if path.starts_with('/') {
+        let origin = ::gloo_utils::window()
+            .location()
+            .origin()
+            .unwrap_or_default();
+        url(&format!("{origin}{path}"))
+    } else {
+        url(path)
+    };
+    WebSocket::open(&url)
+}
+
+/// Opens a connection to `path`, which has to be absolute.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn connect(path: &str) -> Result<WebSocket, Error> {
+    let (socket, _) = ::tokio_tungstenite::connect_async(url(path)).await?;
+    Ok(socket)
+}
+
+/// `url` with its `http(s)://` scheme swapped for `ws(s)://`.
+fn url(url: &str) -> String {
+    if let Some(rest) = url.strip_prefix("https://") {
+        format!("wss://{rest}")
+    } else if let Some(rest) = url.strip_prefix("http://") {
+        format!("ws://{rest}")
+    } else {
+        url.to_string()
+    }
+}
@@ -1,43 +1,102 @@
+use proc_macro2::Span;
 use syn::{
-    spanned::Spanned, AngleBracketedGenericArguments, FnArg, GenericArgument, Ident, Pat, Type,
-    TypeReference,
+    spanned::Spanned, AngleBracketedGenericArguments, Attribute, FnArg, GenericArgument, Ident,
+    LitStr, Pat, Signature, Type, TypeReference, TypeTuple,
 };
 
+use crate::attr::Extractors;
+
 pub enum Argument {
-    Json { name: Ident, inner_type: Type },
-    Query { name: Ident, inner_type: Type },
-    Path { inner_types: Vec<(Ident, Type)> },
-    Body { name: Ident },
+    Json {
+        name: Ident,
+        inner_type: Type,
+        optional: bool,
+    },
+    Query {
+        name: Ident,
+        inner_type: Type,
+        optional: bool,
+    },
+    RawQuery {
+        name: Ident,
+    },
+    Path {
+        inner_types: Vec<(Ident, Type)>,
+        format: Option<LitStr>,
+    },
+    Body {
+        name: Ident,
+    },
+    TypedHeader {
+        name: Ident,
+        inner_type: Type,
+    },
+    HeaderMap {
+        name: Ident,
+    },
+    Multipart {
+        name: Ident,
+    },
     Ignored,
+    Unknown {
+        span: Span,
+    },
 }
 
-impl TryFrom<FnArg> for Argument {
-    type Error = syn::Error;
-
-    fn try_from(value: FnArg) -> Result<Self, Self::Error> {
+impl Argument {
+    pub fn new(value: FnArg, extractors: &Extractors) -> syn::Result<Self> {
         let value = match value {
             FnArg::Receiver(_) => {
                 return Ok(Self::Ignored);
             }
             FnArg::Typed(typed) => typed,
         };
+        let format = parse_path_fmt(&value.attrs)?;
         let name: ArgumentName = value.pat.try_into()?;
-        let ty: ArgumentType = value.ty.try_into()?;
+        let span = value.ty.span();
+        let ty = ArgumentType::new(value.ty, extractors)?;
 
         Ok(match ty {
             ArgumentType::Json(inner) => Self::Json {
                 name: name.single()?,
-                inner_type: inner,
+                inner_type: strip_reference(inner),
+                optional: false,
             },
             ArgumentType::Query(inner) => Self::Query {
                 name: name.single()?,
                 inner_type: inner,
+                optional: false,
+            },
+            ArgumentType::Optional(inner) => match *inner {
+                ArgumentType::Json(inner) => Self::Json {
+                    name: name.single()?,
+                    inner_type: strip_reference(inner),
+                    optional: true,
+                },
+                ArgumentType::Query(inner) => Self::Query {
+                    name: name.single()?,
+                    inner_type: inner,
+                    optional: true,
+                },
+                _ => Self::Unknown { span },
+            },
+            ArgumentType::RawQuery => Self::RawQuery {
+                name: name.single()?,
             },
             ArgumentType::Path(types) => {
                 let names = name.multiple();
+                if format.is_some() && names.len() > 1 {
+                    return Err(syn::Error::new(
+                        names[1].span(),
+                        "`path_fmt` only applies to single segment paths",
+                    ));
+                }
                 if names.len() == types.len() {
-                    let types = names.into_iter().zip(types.into_iter()).collect();
-                    Self::Path { inner_types: types }
+                    let types = names.into_iter().zip(types).collect();
+                    Self::Path {
+                        inner_types: types,
+                        format,
+                    }
                 } else {
                     return Err(syn::Error::new(
                         types[0].span(),
@@ -48,11 +107,64 @@ impl TryFrom<FnArg> for Argument {
             ArgumentType::Body => Self::Body {
                 name: name.single()?,
             },
-            ArgumentType::Ignored => Self::Ignored,
+            ArgumentType::TypedHeader(inner) => Self::TypedHeader {
+                name: name.single()?,
+                inner_type: inner,
+            },
+            ArgumentType::HeaderMap => Self::HeaderMap {
+                name: name.single()?,
+            },
+            ArgumentType::Multipart => Self::Multipart {
+                name: name.single()?,
+            },
+            ArgumentType::Unit
+            | ArgumentType::Bincode(_)
+            | ArgumentType::Bytes
+            | ArgumentType::Text(_)
+            | ArgumentType::Stream
+            | ArgumentType::NdJson(_)
+            | ArgumentType::ResponseHeaders(_)
+            | ArgumentType::Ignored => Self::Ignored,
+            ArgumentType::Unknown(span) => Self::Unknown { span },
         })
     }
 }
 
+/// The client borrows JSON bodies anyway, so `Json<&T>` is taken as `&T`
+/// rather than `&&T`.
+fn strip_reference(ty: Type) -> Type {
+    match ty {
+        Type::Reference(reference) => *reference.elem,
+        ty => ty,
+    }
+}
+
+/// Reads the format string of a `#[path_fmt("{:x}")]` argument attribute.
+fn parse_path_fmt(attrs: &[Attribute]) -> syn::Result<Option<LitStr>> {
+    let Some(attr) = attrs.iter().find(|attr| attr.path().is_ident("path_fmt")) else {
+        return Ok(None);
+    };
+    let format: LitStr = attr.parse_args()?;
+    let value = format.value().replace("{{", "").replace("}}", "");
+    if value.matches('{').count() != 1 || value.matches('}').count() != 1 {
+        return Err(syn::Error::new(
+            format.span(),
+            "`path_fmt` must contain exactly one placeholder",
+        ));
+    }
+    Ok(Some(format))
+}
+
+/// Removes the `path_fmt` helper attributes, which aren't valid on the
+/// handler itself.
+pub fn strip_path_formats(sig: &mut Signature) {
+    for arg in &mut sig.inputs {
+        if let FnArg::Typed(typed) = arg {
+            typed.attrs.retain(|attr| !attr.path().is_ident("path_fmt"));
+        }
+    }
+}
+
 pub enum ArgumentName {
     Single(Ident),
     Multiple(Vec<Ident>),
@@ -124,15 +236,40 @@ impl ArgumentName {
 pub enum ArgumentType {
     Json(Type),
     Query(Type),
+    /// `RawQuery`, the query string passed through as it is.
+    RawQuery,
     Path(Vec<Type>),
     Body,
+    TypedHeader(Type),
+    HeaderMap,
+    Multipart,
+    Unit,
+    Bincode(Type),
+    /// The raw response body, for `raw_bytes_return`.
+    Bytes,
+    /// The response body as text, parsed with `FromStr`, for `returns(text(T))`.
+    Text(Type),
+    /// A stream of response body chunks, for `returns(stream)`.
+    Stream,
+    /// A stream of JSON values, one per line of the response body, for
+    /// `returns(ndjson(T))`.
+    NdJson(Type),
+    /// Typed response headers, for `returns(headers(...))`. The client returns
+    /// a struct with an optional field per header instead of the body.
+    ResponseHeaders(Vec<Type>),
+    /// `Option<Json<T>>` or `Option<Query<T>>`, anything else is unknown.
+    Optional(Box<ArgumentType>),
     Ignored,
+    /// A type that isn't a known extractor. Only valid as an argument with
+    /// `allow_unknown_extractors`, where it's skipped like `Ignored`.
+    Unknown(Span),
 }
 
-impl TryFrom<Box<Type>> for ArgumentType {
-    type Error = syn::Error;
-
-    fn try_from(value: Box<Type>) -> syn::Result<Self> {
+impl ArgumentType {
+    /// Recognizes the extractor or response type `value`, including under the
+    /// aliases registered with `extractors(...)`.
+    pub fn new(value: Box<Type>, extractors: &Extractors) -> syn::Result<Self> {
+        let span = value.span();
         let ty = match *value {
             Type::Path(path) => Ok(path.path),
             Type::Reference(TypeReference { elem, .. }) => match *elem {
@@ -140,6 +277,8 @@ impl TryFrom<Box<Type>> for ArgumentType {
                 value => Err(syn::Error::new(value.span(), "Argument type must be path")),
             },
             Type::ImplTrait(_) => return Ok(Self::Ignored),
+            Type::Tuple(tuple) if tuple.elems.is_empty() => return Ok(Self::Unit),
+            Type::Tuple(_) => return Ok(Self::Unknown(span)),
             value => Err(syn::Error::new(value.span(), "Argument type must be path")),
         }?;
 
@@ -155,11 +294,13 @@ impl TryFrom<Box<Type>> for ArgumentType {
         };
 
         #[allow(clippy::unnecessary_unwrap)] // The if let alternative is unstable
-        if last.ident == "Json" && arg.is_some() {
+        if extractors.is_json(&last.ident) && arg.is_some() {
             Ok(ArgumentType::Json(arg.unwrap()))
-        } else if last.ident == "Query" && arg.is_some() {
+        } else if extractors.is_query(&last.ident) && arg.is_some() {
             Ok(ArgumentType::Query(arg.unwrap()))
-        } else if last.ident == "Path" && arg.is_some() {
+        } else if last.ident == "RawQuery" {
+            Ok(ArgumentType::RawQuery)
+        } else if extractors.is_path(&last.ident) && arg.is_some() {
             let inner_types = match arg.unwrap() {
                 Type::Path(path) => Ok(vec![Type::Path(path)]),
                 Type::Tuple(tuple) => Ok(tuple.elems.into_iter().collect()),
@@ -169,10 +310,108 @@ impl TryFrom<Box<Type>> for ArgumentType {
                 )),
             }?;
             Ok(ArgumentType::Path(inner_types))
+        } else if last.ident == "TypedHeader" && arg.is_some() {
+            Ok(ArgumentType::TypedHeader(arg.unwrap()))
+        } else if last.ident == "HeaderMap" {
+            Ok(ArgumentType::HeaderMap)
+        } else if last.ident == "Multipart" {
+            Ok(ArgumentType::Multipart)
+        } else if last.ident == "StatusCode" {
+            Ok(ArgumentType::Unit)
         } else if last.ident == "String" || last.ident == "str" {
             Ok(ArgumentType::Body)
-        } else {
+        } else if last.ident == "Option" && arg.is_some() {
+            let inner = Self::new(Box::new(arg.unwrap()), extractors)?;
+            Ok(ArgumentType::Optional(Box::new(inner)))
+        } else if last.ident == "State"
+            || last.ident == "Extension"
+            || last.ident == "Host"
+            || last.ident == "WebSocketUpgrade"
+        {
             Ok(ArgumentType::Ignored)
+        } else {
+            Ok(ArgumentType::Unknown(span))
+        }
+    }
+
+    /// Recognizes the response type `value`. Unlike arguments, tuples are
+    /// responses made of parts, one of which is the body.
+    pub fn new_return(value: Box<Type>, extractors: &Extractors) -> syn::Result<Self> {
+        let span = value.span();
+        match *value {
+            // `(HeaderMap, T)` responses carry their body in `T`
+            Type::Tuple(tuple) if tuple.elems.len() == 2 && is_header_map(&tuple.elems[0]) => {
+                Self::new_return(Box::new(tuple.elems[1].clone()), extractors)
+            }
+            // `(StatusCode, Json<T>)` and friends carry their body in one of the
+            // elements, the others are response parts
+            Type::Tuple(tuple) if !tuple.elems.is_empty() => tuple_body(tuple, extractors),
+            value => Ok(Self::new(Box::new(value), extractors).unwrap_or(Self::Unknown(span))),
+        }
+    }
+}
+
+/// The single body of a tuple response. Tuples without a recognizable body,
+/// or with more than one, can't be derived from.
+fn tuple_body(tuple: TypeTuple, extractors: &Extractors) -> syn::Result<ArgumentType> {
+    let span = tuple.span();
+    let mut bodies = Vec::new();
+    for elem in tuple.elems {
+        let body = match &elem {
+            Type::Path(path)
+                if path
+                    .path
+                    .segments
+                    .last()
+                    .is_some_and(|last| last.ident == "Bytes") =>
+            {
+                ArgumentType::Bytes
+            }
+            _ => match ArgumentType::new(Box::new(elem), extractors) {
+                Ok(body @ (ArgumentType::Json(_) | ArgumentType::Body)) => body,
+                _ => continue,
+            },
+        };
+        bodies.push(body);
+    }
+    match bodies.len() {
+        0 => Err(syn::Error::new(span, "Tuple response has no body")),
+        1 => Ok(bodies.remove(0)),
+        _ => Err(syn::Error::new(
+            span,
+            "Tuple response has more than one body",
+        )),
+    }
+}
+
+fn is_header_map(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident == "HeaderMap")
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+/// The element type of a `Vec<T>`, so JSON bodies can be borrowed as `&[T]`.
+pub fn vec_element(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let last = path.path.segments.last()?;
+    if last.ident != "Vec" {
+        return None;
+    }
+    match &last.arguments {
+        syn::PathArguments::AngleBracketed(AngleBracketedGenericArguments { args, .. }) => {
+            match args.first() {
+                Some(GenericArgument::Type(ty)) => Some(ty),
+                _ => None,
+            }
         }
+        _ => None,
     }
 }
@@ -1,4 +1,8 @@
-use syn::{parenthesized, parse::Parse, Ident, LitStr, Token, Type};
+use proc_macro2::Span;
+use syn::{
+    braced, parenthesized, parse::Parse, punctuated::Punctuated, token, Expr, Ident, LitInt,
+    LitStr, Token, Type, Visibility,
+};
 
 use crate::argument::ArgumentType;
 
@@ -6,49 +10,1032 @@ use crate::argument::ArgumentType;
 pub struct RpcAttribute {
     pub method: Ident,
     pub path: String,
+    pub path_span: Span,
+    pub path_types: Vec<(String, Type)>,
+    pub prefix: Option<Expr>,
     pub return_override: Option<ArgumentType>,
+    pub path_defaults: Vec<PathDefault>,
+    pub path_struct: Option<PathStruct>,
+    pub extractors: Extractors,
+    /// Overrides the visibility of the client functions, which otherwise
+    /// follows the handler's.
+    pub vis: Option<Visibility>,
+    pub query_static: Vec<QueryPair>,
+    pub default_headers: Vec<DefaultHeader>,
+    pub framework_error: Option<Type>,
+    pub api_error: Option<Type>,
+    pub named_lifetimes: bool,
+    pub owned_query: bool,
+    pub owned_body: bool,
+    pub query_encoding: QueryEncoding,
+    pub query_array: Option<QueryArray>,
+    pub query_key: Option<LitStr>,
+    pub native_client: NativeClient,
+    pub format: Format,
+    pub expect_version: Option<LitStr>,
+    pub content_type: Option<LitStr>,
+    pub accept: Option<LitStr>,
+    pub pretty_body: bool,
+    pub path_helper: bool,
+    pub builder: bool,
+    pub request_helper: bool,
+    pub raw: bool,
+    pub propagate_trace: bool,
+    pub allow_unknown_extractors: bool,
+    pub returns_with_status: bool,
+    pub returns_headers: bool,
+    pub returns_final_url: bool,
+    pub retry: Option<Retry>,
+    pub wasm_bindgen: bool,
+    pub compress: Option<Compression>,
+    pub cookies: bool,
+    pub etag: bool,
+    pub extra_headers: bool,
+    pub extra_query: bool,
+    pub locale: bool,
+    pub host: bool,
+    pub credentials: Option<Credentials>,
+    pub graphql: bool,
+    pub ws: bool,
+    pub client_only: bool,
+}
+
+impl RpcAttribute {
+    /// The names of the parameters in the path, i.e. `id` for `:id` and `rest`
+    /// for a `*rest` wildcard.
+    pub fn path_params(&self) -> impl Iterator<Item = &str> {
+        self.path.split('/').filter_map(path_param)
+    }
+}
+
+/// The parameter name of a path segment, if it's a parameter.
+pub fn path_param(segment: &str) -> Option<&str> {
+    segment
+        .strip_prefix(':')
+        .or_else(|| segment.strip_prefix('*'))
+}
+
+/// Strips the inline types of path parameters, i.e. `:id<u32>`, returning the
+/// plain path and the declared type of each parameter that has one.
+fn split_path_types(path: &LitStr) -> syn::Result<(String, Vec<(String, Type)>)> {
+    let mut types = Vec::new();
+    let segments = path
+        .value()
+        .split('/')
+        .map(|segment| match (path_param(segment), segment.find('<')) {
+            (Some(_), Some(start)) if segment.ends_with('>') => {
+                let ty = syn::parse_str(&segment[start + 1..segment.len() - 1]).map_err(|_| {
+                    syn::Error::new(
+                        path.span(),
+                        format!("Invalid type in path segment `{segment}`"),
+                    )
+                })?;
+                types.push((segment[1..start].to_string(), ty));
+                Ok(segment[..start].to_string())
+            }
+            _ => Ok(segment.to_string()),
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+    Ok((segments.join("/"), types))
+}
+
+/// Rejects malformed paths, which would otherwise silently produce a route
+/// that never matches. Relative paths are only allowed with a prefix, the one
+/// of an absolute URL is checked like any other.
+fn validate_path(path: &LitStr, plain_path: &str, has_prefix: bool) -> syn::Result<()> {
+    let error = |message: &str| Err(syn::Error::new(path.span(), message));
+    let relative = match plain_path.split_once("://") {
+        Some((_, rest)) => rest.find('/').map_or("/", |start| &rest[start..]),
+        None => plain_path,
+    };
+    let Some(relative) = relative
+        .strip_prefix('/')
+        .or(has_prefix.then_some(relative))
+    else {
+        return error(
+            "Paths must start with `/`, unless they're absolute URLs or use `prefix(...)`",
+        );
+    };
+
+    let segments = relative.split('/').collect::<Vec<_>>();
+    // A trailing slash leaves an empty last segment, which is fine
+    for (i, segment) in segments.iter().enumerate() {
+        if segment.is_empty() && i + 1 < segments.len() {
+            return error(&format!("Empty segment in path `{plain_path}`"));
+        }
+        if let Some(name) = path_param(segment) {
+            if syn::parse_str::<Ident>(name).is_err() {
+                return error(&format!(
+                    "Expected an identifier after `{}` in path segment `{segment}`",
+                    &segment[..1],
+                ));
+            }
+        } else if segment.contains(['{', '}']) {
+            // axum 0.6 routes braces literally, so `{id}` would never match
+            return error(&format!(
+                "Unexpected brace in path segment `{segment}`, parameters are written `:name`"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// The serialization format used for structured bodies and responses.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Json,
+    Cbor,
+    MsgPack,
+}
+
+impl Parse for Format {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let format: Ident = input.parse()?;
+        if format == "json" {
+            Ok(Format::Json)
+        } else if format == "cbor" {
+            Ok(Format::Cbor)
+        } else if format == "msgpack" {
+            Ok(Format::MsgPack)
+        } else {
+            Err(syn::Error::new(
+                format.span(),
+                "Expected one of `json`, `cbor` or `msgpack`",
+            ))
+        }
+    }
+}
+
+/// The `Content-Encoding` applied to serialized request bodies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Deflate,
+}
+
+impl Compression {
+    /// The `Content-Encoding` header value.
+    pub fn encoding(self) -> &'static str {
+        match self {
+            Compression::Gzip => "gzip",
+            Compression::Deflate => "deflate",
+        }
+    }
+}
+
+impl Parse for Compression {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let compression: Ident = input.parse()?;
+        if compression == "gzip" {
+            Ok(Compression::Gzip)
+        } else if compression == "deflate" {
+            Ok(Compression::Deflate)
+        } else {
+            Err(syn::Error::new(
+                compression.span(),
+                "Expected one of `gzip` or `deflate`",
+            ))
+        }
+    }
+}
+
+/// The serializer used for the `Query` argument. `UrlEncoded` matches axum's
+/// `Query` extractor.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum QueryEncoding {
+    Qs,
+    #[default]
+    UrlEncoded,
+}
+
+impl QueryEncoding {
+    /// The crate providing the serializer.
+    pub fn serializer(self) -> Ident {
+        let serializer = match self {
+            QueryEncoding::Qs => "serde_qs",
+            QueryEncoding::UrlEncoded => "serde_urlencoded",
+        };
+        Ident::new(serializer, Span::call_site())
+    }
+}
+
+impl Parse for QueryEncoding {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let encoding: Ident = input.parse()?;
+        if encoding == "qs" {
+            Ok(QueryEncoding::Qs)
+        } else if encoding == "urlencoded" {
+            Ok(QueryEncoding::UrlEncoded)
+        } else {
+            Err(syn::Error::new(
+                encoding.span(),
+                "Expected one of `qs` or `urlencoded`",
+            ))
+        }
+    }
+}
+
+/// How `query_array(...)` spells sequences in the query string, i.e. the
+/// variant of `wrpc::query::Arrays`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryArray {
+    /// `id=1&id=2`
+    Repeat,
+    /// `id[]=1&id[]=2`
+    Brackets,
+}
+
+impl QueryArray {
+    pub fn variant(self) -> Ident {
+        let variant = match self {
+            QueryArray::Repeat => "Repeat",
+            QueryArray::Brackets => "Brackets",
+        };
+        Ident::new(variant, Span::call_site())
+    }
+}
+
+impl Parse for QueryArray {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let style: Ident = input.parse()?;
+        if style == "repeat" {
+            Ok(QueryArray::Repeat)
+        } else if style == "brackets" {
+            Ok(QueryArray::Brackets)
+        } else {
+            Err(syn::Error::new(
+                style.span(),
+                "Expected one of `repeat` or `brackets`",
+            ))
+        }
+    }
+}
+
+/// The HTTP client used by the native client function.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum NativeClient {
+    #[default]
+    Reqwest,
+    /// A synchronous client using `ureq`, for `native_client(ureq)`.
+    Ureq,
+}
+
+impl Parse for NativeClient {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let client: Ident = input.parse()?;
+        if client == "reqwest" {
+            Ok(NativeClient::Reqwest)
+        } else if client == "ureq" {
+            Ok(NativeClient::Ureq)
+        } else {
+            Err(syn::Error::new(
+                client.span(),
+                "Expected one of `reqwest` or `ureq`",
+            ))
+        }
+    }
+}
+
+/// Whether the browser sends cookies with the request, as in the Fetch API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Credentials {
+    Include,
+    SameOrigin,
+    Omit,
+}
+
+impl Credentials {
+    /// The matching `RequestCredentials` variant.
+    pub fn variant(self) -> Ident {
+        let variant = match self {
+            Credentials::Include => "Include",
+            Credentials::SameOrigin => "SameOrigin",
+            Credentials::Omit => "Omit",
+        };
+        Ident::new(variant, Span::call_site())
+    }
+}
+
+impl Parse for Credentials {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let credentials: Ident = input.parse()?;
+        if credentials == "include" {
+            Ok(Credentials::Include)
+        } else if credentials == "same_origin" {
+            Ok(Credentials::SameOrigin)
+        } else if credentials == "omit" {
+            Ok(Credentials::Omit)
+        } else {
+            Err(syn::Error::new(
+                credentials.span(),
+                "Expected one of `include`, `same_origin` or `omit`",
+            ))
+        }
+    }
+}
+
+/// How a trailing `/` on the handler path is treated.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TrailingSlash {
+    #[default]
+    Keep,
+    Strip,
+    Add,
+}
+
+impl TrailingSlash {
+    /// Normalizes `path` according to this mode. The root path `/` is never
+    /// stripped.
+    pub fn apply(self, path: &str) -> String {
+        match self {
+            TrailingSlash::Keep => path.to_string(),
+            TrailingSlash::Strip => {
+                let stripped = path.trim_end_matches('/');
+                if stripped.is_empty() && path.starts_with('/') {
+                    "/".to_string()
+                } else {
+                    stripped.to_string()
+                }
+            }
+            TrailingSlash::Add if path.ends_with('/') => path.to_string(),
+            TrailingSlash::Add => format!("{path}/"),
+        }
+    }
+}
+
+impl Parse for TrailingSlash {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mode: Ident = input.parse()?;
+        if mode == "keep" {
+            Ok(TrailingSlash::Keep)
+        } else if mode == "strip" {
+            Ok(TrailingSlash::Strip)
+        } else if mode == "add" {
+            Ok(TrailingSlash::Add)
+        } else {
+            Err(syn::Error::new(
+                mode.span(),
+                "Expected one of `keep`, `strip` or `add`",
+            ))
+        }
+    }
 }
 
 impl Parse for RpcAttribute {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let options = input.parse_terminated(AttributeOption::parse, Token![,])?;
-        let (method, path, return_override) = options
-            .into_iter()
-            .map(|option| match option {
-                AttributeOption::Method(method, path) => (Some(method), Some(path), None),
-                AttributeOption::ReturnOverride(ty) => (None, None, Some(ArgumentType::Json(ty))),
-            })
-            .fold((None, None, None), |(a1, b1, c1), (a2, b2, c2)| {
-                (a1.or(a2), b1.or(b2), c1.or(c2))
-            });
 
+        let mut method = None;
+        let mut path = None;
+        let mut return_override = None;
+        let mut path_defaults = Vec::new();
+        let mut query_static = Vec::new();
+        let mut default_headers = Vec::new();
+        let mut trailing_slash = TrailingSlash::default();
+        let mut framework_error = None;
+        let mut api_error = None;
+        let mut named_lifetimes = false;
+        let mut owned_query = false;
+        let mut owned_body = false;
+        let mut query_encoding = QueryEncoding::default();
+        let mut query_array = None;
+        let mut query_key = None;
+        let mut native_client = NativeClient::default();
+        let mut format = Format::default();
+        let mut expect_version = None;
+        let mut pretty_body = false;
+        let mut path_helper = false;
+        let mut builder = false;
+        let mut request_helper = false;
+        let mut raw = false;
+        let mut propagate_trace = false;
+        let mut allow_unknown_extractors = false;
+        let mut returns_with_status = false;
+        let mut returns_headers = false;
+        let mut returns_final_url = false;
+        let mut fire_and_forget = None;
+        let mut retry = None;
+        let mut wasm_bindgen = false;
+        let mut compress = None;
+        let mut cookies = false;
+        let mut etag = false;
+        let mut extra_headers = false;
+        let mut extra_query = false;
+        let mut locale = false;
+        let mut host = false;
+        let mut credentials = None;
+        let mut graphql = false;
+        let mut ws = false;
+        // The feature makes it the default for every handler
+        let mut client_only = cfg!(feature = "client_only");
+        let mut prefix = None;
+        let mut content_type = None;
+        let mut merge_patch = None;
+        let mut accept = None;
+        let mut path_struct = None;
+        let mut extractors = Extractors::default();
+        let mut vis = None;
+        for option in options {
+            match option {
+                AttributeOption::Method(name, value) => {
+                    method = method.or(Some(name));
+                    path = path.or(Some(value));
+                }
+                AttributeOption::Ws(name, value) => {
+                    // WebSockets are upgraded from a `GET` request
+                    method = method.or(Some(Ident::new("get", name.span())));
+                    path = path.or(Some(value));
+                    ws = true;
+                }
+                AttributeOption::ReturnOverride(ty) => {
+                    return_override = return_override.or(Some(ty));
+                }
+                AttributeOption::ReturnBincode(ty) => {
+                    return_override = return_override.or(Some(ArgumentType::Bincode(ty)));
+                }
+                AttributeOption::RawBytesReturn => {
+                    return_override = return_override.or(Some(ArgumentType::Bytes));
+                }
+                AttributeOption::PathDefaults(defaults) => path_defaults.extend(defaults),
+                AttributeOption::QueryStatic(pairs) => query_static.extend(pairs),
+                AttributeOption::DefaultHeaders(headers) => default_headers.extend(headers),
+                AttributeOption::TrailingSlash(mode) => trailing_slash = mode,
+                AttributeOption::NamedLifetimes => named_lifetimes = true,
+                AttributeOption::OwnedQuery => owned_query = true,
+                AttributeOption::OwnedBody => owned_body = true,
+                AttributeOption::QueryEncoding(value) => query_encoding = value,
+                AttributeOption::QueryKey(value) => query_key = Some(value),
+                AttributeOption::QueryArray(value) => query_array = Some(value),
+                AttributeOption::NativeClient(value) => native_client = value,
+                AttributeOption::PrettyBody => pretty_body = true,
+                AttributeOption::PathHelper => path_helper = true,
+                AttributeOption::Builder => builder = true,
+                AttributeOption::RequestHelper => request_helper = true,
+                AttributeOption::Raw => raw = true,
+                AttributeOption::PropagateTrace => propagate_trace = true,
+                AttributeOption::AllowUnknownExtractors => allow_unknown_extractors = true,
+                AttributeOption::ReturnsWithStatus => returns_with_status = true,
+                AttributeOption::ReturnsHeaders => returns_headers = true,
+                AttributeOption::ReturnsFinalUrl => returns_final_url = true,
+                AttributeOption::FireAndForget(span) => fire_and_forget = Some(span),
+                AttributeOption::Retry(value) => retry = Some(value),
+                AttributeOption::WasmBindgen => wasm_bindgen = true,
+                AttributeOption::Compress(value) => compress = Some(value),
+                AttributeOption::Cookies => cookies = true,
+                AttributeOption::Etag => etag = true,
+                AttributeOption::HeaderMap => extra_headers = true,
+                AttributeOption::ExtraQuery => extra_query = true,
+                AttributeOption::Locale => locale = true,
+                AttributeOption::Host => host = true,
+                AttributeOption::Credentials(value) => credentials = Some(value),
+                AttributeOption::GraphQl => graphql = true,
+                AttributeOption::ClientOnly => client_only = true,
+                AttributeOption::Prefix(value) => prefix = Some(value),
+                AttributeOption::ContentType(value) => content_type = Some(value),
+                AttributeOption::MergePatch(span) => merge_patch = Some(span),
+                AttributeOption::Accept(value) => accept = Some(value),
+                AttributeOption::PathStruct(value) => path_struct = Some(value),
+                AttributeOption::Extractors(value) => extractors = value,
+                AttributeOption::Vis(value) => vis = Some(value),
+                AttributeOption::Format(value) => format = value,
+                AttributeOption::ExpectVersion(version) => expect_version = Some(version),
+                AttributeOption::ApiError(ty) => api_error = Some(ty),
+                AttributeOption::Leptos(error) => {
+                    framework_error =
+                        Some(error.unwrap_or_else(|| syn::parse_quote!(::leptos::ServerFnError)));
+                }
+            }
+        }
+
+        let method = method.ok_or_else(|| input.error("Missing method"))?;
+        if let Some(retry) = &retry {
+            const IDEMPOTENT: &[&str] = &["get", "put", "delete"];
+            if !retry.always && !IDEMPOTENT.iter().any(|&idempotent| method == idempotent) {
+                return Err(syn::Error::new(
+                    retry.count.span(),
+                    "Only idempotent methods are retried. Use `retry(count, backoff_ms, always)` to retry anyway",
+                ));
+            }
+        }
+
+        if let Some(span) = merge_patch {
+            if method != "patch" {
+                return Err(syn::Error::new(
+                    span,
+                    "`merge_patch` is only valid with `patch`",
+                ));
+            }
+            if content_type.is_some() {
+                return Err(syn::Error::new(
+                    span,
+                    "`merge_patch` can't be combined with `content_type`",
+                ));
+            }
+            // RFC 7396 JSON Merge Patch
+            content_type = Some(LitStr::new("application/merge-patch+json", span));
+        }
+
+        if let Some(content_type) = &content_type {
+            const WITH_BODY: &[&str] = &["post", "put", "patch", "delete"];
+            if !WITH_BODY.iter().any(|&with_body| method == with_body) {
+                return Err(syn::Error::new(
+                    content_type.span(),
+                    "`content_type` needs a method with a body, i.e. `post`, `put`, `patch` or `delete`",
+                ));
+            }
+        }
+
+        if let Some(span) = fire_and_forget {
+            if return_override.is_some() {
+                return Err(syn::Error::new(
+                    span,
+                    "`fire_and_forget` can't be combined with a return override",
+                ));
+            }
+            return_override = Some(ArgumentType::Unit);
+        }
+
+        let path = path.unwrap();
+        let (plain_path, path_types) = split_path_types(&path)?;
+        validate_path(&path, &plain_path, prefix.is_some())?;
         Ok(RpcAttribute {
-            method: method.ok_or_else(|| input.error("Missing method"))?,
-            path: path.unwrap().value(),
+            method,
+            path: trailing_slash.apply(&plain_path),
+            path_span: path.span(),
+            path_types,
+            prefix,
             return_override,
+            path_defaults,
+            path_struct,
+            extractors,
+            vis,
+            query_static,
+            default_headers,
+            framework_error,
+            api_error,
+            named_lifetimes,
+            owned_query,
+            owned_body,
+            query_encoding,
+            query_array,
+            query_key,
+            native_client,
+            format,
+            expect_version,
+            content_type,
+            accept,
+            pretty_body,
+            path_helper,
+            builder,
+            request_helper,
+            raw,
+            propagate_trace,
+            allow_unknown_extractors,
+            returns_with_status,
+            returns_headers,
+            returns_final_url,
+            retry,
+            wasm_bindgen,
+            compress,
+            cookies,
+            etag,
+            extra_headers,
+            extra_query,
+            locale,
+            host,
+            credentials,
+            graphql,
+            ws,
+            client_only,
+        })
+    }
+}
+
+/// Retry settings, i.e. `retry(3, 100)` or `retry(3, 100, always)`.
+#[derive(Debug)]
+pub struct Retry {
+    pub count: LitInt,
+    pub backoff_ms: LitInt,
+    pub always: bool,
+}
+
+impl Parse for Retry {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let count = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let backoff_ms = input.parse()?;
+        let always = if input.parse::<Option<Token![,]>>()?.is_some() {
+            let flag: Ident = input.parse()?;
+            if flag != "always" {
+                return Err(syn::Error::new(flag.span(), "Expected `always`"));
+            }
+            true
+        } else {
+            false
+        };
+        Ok(Retry {
+            count,
+            backoff_ms,
+            always,
+        })
+    }
+}
+
+/// A fallback for an optional path segment, i.e. `id = 0`.
+#[derive(Debug)]
+pub struct PathDefault {
+    pub name: Ident,
+    pub value: Expr,
+}
+
+impl Parse for PathDefault {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let value = input.parse()?;
+        Ok(PathDefault { name, value })
+    }
+}
+
+/// The fields of a struct extracted with `Path<Params>`, i.e.
+/// `Params { id: u32, team: String }`. Each one is a path segment.
+#[derive(Debug)]
+pub struct PathStruct {
+    pub name: Ident,
+    pub fields: Vec<(Ident, Type)>,
+}
+
+impl Parse for PathStruct {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+        let content;
+        braced!(content in input);
+        let fields = content.parse_terminated(
+            |input| {
+                let name = input.parse()?;
+                input.parse::<Token![:]>()?;
+                Ok((name, input.parse()?))
+            },
+            Token![,],
+        )?;
+        Ok(PathStruct {
+            name,
+            fields: fields.into_iter().collect(),
+        })
+    }
+}
+
+/// Names the handler's extractors are imported under besides their own, i.e.
+/// `json = AxJson` after `use axum::Json as AxJson`.
+#[derive(Debug, Default)]
+pub struct Extractors {
+    pub json: Option<Ident>,
+    pub query: Option<Ident>,
+    pub path: Option<Ident>,
+}
+
+impl Extractors {
+    pub fn is_json(&self, ident: &Ident) -> bool {
+        ident == "Json"
+            || ident == "Cbor"
+            || ident == "MsgPack"
+            || self.json.as_ref() == Some(ident)
+    }
+
+    pub fn is_query(&self, ident: &Ident) -> bool {
+        ident == "Query" || self.query.as_ref() == Some(ident)
+    }
+
+    pub fn is_path(&self, ident: &Ident) -> bool {
+        ident == "Path" || self.path.as_ref() == Some(ident)
+    }
+}
+
+impl Parse for Extractors {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut extractors = Extractors::default();
+        let aliases = input.parse_terminated(
+            |input| {
+                let kind: Ident = input.parse()?;
+                input.parse::<Token![=]>()?;
+                Ok((kind, input.parse::<Ident>()?))
+            },
+            Token![,],
+        )?;
+        for (kind, alias) in aliases {
+            let slot = if kind == "json" {
+                &mut extractors.json
+            } else if kind == "query" {
+                &mut extractors.query
+            } else if kind == "path" {
+                &mut extractors.path
+            } else {
+                return Err(syn::Error::new(
+                    kind.span(),
+                    "Expected `json`, `query` or `path`",
+                ));
+            };
+            *slot = Some(alias);
+        }
+        Ok(extractors)
+    }
+}
+
+/// A fixed query parameter, i.e. `"format" => "json"`. The value may be any
+/// expression, including client arguments.
+#[derive(Debug)]
+pub struct QueryPair {
+    pub key: LitStr,
+    pub value: Expr,
+}
+
+impl Parse for QueryPair {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let key: LitStr = input.parse()?;
+        let unreserved = |c: char| c.is_ascii_alphanumeric() || "-_.~".contains(c);
+        if key.value().is_empty() || !key.value().chars().all(unreserved) {
+            return Err(syn::Error::new(
+                key.span(),
+                "Query keys may only contain ASCII letters, digits, `-`, `_`, `.` and `~`",
+            ));
+        }
+        input.parse::<Token![=>]>()?;
+        let value = input.parse()?;
+        Ok(QueryPair { key, value })
+    }
+}
+
+/// A header sent with every request, i.e. `"X-Client=wrpc"`.
+#[derive(Debug)]
+pub struct DefaultHeader {
+    pub name: String,
+    pub value: String,
+}
+
+impl Parse for DefaultHeader {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let header: LitStr = input.parse()?;
+        let text = header.value();
+        let Some((name, value)) = text.split_once('=') else {
+            return Err(syn::Error::new(
+                header.span(),
+                "Expected a header as `\"Name=value\"`",
+            ));
+        };
+        let token = |c: char| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c);
+        if name.is_empty() || !name.chars().all(token) {
+            return Err(syn::Error::new(header.span(), "Invalid header name"));
+        }
+        if value.chars().any(|c| c.is_ascii_control() && c != '\t') {
+            return Err(syn::Error::new(header.span(), "Invalid header value"));
+        }
+        Ok(DefaultHeader {
+            name: name.to_string(),
+            value: value.to_string(),
         })
     }
 }
 
 enum AttributeOption {
     Method(Ident, LitStr),
-    ReturnOverride(Type),
+    Ws(Ident, LitStr),
+    ReturnOverride(ArgumentType),
+    ReturnBincode(Type),
+    RawBytesReturn,
+    PathDefaults(Punctuated<PathDefault, Token![,]>),
+    QueryStatic(Punctuated<QueryPair, Token![,]>),
+    DefaultHeaders(Punctuated<DefaultHeader, Token![,]>),
+    TrailingSlash(TrailingSlash),
+    Leptos(Option<Type>),
+    NamedLifetimes,
+    OwnedQuery,
+    OwnedBody,
+    PrettyBody,
+    PathHelper,
+    Builder,
+    RequestHelper,
+    Raw,
+    PropagateTrace,
+    AllowUnknownExtractors,
+    ReturnsWithStatus,
+    ReturnsHeaders,
+    ReturnsFinalUrl,
+    FireAndForget(Span),
+    MergePatch(Span),
+    Retry(Retry),
+    WasmBindgen,
+    Compress(Compression),
+    Cookies,
+    Etag,
+    ExtraQuery,
+    HeaderMap,
+    Locale,
+    Host,
+    Credentials(Credentials),
+    QueryEncoding(QueryEncoding),
+    QueryKey(LitStr),
+    QueryArray(QueryArray),
+    NativeClient(NativeClient),
+    GraphQl,
+    ClientOnly,
+    Prefix(Expr),
+    ContentType(LitStr),
+    Accept(LitStr),
+    PathStruct(PathStruct),
+    Vis(Visibility),
+    Extractors(Extractors),
+    Format(Format),
+    ExpectVersion(LitStr),
+    ApiError(Type),
 }
 
 impl Parse for AttributeOption {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        const METHODS: &[&str] = &["get", "post", "put", "delete", "patch"];
+        const METHODS: &[&str] = &["get", "post", "put", "delete", "patch", "trace", "connect"];
 
         let name: Ident = input.parse()?;
+
+        if !input.peek(token::Paren) {
+            return if name == "leptos" {
+                Ok(AttributeOption::Leptos(None))
+            } else if name == "named_lifetimes" {
+                Ok(AttributeOption::NamedLifetimes)
+            } else if name == "pretty_body" {
+                Ok(AttributeOption::PrettyBody)
+            } else if name == "path_helper" {
+                Ok(AttributeOption::PathHelper)
+            } else if name == "builder" {
+                Ok(AttributeOption::Builder)
+            } else if name == "request_helper" {
+                Ok(AttributeOption::RequestHelper)
+            } else if name == "raw" {
+                Ok(AttributeOption::Raw)
+            } else if name == "propagate_trace" {
+                Ok(AttributeOption::PropagateTrace)
+            } else if name == "allow_unknown_extractors" {
+                Ok(AttributeOption::AllowUnknownExtractors)
+            } else if name == "returns_with_status" {
+                Ok(AttributeOption::ReturnsWithStatus)
+            } else if name == "returns_headers" {
+                Ok(AttributeOption::ReturnsHeaders)
+            } else if name == "returns_final_url" {
+                Ok(AttributeOption::ReturnsFinalUrl)
+            } else if name == "wasm_bindgen" {
+                Ok(AttributeOption::WasmBindgen)
+            } else if name == "raw_bytes_return" {
+                Ok(AttributeOption::RawBytesReturn)
+            } else if name == "cookies" {
+                Ok(AttributeOption::Cookies)
+            } else if name == "etag" {
+                Ok(AttributeOption::Etag)
+            } else if name == "header_map" {
+                Ok(AttributeOption::HeaderMap)
+            } else if name == "extra_query" {
+                Ok(AttributeOption::ExtraQuery)
+            } else if name == "locale" {
+                Ok(AttributeOption::Locale)
+            } else if name == "host" {
+                Ok(AttributeOption::Host)
+            } else if name == "graphql" {
+                Ok(AttributeOption::GraphQl)
+            } else if name == "client_only" {
+                Ok(AttributeOption::ClientOnly)
+            } else if name == "fire_and_forget" {
+                Ok(AttributeOption::FireAndForget(name.span()))
+            } else if name == "merge_patch" {
+                Ok(AttributeOption::MergePatch(name.span()))
+            } else {
+                Err(syn::Error::new(name.span(), "Unexpected option"))
+            };
+        }
+
         let content;
         parenthesized!(content in input);
 
         if METHODS.iter().any(|&method| name == method) {
             Ok(AttributeOption::Method(name, content.parse()?))
+        } else if name == "ws" {
+            Ok(AttributeOption::Ws(name, content.parse()?))
         } else if name == "returns" {
-            Ok(AttributeOption::ReturnOverride(content.parse()?))
+            Ok(AttributeOption::ReturnOverride(parse_return(&content)?))
+        } else if name == "returns_bincode" {
+            Ok(AttributeOption::ReturnBincode(content.parse()?))
+        } else if name == "path_default" {
+            Ok(AttributeOption::PathDefaults(
+                content.parse_terminated(PathDefault::parse, Token![,])?,
+            ))
+        } else if name == "path_struct" {
+            Ok(AttributeOption::PathStruct(content.parse()?))
+        } else if name == "vis" {
+            Ok(AttributeOption::Vis(content.parse()?))
+        } else if name == "extractors" {
+            Ok(AttributeOption::Extractors(content.parse()?))
+        } else if name == "query_static" {
+            Ok(AttributeOption::QueryStatic(
+                content.parse_terminated(QueryPair::parse, Token![,])?,
+            ))
+        } else if name == "default_headers" {
+            Ok(AttributeOption::DefaultHeaders(
+                content.parse_terminated(DefaultHeader::parse, Token![,])?,
+            ))
+        } else if name == "leptos" {
+            Ok(AttributeOption::Leptos(Some(content.parse()?)))
+        } else if name == "query" {
+            let flag: Ident = content.parse()?;
+            if flag == "owned" {
+                Ok(AttributeOption::OwnedQuery)
+            } else {
+                Err(syn::Error::new(flag.span(), "Expected `owned`"))
+            }
+        } else if name == "json" {
+            let flag: Ident = content.parse()?;
+            if flag == "pretty" {
+                Ok(AttributeOption::PrettyBody)
+            } else {
+                Err(syn::Error::new(flag.span(), "Expected `pretty`"))
+            }
+        } else if name == "body" {
+            let flag: Ident = content.parse()?;
+            if flag == "owned" {
+                Ok(AttributeOption::OwnedBody)
+            } else {
+                Err(syn::Error::new(flag.span(), "Expected `owned`"))
+            }
+        } else if name == "format" {
+            Ok(AttributeOption::Format(content.parse()?))
+        } else if name == "content_type" {
+            Ok(AttributeOption::ContentType(content.parse()?))
+        } else if name == "accept" {
+            Ok(AttributeOption::Accept(content.parse()?))
+        } else if name == "prefix" {
+            Ok(AttributeOption::Prefix(content.parse()?))
+        } else if name == "query_encoding" {
+            Ok(AttributeOption::QueryEncoding(content.parse()?))
+        } else if name == "query_array" {
+            Ok(AttributeOption::QueryArray(content.parse()?))
+        } else if name == "query_key" {
+            Ok(AttributeOption::QueryKey(content.parse()?))
+        } else if name == "native_client" {
+            Ok(AttributeOption::NativeClient(content.parse()?))
+        } else if name == "expect_version" {
+            Ok(AttributeOption::ExpectVersion(content.parse()?))
+        } else if name == "error" {
+            Ok(AttributeOption::ApiError(content.parse()?))
+        } else if name == "retry" {
+            Ok(AttributeOption::Retry(content.parse()?))
+        } else if name == "compress" {
+            Ok(AttributeOption::Compress(content.parse()?))
+        } else if name == "credentials" {
+            Ok(AttributeOption::Credentials(content.parse()?))
+        } else if name == "trailing_slash" {
+            Ok(AttributeOption::TrailingSlash(content.parse()?))
         } else {
             Err(syn::Error::new(name.span(), "Unexpected option"))
         }
     }
 }
+
+/// Parses the content of `returns(...)`. `()` means no body, `json(T)`
+/// explicitly decodes `T` as JSON and any other type is taken to be JSON too.
+fn parse_return(input: syn::parse::ParseStream) -> syn::Result<ArgumentType> {
+    let fork = input.fork();
+    if fork.parse::<Ident>().is_ok_and(|kind| kind == "stream") && fork.is_empty() {
+        input.parse::<Ident>()?;
+        return Ok(ArgumentType::Stream);
+    }
+
+    if input.peek(Ident) && input.peek2(token::Paren) {
+        let kind: Ident = input.parse()?;
+        let content;
+        parenthesized!(content in input);
+        // `status_json` only documents that the handler returns a
+        // `(StatusCode, Json<T>)` tuple, the body is decoded the same way
+        return if kind == "json" || kind == "status_json" {
+            Ok(ArgumentType::Json(content.parse()?))
+        } else if kind == "text" {
+            Ok(ArgumentType::Text(content.parse()?))
+        } else if kind == "ndjson" {
+            Ok(ArgumentType::NdJson(content.parse()?))
+        } else if kind == "headers" {
+            let headers = Punctuated::<Type, Token![,]>::parse_terminated(&content)?;
+            if headers.is_empty() {
+                return Err(syn::Error::new(
+                    kind.span(),
+                    "`headers(...)` needs at least one header type",
+                ));
+            }
+            Ok(ArgumentType::ResponseHeaders(headers.into_iter().collect()))
+        } else {
+            Err(syn::Error::new(
+                kind.span(),
+                "Expected `json(T)`, `text(T)`, `ndjson(T)`, `status_json(T)`, `headers(...)` or a type",
+            ))
+        };
+    }
+
+    Ok(match input.parse()? {
+        Type::Tuple(tuple) if tuple.elems.is_empty() => ArgumentType::Unit,
+        ty => ArgumentType::Json(ty),
+    })
+}
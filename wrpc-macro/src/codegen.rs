@@ -1,147 +1,2241 @@
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote, quote_spanned, ToTokens};
-use syn::{spanned::Spanned, Visibility};
+use syn::{parse_quote, spanned::Spanned, Ident, Type, Visibility};
 
-use crate::{argument::ArgumentType, attr::RpcAttribute, RpcSignature};
+use crate::{
+    argument::{vec_element, ArgumentType},
+    attr::{path_param, Credentials, Format, NativeClient, RpcAttribute},
+    RpcSignature,
+};
+
+mod ts;
+mod ws;
 
 impl ToTokens for ArgumentType {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
         let ty = match self {
             ArgumentType::Json(inner) => quote!(#inner),
             ArgumentType::Query(inner) => quote!(#inner),
+            ArgumentType::RawQuery => quote!(::std::option::Option<::std::string::String>),
             ArgumentType::Path(inners) => quote!(#(#inners),*),
             ArgumentType::Body => quote!(String),
-            ArgumentType::Ignored => quote!(),
+            ArgumentType::TypedHeader(inner) => quote!(#inner),
+            ArgumentType::HeaderMap => quote!(&[(&str, &str)]),
+            ArgumentType::Multipart => quote!(::std::vec::Vec<::wrpc::multipart::Part>),
+            ArgumentType::Unit => quote!(()),
+            ArgumentType::Bincode(inner) => quote!(#inner),
+            ArgumentType::Bytes => quote!(::wrpc::ClientBytes),
+            ArgumentType::Text(inner) => quote!(#inner),
+            ArgumentType::Stream => {
+                quote!(
+                    impl ::wrpc::Stream<Item = ::wrpc::Result<::wrpc::ClientBytes>>
+                        + ::std::marker::Send
+                )
+            }
+            ArgumentType::NdJson(inner) => {
+                quote!(
+                    impl ::wrpc::Stream<Item = ::std::result::Result<#inner, ::wrpc::Error>>
+                        + ::std::marker::Send
+                )
+            }
+            ArgumentType::Optional(inner) => quote!(::std::option::Option<#inner>),
+            // Named after the handler, see `response_headers_struct`
+            ArgumentType::ResponseHeaders(_) | ArgumentType::Ignored | ArgumentType::Unknown(_) => {
+                quote!()
+            }
         };
         tokens.extend(ty);
     }
 }
 
+/// The HTTP client a client function is generated for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Client {
+    Wasm,
+    Reqwest,
+    /// `reqwest::blocking`, for the `blocking` feature.
+    Blocking,
+}
+
+impl Client {
+    /// Awaits a future of this client, if it has any.
+    fn awaited(self) -> TokenStream {
+        match self {
+            Client::Blocking => quote!(),
+            Client::Wasm | Client::Reqwest => quote!(.await),
+        }
+    }
+}
+
 impl RpcSignature {
     pub fn to_tokens(&self, options: &RpcAttribute, vis: &Visibility) -> proc_macro2::TokenStream {
         let Self {
             name, return_type, ..
         } = self;
+        let vis = options.vis.as_ref().unwrap_or(vis);
+
+        let reference = reference(options);
+        let (args, path_args) = self.client_args(options, &reference);
+        let path_args = args[..path_args]
+            .iter()
+            .map(|(name, ty)| quote!(#name: #ty))
+            .collect::<Vec<_>>();
+        let args = args
+            .iter()
+            .map(|(name, ty)| quote!(#name: #ty))
+            .collect::<Vec<_>>();
+        println!("{options:?}");
+        let return_type = options.return_override.as_ref().unwrap_or(return_type);
+
+        let name = format_ident!("call_{name}");
+        // Values returned alongside the body, in the order of `Companion::all`
+        let companions = Companion::all(options)
+            .into_iter()
+            .map(Companion::ty)
+            .collect::<Vec<_>>();
+        let output = if let ArgumentType::ResponseHeaders(_) = return_type {
+            let struct_name = self.response_headers_name();
+            quote!(#struct_name)
+        } else if companions.is_empty() {
+            quote!(#return_type)
+        } else {
+            quote!((#(#companions,)* #return_type))
+        };
+        // `None` stands for a 304, the caller's cached copy is still current
+        let output = if options.etag {
+            quote!(::std::option::Option<#output>)
+        } else {
+            output
+        };
+        let result = if let Some(error) = &options.framework_error {
+            quote!(::std::result::Result<#output, #error>)
+        } else if let Some(error) = &options.api_error {
+            quote!(::std::result::Result<#output, ::wrpc::Error<#error>>)
+        } else if returns_wrpc_error(return_type, options) {
+            quote!(::std::result::Result<#output, ::wrpc::Error>)
+        } else {
+            quote!(::wrpc::Result<#output>)
+        };
+        // The handler's own generics carry over, after the shared lifetime
+        let mut generics = self.generics.clone();
+        if options.named_lifetimes {
+            generics.params.insert(0, parse_quote!('wrpc));
+        }
+        let (generics, _, where_clause) = generics.split_for_impl();
+        let allow = allowed_lints(args.len());
+        let sig = quote! {
+            #[must_use]
+            #allow
+            #vis async fn #name #generics(#(#args),*) -> #result #where_clause
+        };
+        let wasm_body = self.wasm_body(options);
+        // `ureq` clients are synchronous and always return `wrpc::Error`
+        let native_sig = match options.native_client {
+            NativeClient::Reqwest => sig.clone(),
+            NativeClient::Ureq => quote! {
+                #[must_use]
+                #allow
+                #vis fn #name #generics(#(#args),*) -> ::std::result::Result<#output, ::wrpc::Error> #where_clause
+            },
+        };
+        let native_body = match options.native_client {
+            NativeClient::Reqwest => self.reqwest_body(options, Client::Reqwest),
+            NativeClient::Ureq => self.ureq_body(options),
+        };
+        // Only the async clients are instrumented
+        let (wasm_body, native_body) = if cfg!(feature = "tracing") {
+            let native_body = match options.native_client {
+                NativeClient::Reqwest => self.instrumented(native_body, options, &result),
+                NativeClient::Ureq => native_body,
+            };
+            (self.instrumented(wasm_body, options, &result), native_body)
+        } else {
+            (wasm_body, native_body)
+        };
+
+        let probe = if cfg!(feature = "probe") {
+            let name = format_ident!("{}_probe", self.name);
+            let probe_body = self.probe_body(options);
+            let allow = allowed_lints(args.len() + 1);
+            quote! {
+                #[cfg(all(test, not(target_arch = "wasm32")))]
+                #allow
+                #vis async fn #name #generics(base: &str, #(#args),*) -> ::std::time::Duration #where_clause {
+                    #probe_body
+                }
+            }
+        } else {
+            quote!()
+        };
+
+        // Neither registration makes sense without the handler
+        let registered = !options.client_only;
+        // axum and OpenAPI don't know `CONNECT`, those handlers are routed by hand
+        let routed = registered && options.method != "connect";
+        let inventory = if cfg!(feature = "inventory") && routed {
+            let handler = &self.name;
+            let method = &options.method;
+            let method_name = method.to_string();
+            let path = &options.path;
+            let native_cfg = native_cfg();
+            quote! {
+                #native_cfg
+                ::wrpc::inventory::submit! {
+                    ::wrpc::routes::Route {
+                        method: #method_name,
+                        path: #path,
+                        route: || ::axum::routing::#method(#handler),
+                    }
+                }
+            }
+        } else {
+            quote!()
+        };
+
+        // WebSocket handlers get a connect function instead of the clients
+        if options.ws {
+            let connect = self.ws_connect(options, vis);
+            let deserialize_assertion = self.deserialize_assertion(options);
+            return quote! {
+                #connect
+                #inventory
+                #deserialize_assertion
+            };
+        }
+
+        let openapi = if cfg!(feature = "openapi") && routed {
+            self.openapi_operation(options)
+        } else {
+            quote!()
+        };
+
+        let ts = if cfg!(feature = "ts-export") && registered {
+            self.ts_function(options)
+        } else {
+            quote!()
+        };
+
+        let stream = is_stream(&self.return_type, options);
+        let wasm_unsupported = wasm_unsupported(return_type, options);
+        let spawn = if cfg!(feature = "spawn") && wasm_unsupported.is_none() {
+            // The future outlives the call, so nothing can be borrowed from it
+            let (spawn_args, _) = self.client_args(options, &quote!(&'static));
+            let names = spawn_args.iter().map(|(name, _)| name);
+            let allow = allowed_lints(spawn_args.len() + 1);
+            let spawn_args = spawn_args.iter().map(|(name, ty)| quote!(#name: #ty));
+            let spawn_name = format_ident!("spawn_{name}");
+            let wasm_cfg = wasm_cfg();
+            quote! {
+                #wasm_cfg
+                #allow
+                #vis fn #spawn_name #generics(#(#spawn_args,)* on_result: impl ::std::ops::FnOnce(#result) + 'static) #where_clause {
+                    ::wrpc::wasm_bindgen_futures::spawn_local(async move {
+                        on_result(#name(#(#names),*).await);
+                    });
+                }
+            }
+        } else {
+            quote!()
+        };
+
+        // Only `GET` requests can be prefetched by the browser
+        let prefetch = if cfg!(feature = "prefetch") && options.method == "get" {
+            let prefetch_name = format_ident!("prefetch_{}", self.name);
+            let path = self.path_string(options);
+            let wasm_cfg = wasm_cfg();
+            quote! {
+                #wasm_cfg
+                #[must_use]
+                #vis fn #prefetch_name #generics(#(#path_args),*) -> ::wrpc::prefetch::HtmlLinkElement #where_clause {
+                    ::wrpc::prefetch::link(&#path)
+                }
+            }
+        } else {
+            quote!()
+        };
+
+        let blocking = if cfg!(feature = "blocking") && self.supports_blocking(options) {
+            let blocking_name = format_ident!("{name}_blocking");
+            let blocking_body = self.reqwest_body(options, Client::Blocking);
+            let native_cfg = native_cfg();
+            quote! {
+                #native_cfg
+                #[must_use]
+                #allow
+                #vis fn #blocking_name #generics(#(#args),*) -> #result #where_clause {
+                    #blocking_body
+                }
+            }
+        } else {
+            quote!()
+        };
+
+        let wasm_bindgen = if options.wasm_bindgen {
+            self.wasm_bindgen_fn(options, vis)
+        } else {
+            quote!()
+        };
+
+        let raw = if options.raw {
+            let raw_name = format_ident!("{name}_raw");
+            // Only the errors raised before the body is read remain
+            let raw_result = if let Some(error) = &options.api_error {
+                quote!(::std::result::Result<::wrpc::Response, ::wrpc::Error<#error>>)
+            } else if options.expect_version.is_some() {
+                quote!(::std::result::Result<::wrpc::Response, ::wrpc::Error>)
+            } else {
+                quote!(::wrpc::Result<::wrpc::Response>)
+            };
+            let wasm_raw_body = self.raw_body(options, Client::Wasm);
+            let native_raw_body = self.raw_body(options, Client::Reqwest);
+            let wasm_cfg = wasm_cfg();
+            let native_cfg = native_cfg();
+            quote! {
+                #wasm_cfg
+                #[cfg_attr(docsrs, doc(hidden))]
+                #[must_use]
+                #allow
+                #vis async fn #raw_name #generics(#(#args),*) -> #raw_result #where_clause {
+                    #wasm_raw_body
+                }
+
+                #native_cfg
+                #[must_use]
+                #allow
+                #vis async fn #raw_name #generics(#(#args),*) -> #raw_result #where_clause {
+                    #native_raw_body
+                }
+            }
+        } else {
+            quote!()
+        };
+
+        let request_helper = if options.request_helper {
+            let name = format_ident!("{name}_request");
+            let describe_body = self.describe_body(options);
+            quote! {
+                #allow
+                #vis fn #name #generics(#(#args),*) -> ::wrpc::RequestDescription #where_clause {
+                    #describe_body
+                }
+            }
+        } else {
+            quote!()
+        };
+
+        let deserialize_assertion = self.deserialize_assertion(options);
+
+        let path_helper = if options.path_helper {
+            let name = format_ident!("{}_path", self.name);
+            let path = self.path_string(options);
+            quote! {
+                #vis fn #name #generics(#(#path_args),*) -> ::std::string::String #where_clause {
+                    #path
+                }
+            }
+        } else {
+            quote!()
+        };
+
+        let response_headers = match return_type {
+            ArgumentType::ResponseHeaders(headers) => self.response_headers_struct(headers, vis),
+            _ => quote!(),
+        };
+
+        let builder = if options.builder {
+            self.builder(options, vis, &result)
+        } else {
+            quote!()
+        };
+
+        // Test builds get a stub returning canned responses instead. Streams
+        // can't be canned, so their clients stay real
+        let (client_cfg, mock) = if cfg!(feature = "mock") && !stream {
+            let handler = self.name.to_string();
+            let mock = |sig: &TokenStream| {
+                quote! {
+                    #[cfg(test)]
+                    #[allow(unused_variables, clippy::ptr_arg)]
+                    #sig {
+                        ::wrpc::mock::response(#handler)
+                    }
+                }
+            };
+            let mock = match options.native_client {
+                NativeClient::Reqwest => mock(&sig),
+                NativeClient::Ureq => {
+                    let (wasm_mock, native_mock) = (mock(&sig), mock(&native_sig));
+                    let (wasm_cfg, native_cfg) = (wasm_cfg(), native_cfg());
+                    quote! {
+                        #wasm_cfg
+                        #wasm_mock
+
+                        #native_cfg
+                        #native_mock
+                    }
+                }
+            };
+            (quote!(#[cfg(not(test))]), mock)
+        } else {
+            (quote!(), quote!())
+        };
+
+        let wasm_cfg = wasm_cfg();
+        let native_cfg = native_cfg();
+
+        let wasm_client = if let Some(message) = wasm_unsupported {
+            quote! {
+                #wasm_cfg
+                ::std::compile_error!(#message);
+            }
+        } else {
+            quote! {
+                #wasm_cfg
+                #client_cfg
+                #[cfg_attr(docsrs, doc(hidden))]
+                #sig {
+                    #wasm_body
+                }
+            }
+        };
+
+        quote! {
+            #response_headers
+            #wasm_client
+
+            #wasm_bindgen
+            #spawn
+            #prefetch
+
+            #native_cfg
+            #client_cfg
+            #native_sig {
+                #native_body
+            }
 
+            #blocking
+            #raw
+            #mock
+            #path_helper
+            #builder
+            #request_helper
+            #probe
+            #inventory
+            #openapi
+            #ts
+            #deserialize_assertion
+        }
+    }
+
+    /// Asserts the `DeserializeOwned` bound the client puts on the handler's
+    /// return type, so a missing derive is reported at the type instead of deep
+    /// inside the client function. Serialized arguments don't need one, they're
+    /// spanned at their type where they're serialized. Generic handlers are
+    /// skipped, their parameters aren't in scope of the assertion.
+    fn deserialize_assertion(&self, options: &RpcAttribute) -> TokenStream {
+        if !self.generics.params.is_empty() {
+            return quote!();
+        }
+        let ty = match options
+            .return_override
+            .as_ref()
+            .unwrap_or(&self.return_type)
+        {
+            ArgumentType::Json(ty) | ArgumentType::Bincode(ty) | ArgumentType::NdJson(ty) => ty,
+            _ => return quote!(),
+        };
+
+        let assertion = quote_spanned!(ty.span() => assert_deserialize::<#ty>(););
+        quote! {
+            const _: fn() = || {
+                fn assert_deserialize<T: ::serde::de::DeserializeOwned>() {}
+                #assertion
+            };
+        }
+    }
+
+    /// Whether a `reqwest::blocking` client can be generated. The helpers
+    /// behind these options are async or take an async `reqwest::Response`.
+    fn supports_blocking(&self, options: &RpcAttribute) -> bool {
+        options.native_client == NativeClient::Reqwest
+            && options.retry.is_none()
+            && options.api_error.is_none()
+            && options.expect_version.is_none()
+            && Companion::all(options).is_empty()
+            && !options.etag
+            && self.multipart.is_none()
+            && !is_stream(&self.return_type, options)
+            && !matches!(
+                options.return_override,
+                Some(ArgumentType::ResponseHeaders(_))
+            )
+    }
+
+    /// `GetUserHeaders` for `get_user`, the struct returned for
+    /// `returns(headers(...))`.
+    fn response_headers_name(&self) -> Ident {
+        format_ident!("{}Headers", pascal_case(&self.name.to_string()))
+    }
+
+    /// The struct of `returns(headers(...))`, with an optional field per header
+    /// named after its type, i.e. `last_modified: Option<LastModified>`.
+    fn response_headers_struct(&self, headers: &[Type], vis: &Visibility) -> TokenStream {
+        let struct_name = self.response_headers_name();
+        let fields = headers.iter().map(|ty| {
+            let name = header_field(ty);
+            quote!(pub #name: ::std::option::Option<#ty>)
+        });
+        quote! {
+            #[derive(Debug, Clone)]
+            #vis struct #struct_name {
+                #(#fields,)*
+            }
+        }
+    }
+
+    /// The `builder` struct, named after the handler, i.e. `GetUserRequest` for
+    /// `get_user`. It holds the path and query arguments, which `validate`
+    /// makes sure are the only ones, and `send` passes them to the client
+    /// function. Arguments the client function takes as `Option` may be left
+    /// unset.
+    fn builder(
+        &self,
+        options: &RpcAttribute,
+        vis: &Visibility,
+        result: &TokenStream,
+    ) -> TokenStream {
+        let struct_name = format_ident!("{}Request", pascal_case(&self.name.to_string()));
+        let call = format_ident!("call_{}", self.name);
+        // Arguments the client function can do without are set afterwards,
+        // everything else is taken by `new`
+        let mut fields = Vec::new();
+        for (name, ty) in self.path.iter().flatten() {
+            let has_default = options
+                .path_defaults
+                .iter()
+                .any(|default| &default.name == name);
+            let (optional, arg) = match (has_default && !is_option(ty), is_string(ty) || is_map(ty))
+            {
+                (true, true) => (true, quote!(self.#name.as_deref())),
+                (true, false) => (true, quote!(self.#name)),
+                (false, true) => (false, quote!(&self.#name)),
+                (false, false) => (false, quote!(self.#name)),
+            };
+            fields.push((name, ty, optional, arg));
+        }
+        if let Some((name, ty)) = &self.query {
+            let arg = match (self.optional_query, options.owned_query) {
+                (true, false) => quote!(self.#name.as_ref()),
+                (false, false) => quote!(&self.#name),
+                (_, true) => quote!(self.#name),
+            };
+            fields.push((name, ty, self.optional_query, arg));
+        }
+        let args = fields.iter().map(|(.., arg)| arg);
+        let (optional, required): (Vec<_>, Vec<_>) =
+            fields.iter().partition(|(_, _, optional, _)| *optional);
+        let (required_names, required_types): (Vec<_>, Vec<_>) =
+            required.iter().map(|(name, ty, ..)| (*name, *ty)).unzip();
+        let (optional_names, optional_types): (Vec<_>, Vec<_>) =
+            optional.iter().map(|(name, ty, ..)| (*name, *ty)).unzip();
+        // A `new()` without arguments is expected to come with `Default`
+        let derive = required.is_empty().then(|| quote!(#[derive(Default)]));
+        let allow = allowed_lints(required.len());
+
+        quote! {
+            #derive
+            #vis struct #struct_name {
+                #(#required_names: #required_types,)*
+                #(#optional_names: ::std::option::Option<#optional_types>,)*
+            }
+
+            impl #struct_name {
+                #allow
+                #[must_use]
+                #vis fn new(#(#required_names: #required_types),*) -> Self {
+                    Self {
+                        #(#required_names,)*
+                        #(#optional_names: ::std::option::Option::None,)*
+                    }
+                }
+
+                #(
+                    #[must_use]
+                    #vis fn #optional_names(mut self, #optional_names: #optional_types) -> Self {
+                        self.#optional_names = ::std::option::Option::Some(#optional_names);
+                        self
+                    }
+                )*
+
+                #[must_use]
+                #vis async fn send(self) -> #result {
+                    #call(#(#args),*).await
+                }
+            }
+        }
+    }
+
+    /// The argument of a `Path<HashMap<String, String>>`, whose entries fill
+    /// in the segments of the same name.
+    pub fn path_map(&self) -> Option<(&Ident, &Type)> {
+        match self.path.as_deref() {
+            Some([(name, ty)]) if is_map(ty) => Some((name, ty)),
+            _ => None,
+        }
+    }
+
+    /// The arguments of the client function as `(name, type)` pairs, with
+    /// borrowed arguments taken as `reference`. The first `usize` arguments are
+    /// the ones making up the URL, i.e. `host`, path and query.
+    pub fn client_args(
+        &self,
+        options: &RpcAttribute,
+        reference: &TokenStream,
+    ) -> (Vec<(Ident, TokenStream)>, usize) {
         let mut args = Vec::new();
-        if let Some(vars) = &self.path {
-            let vars = vars.iter().map(|(name, ty)| quote!(#name: #ty));
+        if options.host {
+            args.push((format_ident!("host"), quote!(#reference str)));
+        }
+        if let Some((name, ty)) = self.path_map() {
+            args.push((name.clone(), quote!(#reference #ty)));
+        } else if let Some(vars) = &self.path {
+            let vars = vars.iter().map(|(name, ty)| {
+                let has_default = options
+                    .path_defaults
+                    .iter()
+                    .any(|default| &default.name == name);
+                let optional = has_default && !is_option(ty);
+                // `String` segments are borrowed, so callers needn't allocate
+                let ty = if is_string(ty) {
+                    quote!(#reference str)
+                } else {
+                    quote!(#ty)
+                };
+                if optional {
+                    (name.clone(), quote!(::std::option::Option<#ty>))
+                } else {
+                    (name.clone(), ty)
+                }
+            });
             args.extend(vars);
         }
         if let Some((name, ty)) = &self.query {
-            args.push(quote!(#name: &#ty));
+            let ty = if options.owned_query {
+                quote!(#ty)
+            } else {
+                quote!(#reference #ty)
+            };
+            if self.optional_query {
+                args.push((name.clone(), quote!(::std::option::Option<#ty>)));
+            } else {
+                args.push((name.clone(), quote!(#ty)));
+            }
+        }
+        if let Some(name) = &self.raw_query {
+            args.push((name.clone(), quote!(::std::option::Option<#reference str>)));
+        }
+        if options.extra_query {
+            args.push((
+                format_ident!("extra_query"),
+                quote!(#reference [(#reference str, #reference str)]),
+            ));
+        }
+        let path_args = args.len();
+        // GraphQL requests always carry the same envelope, whatever the handler
+        // extracts from it
+        if options.graphql {
+            args.push((format_ident!("query"), quote!(#reference str)));
+            args.push((
+                format_ident!("variables"),
+                quote!(#reference impl ::serde::Serialize),
+            ));
+        } else {
+            self.body_args(&mut args, options, reference);
+        }
+        for (name, ty) in &self.headers {
+            args.push((name.clone(), quote!(#reference #ty)));
+        }
+        if let Some(name) = &self.header_map {
+            args.push((
+                name.clone(),
+                quote!(#reference [(#reference str, #reference str)]),
+            ));
         }
+        if options.extra_headers {
+            args.push((
+                format_ident!("extra_headers"),
+                quote!(#reference ::wrpc::ClientHeaders),
+            ));
+        }
+        if options.locale {
+            args.push((format_ident!("locale"), quote!(#reference str)));
+        }
+        if options.cookies {
+            args.push((
+                format_ident!("cookie"),
+                quote!(::std::option::Option<#reference str>),
+            ));
+        }
+        if options.etag {
+            args.push((
+                format_ident!("etag"),
+                quote!(::std::option::Option<#reference str>),
+            ));
+        }
+        (args, path_args)
+    }
+
+    /// The body arguments of the client function, see `client_args`.
+    fn body_args(
+        &self,
+        args: &mut Vec<(Ident, TokenStream)>,
+        options: &RpcAttribute,
+        reference: &TokenStream,
+    ) {
         if let Some(name) = &self.body {
-            args.push(quote!(#name: &str));
+            args.push((name.clone(), quote!(#reference str)));
         }
         if let Some((name, ty)) = &self.json {
-            args.push(quote!(#name: &#ty));
+            let ty = match vec_element(ty) {
+                _ if options.owned_body => quote!(#ty),
+                Some(element) => quote!(#reference [#element]),
+                None => quote!(#reference #ty),
+            };
+            if self.optional_json {
+                args.push((name.clone(), quote!(::std::option::Option<#ty>)));
+            } else {
+                args.push((name.clone(), quote!(#ty)));
+            }
         }
-        println!("{options:?}");
-        let return_type = options.return_override.as_ref().unwrap_or(return_type);
+        if let Some(name) = &self.multipart {
+            args.push((
+                name.clone(),
+                quote!(::std::vec::Vec<::wrpc::multipart::Part>),
+            ));
+        }
+    }
 
-        let name = format_ident!("call_{name}");
-        let sig = quote!(#vis async fn #name(#(#args),*) -> ::wrpc::Result<#return_type>);
-        let wasm_body = self.wasm_body(options);
-        let reqwest_body = self.reqwest_body(options);
+    /// Registers a description of this handler for `wrpc::openapi::collect`.
+    fn openapi_operation(&self, options: &RpcAttribute) -> TokenStream {
+        let parameter = |name: &Ident, location: TokenStream, ty: String, required: bool| {
+            let name = name.to_string();
+            quote! {
+                ::wrpc::openapi::Parameter {
+                    name: #name,
+                    location: ::wrpc::openapi::Location::#location,
+                    ty: #ty,
+                    required: #required,
+                }
+            }
+        };
 
-        quote! {
-            #[cfg(target_arch = "wasm32")]
-            #sig {
-                #wasm_body
+        let mut parameters = Vec::new();
+        if self.path_map().is_some() {
+            for segment in options.path_params() {
+                let name = format_ident!("{segment}");
+                parameters.push(parameter(&name, quote!(Path), "String".to_string(), true));
+            }
+        } else {
+            for (name, ty) in self.path.iter().flatten() {
+                let ty = ty.to_token_stream().to_string();
+                parameters.push(parameter(name, quote!(Path), ty, true));
             }
+        }
+        if let Some((name, ty)) = &self.query {
+            let ty = ty.to_token_stream().to_string();
+            parameters.push(parameter(name, quote!(Query), ty, !self.optional_query));
+        }
+        if let Some(name) = &self.raw_query {
+            parameters.push(parameter(name, quote!(Query), "String".to_string(), false));
+        }
+        for (name, ty) in &self.headers {
+            let header = quote!(Header(|| <#ty as ::headers::Header>::name().as_str()));
+            let ty = ty.to_token_stream().to_string();
+            parameters.push(parameter(name, header, ty, true));
+        }
+        if let Some(name) = &self.body {
+            let body = quote!(Body("text/plain"));
+            parameters.push(parameter(name, body, "String".to_string(), true));
+        } else if let Some((name, ty)) = &self.json {
+            let content_type = options.format.content_type();
+            let ty = ty.to_token_stream().to_string();
+            let body = quote!(Body(#content_type));
+            parameters.push(parameter(name, body, ty, !self.optional_json));
+        } else if let Some(name) = &self.multipart {
+            let body = quote!(Body("multipart/form-data"));
+            parameters.push(parameter(name, body, "Multipart".to_string(), true));
+        }
 
-            #[cfg(not(target_arch = "wasm32"))]
-            #sig {
-                #reqwest_body
+        let name = self.name.to_string();
+        let method = options.method.to_string();
+        let path = &options.path;
+        let native_cfg = native_cfg();
+        quote! {
+            #native_cfg
+            ::wrpc::inventory::submit! {
+                ::wrpc::openapi::Operation {
+                    name: #name,
+                    method: #method,
+                    path: #path,
+                    parameters: &[#(#parameters),*],
+                }
             }
         }
     }
 
     pub fn wasm_body(&self, options: &RpcAttribute) -> proc_macro2::TokenStream {
-        let (path, request_signature) = self.request_signature(options);
-        let method = &options.method;
+        let request = self.wasm_request(options);
+        let call = wrap_result(options, self.call(request, options, Client::Wasm));
 
-        quote! {
-            ::reqwasm::http::Request::#method(#path)
-                #request_signature
+        if options.cookies {
+            quote! {
+                let _ = cookie;
+                #call
+            }
+        } else {
+            call
         }
     }
 
-    pub fn reqwest_body(&self, options: &RpcAttribute) -> proc_macro2::TokenStream {
-        let (path, request_signature) = self.request_signature(options);
-        let method = &options.method;
+    pub fn reqwest_body(&self, options: &RpcAttribute, client: Client) -> proc_macro2::TokenStream {
+        let request = self.reqwest_request(options);
+        let call = wrap_result(options, self.call(request, options, client));
+        let client = reqwest_client(options, client);
 
         quote! {
-            let client = ::reqwest::Client::new();
-            client.#method(#path)
-                #request_signature
+            let __wrpc_client = #client;
+            #call
         }
     }
 
-    fn request_signature(&self, options: &RpcAttribute) -> (TokenStream, TokenStream) {
-        let RpcAttribute {
-            path,
-            return_override,
-            ..
-        } = options;
+    /// Runs `body` in a span named after the client function, logging whether
+    /// it succeeded. `result` pins the error type `?` converts to.
+    fn instrumented(
+        &self,
+        body: TokenStream,
+        options: &RpcAttribute,
+        result: &TokenStream,
+    ) -> TokenStream {
+        let span_name = format!("call_{}", self.name);
+        let method = options.method.to_string().to_uppercase();
+        let path = &options.path;
+        quote! {
+            ::wrpc::instrument::call(
+                ::wrpc::instrument::info_span!(
+                    #span_name,
+                    method = #method,
+                    path = #path,
+                    status = ::wrpc::instrument::field::Empty,
+                ),
+                async move {
+                    let result: #result = { #body };
+                    result
+                },
+            )
+            .await
+        }
+    }
 
-        let mut segments = vec![];
-        let mut path = path
-            .split('/')
-            .map(|segment| {
-                if let Some(segment) = segment.strip_prefix(':') {
-                    segments.push(format_ident!("{segment}"));
-                    "{}".to_string()
-                } else {
-                    segment.to_string()
+    /// Body of the `raw` client function, which stops at the response instead
+    /// of reading its body.
+    fn raw_body(&self, options: &RpcAttribute, client: Client) -> TokenStream {
+        let (setup, request) = match client {
+            Client::Wasm => {
+                let setup = options.cookies.then(|| quote!(let _ = cookie;));
+                (quote!(#setup), self.wasm_request(options))
+            }
+            Client::Reqwest | Client::Blocking => {
+                let reqwest_client = reqwest_client(options, client);
+                (
+                    quote!(let __wrpc_client = #reqwest_client;),
+                    self.reqwest_request(options),
+                )
+            }
+        };
+        let request_signature = self.request_signature(request, options, client);
+
+        quote! {
+            #setup
+            #request_signature
+                .map(::wrpc::Response::from)
+        }
+    }
+
+    /// The `reqwasm` request before headers and body are set.
+    fn wasm_request(&self, options: &RpcAttribute) -> TokenStream {
+        let path = self.request_path(options);
+        let request = self.request(wasm_method(options, path));
+        // The browser manages cookies, it only has to be told to send them
+        let credentials = options
+            .credentials
+            .or(options.cookies.then_some(Credentials::Include));
+        match credentials {
+            Some(credentials) => {
+                let variant = credentials.variant();
+                quote! {
+                    #request.credentials(::reqwasm::http::RequestCredentials::#variant)
                 }
-            })
-            .collect::<Vec<_>>()
-            .join("/");
+            }
+            None => request,
+        }
+    }
 
-        let query_binding = if let Some((name, _)) = &self.query {
-            path += "?{}";
-            Some(quote!(::serde_qs::to_string(#name).unwrap()))
-        } else {
-            None
-        };
+    /// The `reqwest` request before headers and body are set, built from
+    /// `__wrpc_client`.
+    fn reqwest_request(&self, options: &RpcAttribute) -> TokenStream {
+        let path = self.request_path(options);
+        self.with_cookie(self.request(reqwest_method(options, path)), options)
+    }
 
-        let path = if !segments.is_empty() || query_binding.is_some() {
-            let mut segments = quote!(#(,#segments)*);
-            if let Some(query_binding) = query_binding {
-                segments.extend(quote!(,#query_binding));
+    /// Body of the synchronous `native_client(ureq)` client function.
+    /// `validate` rejects the options only implemented for `reqwest`.
+    pub fn ureq_body(&self, options: &RpcAttribute) -> TokenStream {
+        let path = self.request_path(options);
+        let method = options.method.to_string().to_uppercase();
+        let mut request = quote!(::wrpc::ureq::request(#method, #path));
+        if let Some(name) = &self.header_map {
+            request = quote! {
+                #name.iter().fold(#request, |request, (name, value)| request.set(name, value))
+            };
+        }
+        let accept = self
+            .accept(options)
+            .map(|accept| quote!(.set("Accept", #accept)));
+        let defaults = options.default_headers.iter().map(|header| {
+            let (name, value) = (&header.name, &header.value);
+            quote!(.set(#name, #value))
+        });
+        // `ureq` only takes string values, so they're converted up front
+        let header_values = self.headers.iter().map(|(name, ty)| {
+            let values = typed_header_values(name);
+            quote! {
+                let #name = ::wrpc::ureq::header_value(
+                    <#ty as ::headers::Header>::name().as_str(),
+                    &#values,
+                )?;
             }
-            quote!(&::std::format!(#path #segments))
-        } else {
-            quote!(#path)
+        });
+        let headers = self
+            .headers
+            .iter()
+            .map(|(name, ty)| quote!(.set(<#ty as ::headers::Header>::name().as_str(), &#name)));
+        let locale = options
+            .locale
+            .then(|| quote!(.set("Accept-Language", locale)));
+
+        let send = match self.body_parts(options) {
+            Some((body_headers, body)) => {
+                let body_headers = body_headers
+                    .iter()
+                    .map(|(name, value)| quote!(.set(#name, #value)));
+                let send = quote!(::wrpc::ureq::send(__wrpc_request #(#body_headers)*, #body));
+                match &self.json {
+                    Some((name, _)) if self.optional_json && !options.graphql => quote! {
+                        match #name {
+                            ::std::option::Option::Some(#name) => #send,
+                            ::std::option::Option::None => ::wrpc::ureq::call(__wrpc_request),
+                        }
+                    },
+                    _ => send,
+                }
+            }
+            None => quote!(::wrpc::ureq::call(__wrpc_request)),
         };
 
-        let body = if let Some(name) = &self.body {
-            quote!(.body(::std::string::ToString::to_string(#name)))
-        } else if let Some((name, _)) = &self.json {
-            quote!(.body(::serde_json::to_string(#name).unwrap()))
-        } else {
-            quote!()
+        let return_type = options
+            .return_override
+            .as_ref()
+            .unwrap_or(&self.return_type);
+        let extractor = match return_type {
+            ArgumentType::Json(ty) if options.graphql => quote! {
+                .and_then(::wrpc::ureq::json::<::wrpc::graphql::Response<#ty>>)
+                .map(|response| response.data)
+            },
+            ArgumentType::Json(_) => match options.format.decode() {
+                Some(decode) => quote! {
+                    .and_then(::wrpc::ureq::bytes)
+                    .and_then(|bytes| #decode)
+                },
+                None => quote!(.and_then(::wrpc::ureq::json)),
+            },
+            ArgumentType::Unit => quote!(.map(|_| ())),
+            ArgumentType::Bincode(_) => quote! {
+                .and_then(::wrpc::ureq::bytes)
+                .and_then(|bytes| ::bincode::deserialize(&bytes).map_err(::wrpc::Error::decode))
+            },
+            ArgumentType::Bytes => quote!(.and_then(::wrpc::ureq::bytes)),
+            ArgumentType::Text(ty) => quote! {
+                .and_then(::wrpc::ureq::text)
+                .and_then(|text| text.parse::<#ty>().map_err(::wrpc::Error::decode))
+            },
+            _ => quote!(.and_then(::wrpc::ureq::text)),
         };
 
-        let result_extractor = if matches!(self.return_type, ArgumentType::Json(_)) {
-            quote_spanned!(self.return_type.span() => .json())
-        } else if let Some(return_override) = return_override {
-            quote_spanned!(return_override.span() => .json())
-        } else {
-            quote_spanned!(self.return_type.span() => .text())
+        let request = with_trace(
+            quote!(#request #accept #(#defaults)* #(#headers)* #locale),
+            options,
+            quote!(set),
+        );
+
+        quote! {
+            #(#header_values)*
+            let __wrpc_request = #request;
+            #send
+                #extractor
+        }
+    }
+
+    /// A `#[wasm_bindgen]` export of the WASM client function. Arguments and
+    /// the result cross the JS boundary as `JsValue`s converted with
+    /// `serde_wasm_bindgen` rather than as JSON strings.
+    fn wasm_bindgen_fn(&self, options: &RpcAttribute, vis: &Visibility) -> TokenStream {
+        let mut params = Vec::new();
+        let mut conversions = Vec::new();
+        let mut call_args = Vec::new();
+        let mut convert = |name: &Ident, ty: TokenStream, arg: TokenStream| {
+            params.push(quote!(#name: ::wasm_bindgen::JsValue));
+            conversions.push(quote!(let #name: #ty = ::serde_wasm_bindgen::from_value(#name)?;));
+            call_args.push(arg);
         };
 
-        (
-            path,
-            quote! {
-                #body
-                .send()
-                .await?
-                #result_extractor
-                .await
+        if options.host {
+            convert(
+                &format_ident!("host"),
+                quote!(::std::string::String),
+                quote!(&host),
+            );
+        }
+        for (name, ty) in self.path.iter().flatten() {
+            let has_default = options
+                .path_defaults
+                .iter()
+                .any(|default| &default.name == name);
+            match (has_default && !is_option(ty), is_string(ty) || is_map(ty)) {
+                (true, true) => convert(
+                    name,
+                    quote!(::std::option::Option<#ty>),
+                    quote!(#name.as_deref()),
+                ),
+                (true, false) => convert(name, quote!(::std::option::Option<#ty>), quote!(#name)),
+                (false, true) => convert(name, quote!(#ty), quote!(&#name)),
+                (false, false) => convert(name, quote!(#ty), quote!(#name)),
+            }
+        }
+        if let Some((name, ty)) = &self.query {
+            match (self.optional_query, options.owned_query) {
+                (true, true) => convert(name, quote!(::std::option::Option<#ty>), quote!(#name)),
+                (true, false) => convert(
+                    name,
+                    quote!(::std::option::Option<#ty>),
+                    quote!(#name.as_ref()),
+                ),
+                (false, true) => convert(name, quote!(#ty), quote!(#name)),
+                (false, false) => convert(name, quote!(#ty), quote!(&#name)),
+            }
+        }
+        if let Some(name) = &self.raw_query {
+            convert(
+                name,
+                quote!(::std::option::Option<::std::string::String>),
+                quote!(#name.as_deref()),
+            );
+        }
+        if let Some(name) = &self.body {
+            convert(name, quote!(::std::string::String), quote!(&#name));
+        }
+        if let Some((name, ty)) = &self.json {
+            if options.owned_body {
+                let ty = if self.optional_json {
+                    quote!(::std::option::Option<#ty>)
+                } else {
+                    quote!(#ty)
+                };
+                convert(name, ty, quote!(#name));
+            } else if !self.optional_json {
+                convert(name, quote!(#ty), quote!(&#name));
+            } else if vec_element(ty).is_some() {
+                convert(
+                    name,
+                    quote!(::std::option::Option<#ty>),
+                    quote!(#name.as_deref()),
+                );
+            } else {
+                convert(
+                    name,
+                    quote!(::std::option::Option<#ty>),
+                    quote!(#name.as_ref()),
+                );
+            }
+        }
+        if let Some(name) = &self.multipart {
+            convert(
+                name,
+                quote!(::std::vec::Vec<::wrpc::multipart::Part>),
+                quote!(#name),
+            );
+        }
+
+        if options.locale {
+            convert(
+                &format_ident!("locale"),
+                quote!(::std::string::String),
+                quote!(&locale),
+            );
+        }
+        if options.cookies {
+            call_args.push(quote!(::std::option::Option::None));
+        }
+        if options.etag {
+            call_args.push(quote!(::std::option::Option::None));
+        }
+
+        let js_name = self.name.to_string();
+        let name = format_ident!("{}_js", self.name);
+        let client_fn = format_ident!("call_{}", self.name);
+        let wasm_cfg = wasm_cfg();
+        let allow = allowed_lints(params.len());
+        quote! {
+            #wasm_cfg
+            #[::wasm_bindgen::prelude::wasm_bindgen(js_name = #js_name)]
+            #allow
+            #vis async fn #name(#(#params),*) -> ::std::result::Result<::wasm_bindgen::JsValue, ::wasm_bindgen::JsValue> {
+                #(#conversions)*
+                let result = #client_fn(#(#call_args),*)
+                    .await
+                    .map_err(|err| ::wasm_bindgen::JsValue::from_str(&err.to_string()))?;
+                ::std::result::Result::Ok(::serde_wasm_bindgen::to_value(&result)?)
+            }
+        }
+    }
+
+    /// Sends the request against `base` and measures a single round trip,
+    /// ignoring the response entirely.
+    pub fn probe_body(&self, options: &RpcAttribute) -> proc_macro2::TokenStream {
+        let path = self.request_path(options);
+        let request = self.request(reqwest_method(
+            options,
+            quote!(::std::format!("{}{}", base, #path)),
+        ));
+        let request = self.with_cookie(request, options);
+        let headers = self.request_headers(options, Client::Reqwest);
+        let request = with_etag(quote!(#request #headers), options);
+        let request = with_extra_headers(request, options, Client::Reqwest);
+        let request = self.with_body(request, options, Client::Reqwest);
+        let client = reqwest_client(options, Client::Reqwest);
+
+        quote! {
+            let __wrpc_client = #client;
+            let start = ::std::time::Instant::now();
+            let _ = #request
+                .send()
+                .await;
+            start.elapsed()
+        }
+    }
+
+    /// Wraps the freshly created request builder so a caller supplied header
+    /// list is applied before anything else.
+    fn request(&self, builder: TokenStream) -> TokenStream {
+        if let Some(name) = &self.header_map {
+            quote! {
+                #name.iter().fold(#builder, |request, (name, value)| request.header(*name, *value))
+            }
+        } else {
+            builder
+        }
+    }
+
+    /// Adds the caller supplied `Cookie` header to a `reqwest` request, if
+    /// there is one.
+    fn with_cookie(&self, request: TokenStream, options: &RpcAttribute) -> TokenStream {
+        if options.cookies {
+            quote! {
+                cookie.into_iter().fold(#request, |request, cookie| request.header("Cookie", cookie))
+            }
+        } else {
+            request
+        }
+    }
+
+    /// Sends `request` and extracts the result from the response.
+    fn call(&self, request: TokenStream, options: &RpcAttribute, client: Client) -> TokenStream {
+        let request_signature = self.request_signature(request, options, client);
+        if !options.etag {
+            return self.extract(Some(request_signature), options, client);
+        }
+
+        // A 304 has no body, the caller's cached copy is still current
+        let extract = self.extract(None, options, client);
+        quote! {
+            {
+                let response = #request_signature?;
+                if ::wrpc::response::not_modified(&response) {
+                    ::std::result::Result::Ok(::std::option::Option::None)
+                } else {
+                    ::std::result::Result::map(#extract, ::std::option::Option::Some)
+                }
+            }
+        }
+    }
+
+    /// Extracts the result from the response `request_signature` results in,
+    /// or from the already received `response` for `None`.
+    fn extract(
+        &self,
+        request_signature: Option<TokenStream>,
+        options: &RpcAttribute,
+        client: Client,
+    ) -> TokenStream {
+        // Only the headers are read, the body is dropped along with the response
+        if let Some(ArgumentType::ResponseHeaders(headers)) = &options.return_override {
+            let struct_name = self.response_headers_name();
+            let response = request_signature
+                .map(|request_signature| quote!(let response = #request_signature?;));
+            let fields = headers.iter().map(|ty| {
+                let name = header_field(ty);
+                quote! {
+                    #name: ::wrpc::response::header(&response, <#ty as ::headers::Header>::name().as_str())
+                        .and_then(|value| ::headers::HeaderValue::from_str(&value).ok())
+                        .and_then(|value| <#ty as ::headers::Header>::decode(&mut ::std::iter::once(&value)).ok())
+                }
+            });
+            return quote! {
+                {
+                    #response
+                    ::std::result::Result::Ok(#struct_name {
+                        #(#fields,)*
+                    })
+                }
+            };
+        }
+
+        let result_extractor = self.result_extractor(options, client);
+        // Streams are returned as is, they can only fail while being read
+        let stream = is_stream(&self.return_type, options);
+        let response = match &request_signature {
+            Some(request_signature) => quote!(#request_signature?),
+            None => quote!(response),
+        };
+
+        let companions = Companion::all(options);
+        if !companions.is_empty() {
+            let names = companions.iter().map(|companion| companion.name());
+            let result = match result_extractor {
+                Some(extractor) if stream => {
+                    let body = self.stream_body(quote!(response #extractor), options);
+                    quote!(::std::result::Result::Ok((#(#names,)* #body)))
+                }
+                Some(extractor) => quote!(response #extractor.map(|body| (#(#names,)* body))),
+                None => quote!(::std::result::Result::Ok((#(#names,)* ()))),
+            };
+            let bindings = companions.iter().map(|companion| {
+                let name = companion.name();
+                let value = companion.value();
+                quote!(let #name = #value;)
+            });
+            let response = request_signature.map(|_| quote!(let response = #response;));
+            quote! {
+                {
+                    #response
+                    #(#bindings)*
+                    #result
+                }
+            }
+        } else {
+            match (result_extractor, request_signature) {
+                (Some(extractor), _) if stream => {
+                    let body = self.stream_body(quote!(#response #extractor), options);
+                    quote!(::std::result::Result::Ok(#body))
+                }
+                (Some(extractor), _) => quote! {
+                    #response
+                        #extractor
+                },
+                (None, Some(request_signature)) => quote! {
+                    #request_signature
+                        .map(|_| ())
+                },
+                (None, None) => quote!(::std::result::Result::Ok(())),
+            }
+        }
+    }
+
+    /// Wraps the chunks of a streamed body in the adapter its return type asks
+    /// for, if any.
+    fn stream_body(&self, chunks: TokenStream, options: &RpcAttribute) -> TokenStream {
+        match options
+            .return_override
+            .as_ref()
+            .unwrap_or(&self.return_type)
+        {
+            ArgumentType::NdJson(ty) => quote!(::wrpc::ndjson::lines::<#ty>(#chunks)),
+            _ => chunks,
+        }
+    }
+
+    /// Sets headers and body on `request` and sends it, resulting in the
+    /// response.
+    fn request_signature(
+        &self,
+        request: TokenStream,
+        options: &RpcAttribute,
+        client: Client,
+    ) -> TokenStream {
+        let headers = self.request_headers(options, client);
+        let request = with_etag(quote!(#request #headers), options);
+        let request = with_extra_headers(request, options, client);
+        let request = match client {
+            Client::Wasm => request,
+            Client::Reqwest | Client::Blocking => with_trace(request, options, quote!(header)),
+        };
+        let request = self.with_body(request, options, client);
+        let send = if let Some(retry) = &options.retry {
+            let count = &retry.count;
+            let backoff_ms = &retry.backoff_ms;
+            quote!(::wrpc::retry::send(#count, #backoff_ms, || #request).await)
+        } else {
+            let awaited = client.awaited();
+            quote!(#request.send() #awaited)
+        };
+        let send = match client {
+            Client::Wasm => self.with_typed_headers(send),
+            Client::Reqwest | Client::Blocking => send,
+        };
+        // Recorded on the span opened by `instrumented`
+        let send = match client {
+            Client::Wasm | Client::Reqwest if cfg!(feature = "tracing") => {
+                quote!(#send.map(::wrpc::instrument::status))
+            }
+            _ => send,
+        };
+        // Error responses are turned into `wrpc::Error::Api` before anything
+        // else looks at them
+        let send = if options.api_error.is_some() {
+            quote!(::wrpc::response::api_error(#send).await)
+        } else {
+            send
+        };
+
+        let response_check = if let Some(version) = &options.expect_version {
+            let request_error = if options.api_error.is_some() {
+                quote!()
+            } else {
+                quote!(.map_err(::wrpc::Error::Request))
+            };
+            quote! {
+                #request_error
+                .and_then(|response| ::wrpc::response::expect_version(response, #version))
+            }
+        } else {
+            quote!()
+        };
+
+        quote! {
+            #send
+            #response_check
+        }
+    }
+
+    /// Binds each typed header argument to its value as the string `reqwasm`
+    /// takes, failing `send` instead of sending it if that's not possible.
+    /// Parenthesized, since `send` may start a statement.
+    fn with_typed_headers(&self, send: TokenStream) -> TokenStream {
+        self.headers.iter().rev().fold(send, |send, (name, ty)| {
+            let values = typed_header_values(name);
+            quote! {
+                (match ::wrpc::request::header_value(
+                    <#ty as ::headers::Header>::name().as_str(),
+                    &#values,
+                ) {
+                    ::std::result::Result::Ok(#name) => #send,
+                    ::std::result::Result::Err(err) => ::std::result::Result::Err(err),
+                })
+            }
+        })
+    }
+
+    /// Sets the body on `request`. An optional body is only set when the
+    /// caller passes `Some`.
+    fn with_body(
+        &self,
+        request: TokenStream,
+        options: &RpcAttribute,
+        client: Client,
+    ) -> TokenStream {
+        let body = self.request_body(options, client);
+        match &self.json {
+            // Borrowed, so a `retry` closure can build the request again
+            Some((name, _)) if self.optional_json && options.owned_body => quote! {
+                #name.iter().fold(#request, |request, #name| request #body)
+            },
+            Some((name, _)) if self.optional_json => quote! {
+                #name.into_iter().fold(#request, |request, #name| request #body)
+            },
+            _ => quote!(#request #body),
+        }
+    }
+
+    /// Reads the body of a successfully received `response`, or `None` if
+    /// the body is discarded.
+    fn result_extractor(&self, options: &RpcAttribute, client: Client) -> Option<TokenStream> {
+        let return_type = options
+            .return_override
+            .as_ref()
+            .unwrap_or(&self.return_type);
+        let bytes = match client {
+            Client::Wasm => quote!(binary),
+            Client::Reqwest | Client::Blocking => quote!(bytes),
+        };
+        let awaited = client.awaited();
+        // Client errors only convert implicitly at a `?`, so any extractor that
+        // ends in one needs an explicit conversion once `wrpc::Error` is used.
+        let request_error = if returns_wrpc_error(return_type, options) {
+            quote!(.map_err(::wrpc::Error::Request))
+        } else {
+            quote!()
+        };
+        let result_extractor = match return_type {
+            ArgumentType::Json(ty) if options.graphql => quote_spanned! {return_type.span() =>
+                .json::<::wrpc::graphql::Response<#ty>>()
+                #awaited
+                .map(|response| response.data)
+                #request_error
+            },
+            ArgumentType::Json(_) => match options.format.decode() {
+                Some(decode) => decode_bytes(return_type, &bytes, client, decode),
+                None => quote_spanned!(return_type.span() => .json() #awaited #request_error),
             },
-        )
+            ArgumentType::Unit => return None,
+            ArgumentType::Bincode(_) => decode_bytes(
+                return_type,
+                &bytes,
+                client,
+                quote!(::bincode::deserialize(&bytes).map_err(::wrpc::Error::decode)),
+            ),
+            // Only used natively, the WASM client doesn't exist
+            ArgumentType::Stream | ArgumentType::NdJson(_) => {
+                quote_spanned!(return_type.span() => .bytes_stream())
+            }
+            ArgumentType::Bytes => {
+                quote_spanned!(return_type.span() => .#bytes() #awaited #request_error)
+            }
+            ArgumentType::Text(ty) => quote_spanned! {return_type.span() =>
+                .text()
+                #awaited
+                .map_err(::wrpc::Error::Request)
+                .and_then(|text| text.parse::<#ty>().map_err(::wrpc::Error::decode))
+            },
+            _ => quote_spanned!(return_type.span() => .text() #awaited #request_error),
+        };
+        Some(result_extractor)
+    }
+
+    fn request_path(&self, options: &RpcAttribute) -> TokenStream {
+        if options.extra_query {
+            let path = self.path_string(options);
+            return quote!(&#path);
+        }
+        match self.path_format(options) {
+            (path, None) => quote!(#path),
+            (path, Some(args)) => quote!(&::std::format!(#path #args)),
+        }
+    }
+
+    /// The path as an owned `String`, for the `path_helper` function.
+    fn path_string(&self, options: &RpcAttribute) -> TokenStream {
+        // Whether the path already has a query is only known at runtime with
+        // optional queries, so the caller's pairs are appended to the result
+        match (self.path_format(options), options.extra_query) {
+            ((path, None), true) => quote!(::wrpc::query::append(#path, extra_query)),
+            ((path, Some(args)), true) => {
+                quote!(::wrpc::query::append(
+                    &::std::format!(#path #args),
+                    extra_query
+                ))
+            }
+            ((path, None), false) => quote!(::std::string::ToString::to_string(#path)),
+            ((path, Some(args)), false) => quote!(::std::format!(#path #args)),
+        }
+    }
+
+    /// Splits the path into a format string and its arguments. There are no
+    /// arguments if the path is static.
+    fn path_format(&self, options: &RpcAttribute) -> (String, Option<TokenStream>) {
+        let mut segments = vec![];
+        let mut path = options
+            .path
+            .split('/')
+            .map(|segment| {
+                if let Some(param) = path_param(segment) {
+                    // Every segment is looked up by name
+                    if let Some((map, _)) = self.path_map() {
+                        let message =
+                            format!("`{map}` has no entry for the path segment `{segment}`");
+                        segments.push(quote!(#map.get(#param).expect(#message)));
+                        return "{}".to_string();
+                    }
+                    let name = format_ident!("{param}");
+                    let default = options
+                        .path_defaults
+                        .iter()
+                        .find(|default| default.name == name);
+                    let format = self
+                        .path_formats
+                        .iter()
+                        .find(|(segment, _)| segment == &name)
+                        .map(|(_, format)| format);
+                    match (default, format) {
+                        (Some(default), None) => {
+                            let value = &default.value;
+                            segments.push(quote! {
+                                #name.as_ref().map_or_else(
+                                    || ::std::string::ToString::to_string(&#value),
+                                    ::std::string::ToString::to_string,
+                                )
+                            });
+                        }
+                        (Some(default), Some(format)) => {
+                            let value = &default.value;
+                            segments.push(quote! {
+                                #name.as_ref().map_or_else(
+                                    || ::std::format!(#format, #value),
+                                    |#name| ::std::format!(#format, #name),
+                                )
+                            });
+                        }
+                        (None, Some(format)) => {
+                            segments.push(quote!(#name));
+                            return format.value();
+                        }
+                        (None, None) => segments.push(quote!(#name)),
+                    }
+                    "{}".to_string()
+                } else {
+                    segment.to_string()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+
+        // Static pairs follow the dynamic query, so they need a `&` after it
+        // or a `?` of their own
+        let static_query = options
+            .query_static
+            .iter()
+            .map(|pair| format!("{}={{}}", pair.key.value()))
+            .collect::<Vec<_>>()
+            .join("&");
+        let static_args = options.query_static.iter().map(|pair| {
+            let value = &pair.value;
+            quote!(::wrpc::query::encode(&#value))
+        });
+
+        let serializer = options.query_encoding.serializer();
+        let serialize = |query: TokenStream| {
+            let query = match options.query_array {
+                Some(style) => {
+                    let style = style.variant();
+                    quote!(::wrpc::query::to_string(#query, ::wrpc::query::Arrays::#style))
+                }
+                None => quote!(::#serializer::to_string(#query).unwrap()),
+            };
+            match &options.query_key {
+                Some(key) => quote!(::wrpc::query::nest(#key, &#query)),
+                None => query,
+            }
+        };
+        let query_binding = if let Some((name, ty)) = &self.query {
+            let name = &spanned_at(name, ty);
+            if self.optional_query {
+                // The `?` is only added along with a non-empty query, so
+                // `None` leaves the path untouched
+                path += "{}";
+                path += &static_query;
+                let name = if options.owned_query {
+                    quote!(#name.as_ref())
+                } else {
+                    quote!(#name)
+                };
+                let more = !static_query.is_empty();
+                let query = serialize(quote_spanned!(ty.span() => query));
+                Some(quote! {
+                    ::wrpc::query::optional(#name.map(|query| #query), #more)
+                })
+            } else {
+                path += "?{}";
+                if !static_query.is_empty() {
+                    path += "&";
+                    path += &static_query;
+                }
+                if options.owned_query {
+                    Some(serialize(quote_spanned!(ty.span() => &#name)))
+                } else {
+                    Some(serialize(quote!(#name)))
+                }
+            }
+        } else if let Some(name) = &self.raw_query {
+            // Passed through verbatim, with the same `?` handling as optional
+            // queries
+            path += "{}";
+            path += &static_query;
+            let more = !static_query.is_empty();
+            Some(quote! {
+                ::wrpc::query::optional(#name.map(::std::string::ToString::to_string), #more)
+            })
+        } else {
+            if !static_query.is_empty() {
+                path += "?";
+                path += &static_query;
+            }
+            None
+        };
+
+        segments.extend(query_binding);
+        segments.extend(static_args);
+        if options.host {
+            if !path.starts_with('/') {
+                path.insert(0, '/');
+            }
+            path.insert_str(0, "https://{}");
+            segments.insert(0, quote!(host));
+        }
+        if let Some(prefix) = &options.prefix {
+            // The prefix is only known at runtime, so its trailing `/` is
+            // trimmed there to join the two with exactly one
+            if !path.starts_with('/') {
+                path.insert(0, '/');
+            }
+            path.insert_str(0, "{}");
+            segments.insert(
+                0,
+                quote!(::std::primitive::str::trim_end_matches(#prefix, '/')),
+            );
+        }
+        if !segments.is_empty() {
+            (path, Some(quote!(#(,#segments)*)))
+        } else {
+            (path, None)
+        }
+    }
+
+    /// The `Accept` header sent with the request. It's set by `accept(...)` or
+    /// follows `format(...)` for structured responses and `ndjson(T)`, unless a default header
+    /// sets it already.
+    fn accept(&self, options: &RpcAttribute) -> Option<String> {
+        let default = options
+            .default_headers
+            .iter()
+            .any(|header| header.name.eq_ignore_ascii_case("accept"));
+        if default {
+            return None;
+        }
+        if let Some(accept) = &options.accept {
+            return Some(accept.value());
+        }
+        let return_type = options
+            .return_override
+            .as_ref()
+            .unwrap_or(&self.return_type);
+        match return_type {
+            ArgumentType::Json(_) => Some(options.format.content_type().to_string()),
+            ArgumentType::NdJson(_) => Some("application/x-ndjson".to_string()),
+            _ => None,
+        }
+    }
+
+    /// The headers set on every request. On WASM, typed headers are read
+    /// from the strings `with_typed_headers` binds to their argument names.
+    fn request_headers(&self, options: &RpcAttribute, client: Client) -> TokenStream {
+        let accept = self
+            .accept(options)
+            .map(|accept| quote!(.header("Accept", #accept)));
+        let defaults = options.default_headers.iter().map(|header| {
+            let (name, value) = (&header.name, &header.value);
+            quote!(.header(#name, #value))
+        });
+        let headers = self.headers.iter().map(|(name, ty)| match client {
+            Client::Wasm => quote!(.header(<#ty as ::headers::Header>::name().as_str(), &#name)),
+            Client::Reqwest | Client::Blocking => {
+                let values = typed_header_values(name);
+                quote! {
+                    .headers(
+                        #values
+                            .into_iter()
+                            .map(|value| (<#ty as ::headers::Header>::name().clone(), value))
+                            .collect::<::headers::HeaderMap>(),
+                    )
+                }
+            }
+        });
+        let locale = options
+            .locale
+            .then(|| quote!(.header("Accept-Language", locale)));
+        quote!(#accept #(#defaults)* #(#headers)* #locale)
+    }
+
+    fn request_body(&self, options: &RpcAttribute, client: Client) -> TokenStream {
+        if let Some(name) = self.multipart.as_ref().filter(|_| !options.graphql) {
+            return match client {
+                Client::Wasm => quote!(.body(::wrpc::multipart::form(#name))),
+                Client::Reqwest | Client::Blocking => {
+                    quote!(.multipart(::wrpc::multipart::form(#name)))
+                }
+            };
+        }
+
+        match self.body_parts(options) {
+            Some((headers, body)) => {
+                let headers = headers
+                    .iter()
+                    .map(|(name, value)| quote!(.header(#name, #value)));
+                quote!(#(#headers)* .body(#body))
+            }
+            None => quote!(),
+        }
+    }
+
+    /// The headers describing the serialized body and the expression
+    /// serializing it, or `None` if there's no body. Multipart bodies are
+    /// left to `request_body`.
+    fn body_parts(&self, options: &RpcAttribute) -> Option<(Vec<(&str, String)>, TokenStream)> {
+        let (content_type, body) = if options.graphql {
+            (
+                Some("application/json"),
+                quote!(
+                    ::serde_json::to_string(&::wrpc::graphql::Request { query, variables })
+                        .unwrap()
+                ),
+            )
+        } else if let Some(name) = &self.body {
+            (None, quote!(::std::string::ToString::to_string(#name)))
+        } else if let Some((name, ty)) = &self.json {
+            let name = spanned_at(name, ty);
+            let value = if options.owned_body {
+                quote_spanned!(ty.span() => &#name)
+            } else {
+                quote!(#name)
+            };
+            if options.pretty_body && options.format == Format::Json {
+                (
+                    Some("application/json"),
+                    quote!(::serde_json::to_string_pretty(#value).unwrap()),
+                )
+            } else {
+                (
+                    Some(options.format.content_type()),
+                    options.format.encode(&value),
+                )
+            }
+        } else {
+            return None;
+        };
+
+        let content_type = match &options.content_type {
+            Some(content_type) => Some(content_type.value()),
+            None => content_type.map(str::to_string),
+        };
+        let mut headers = content_type
+            .map(|content_type| ("Content-Type", content_type))
+            .into_iter()
+            .collect::<Vec<_>>();
+        match options.compress {
+            Some(compression) => {
+                let encoding = compression.encoding();
+                let compress = format_ident!("{encoding}");
+                headers.push(("Content-Encoding", encoding.to_string()));
+                Some((headers, quote!(::wrpc::compress::#compress(#body))))
+            }
+            None => Some((headers, body)),
+        }
+    }
+
+    /// Body of the `request_helper` function, describing the request instead
+    /// of sending it. Headers are listed in the order they're set on the real
+    /// request.
+    fn describe_body(&self, options: &RpcAttribute) -> TokenStream {
+        let pair = |name: TokenStream, value: TokenStream| {
+            quote! {
+                __wrpc_headers.push((
+                    ::std::string::ToString::to_string(#name),
+                    ::std::string::ToString::to_string(#value),
+                ));
+            }
+        };
+
+        let mut headers = Vec::new();
+        if let Some(name) = &self.header_map {
+            let push = pair(quote!(name), quote!(value));
+            headers.push(quote! {
+                for (name, value) in #name {
+                    #push
+                }
+            });
+        }
+        if options.cookies {
+            let push = pair(quote!("Cookie"), quote!(cookie));
+            headers.push(quote! {
+                if let ::std::option::Option::Some(cookie) = cookie {
+                    #push
+                }
+            });
+        }
+        if let Some(accept) = self.accept(options) {
+            headers.push(pair(quote!("Accept"), quote!(#accept)));
+        }
+        for header in &options.default_headers {
+            let (name, value) = (&header.name, &header.value);
+            headers.push(pair(quote!(#name), quote!(#value)));
+        }
+        for (name, ty) in &self.headers {
+            let values = typed_header_values(name);
+            let push = pair(
+                quote!(<#ty as ::headers::Header>::name().as_str()),
+                quote!(::std::string::String::from_utf8_lossy(value.as_bytes())),
+            );
+            headers.push(quote! {
+                for value in #values {
+                    #push
+                }
+            });
+        }
+        if options.locale {
+            headers.push(pair(quote!("Accept-Language"), quote!(locale)));
+        }
+        if options.etag {
+            let push = pair(quote!("If-None-Match"), quote!(etag));
+            headers.push(quote! {
+                if let ::std::option::Option::Some(etag) = etag {
+                    #push
+                }
+            });
+        }
+        if options.extra_headers {
+            headers.push(quote! {
+                __wrpc_headers.extend(::wrpc::RequestDescription::header_pairs(extra_headers));
+            });
+        }
+
+        let body = match self.body_parts(options) {
+            Some((body_headers, body)) => {
+                let body_headers = body_headers
+                    .iter()
+                    .map(|(name, value)| pair(quote!(#name), quote!(#value)));
+                let body = quote!(::std::convert::Into::<::std::vec::Vec<u8>>::into(#body));
+                match &self.json {
+                    Some((name, _)) if self.optional_json && !options.graphql => {
+                        headers.push(quote! {
+                            if #name.is_some() {
+                                #(#body_headers)*
+                            }
+                        });
+                        quote!(#name.map(|#name| #body))
+                    }
+                    _ => {
+                        headers.extend(body_headers);
+                        quote!(::std::option::Option::Some(#body))
+                    }
+                }
+            }
+            None => quote!(::std::option::Option::None),
+        };
+
+        let method = options.method.to_string().to_uppercase();
+        let url = self.path_string(options);
+        let headers = if headers.is_empty() {
+            quote!(let __wrpc_headers = ::std::vec::Vec::new();)
+        } else {
+            quote! {
+                let mut __wrpc_headers = ::std::vec::Vec::new();
+                #(#headers)*
+            }
+        };
+        quote! {
+            #headers
+            ::wrpc::RequestDescription {
+                method: ::std::string::ToString::to_string(#method),
+                url: #url,
+                headers: __wrpc_headers,
+                body: #body,
+            }
+        }
+    }
+}
+
+impl Format {
+    /// The `Content-Type` of request bodies in this format.
+    fn content_type(self) -> &'static str {
+        match self {
+            Format::Json => "application/json",
+            Format::Cbor => "application/cbor",
+            Format::MsgPack => "application/msgpack",
+        }
+    }
+
+    /// Expression serializing `value` into a request body in this format.
+    fn encode(self, value: &TokenStream) -> TokenStream {
+        match self {
+            Format::Json => quote!(::serde_json::to_string(#value).unwrap()),
+            Format::Cbor => quote! {
+                {
+                    let mut bytes = ::std::vec::Vec::new();
+                    ::wrpc::ciborium::ser::into_writer(#value, &mut bytes).unwrap();
+                    bytes
+                }
+            },
+            Format::MsgPack => quote!(::wrpc::rmp_serde::to_vec(#value).unwrap()),
+        }
+    }
+
+    /// Expression decoding the response `bytes` in this format, or `None` if
+    /// the HTTP client can decode it by itself. JSON is decoded by hand with
+    /// `debug_bodies`, to keep the body around for the error.
+    fn decode(self) -> Option<TokenStream> {
+        match self {
+            Format::Json if cfg!(feature = "debug_bodies") => {
+                Some(quote!(::wrpc::response::json(&bytes)))
+            }
+            Format::Json => None,
+            Format::Cbor => Some(quote! {
+                ::wrpc::ciborium::de::from_reader(&bytes[..]).map_err(::wrpc::Error::decode)
+            }),
+            Format::MsgPack => Some(quote! {
+                ::wrpc::rmp_serde::from_slice(&bytes).map_err(::wrpc::Error::decode)
+            }),
+        }
+    }
+}
+
+/// A value read from the response and returned alongside the body.
+#[derive(Clone, Copy)]
+enum Companion {
+    Status,
+    Headers,
+    FinalUrl,
+}
+
+impl Companion {
+    fn all(options: &RpcAttribute) -> Vec<Companion> {
+        let mut companions = Vec::new();
+        if options.returns_with_status {
+            companions.push(Companion::Status);
+        }
+        if options.returns_headers {
+            companions.push(Companion::Headers);
+        }
+        if options.returns_final_url {
+            companions.push(Companion::FinalUrl);
+        }
+        companions
+    }
+
+    fn name(self) -> Ident {
+        match self {
+            Companion::Status => format_ident!("status"),
+            Companion::Headers => format_ident!("headers"),
+            Companion::FinalUrl => format_ident!("url"),
+        }
+    }
+
+    fn ty(self) -> TokenStream {
+        match self {
+            Companion::Status => quote!(::wrpc::StatusCode),
+            Companion::Headers => quote!(::wrpc::ClientHeaders),
+            Companion::FinalUrl => quote!(::std::string::String),
+        }
+    }
+
+    fn value(self) -> TokenStream {
+        match self {
+            Companion::Status => quote!(::wrpc::response::status(&response)),
+            Companion::Headers => quote!(::wrpc::response::headers(&response)),
+            Companion::FinalUrl => quote!(::wrpc::response::url(&response)),
+        }
+    }
+}
+
+/// The argument `name` spanned at its type, so a missing `Serialize` impl is
+/// reported at the type in the handler's signature instead of the binding.
+fn spanned_at(name: &Ident, ty: &Type) -> Ident {
+    let mut name = name.clone();
+    name.set_span(ty.span());
+    name
+}
+
+/// Expression encoding the `TypedHeader` argument `name` into its
+/// `HeaderValue`s.
+fn typed_header_values(name: &Ident) -> TokenStream {
+    quote! {
+        {
+            let mut values = ::std::vec::Vec::<::headers::HeaderValue>::new();
+            ::headers::Header::encode(#name, &mut values);
+            values
+        }
+    }
+}
+
+/// Sends the caller supplied `etag` as `If-None-Match`, if there is one.
+fn with_etag(request: TokenStream, options: &RpcAttribute) -> TokenStream {
+    if !options.etag {
+        return request;
+    }
+    quote! {
+        etag.into_iter().fold(#request, |request, etag| request.header("If-None-Match", etag))
+    }
+}
+
+/// Merges the caller supplied `extra_headers` into `request`.
+fn with_extra_headers(request: TokenStream, options: &RpcAttribute, client: Client) -> TokenStream {
+    if !options.extra_headers {
+        return request;
+    }
+    match client {
+        Client::Wasm => quote! {
+            extra_headers
+                .entries()
+                .fold(#request, |request, (name, value)| request.header(&name, &value))
+        },
+        Client::Reqwest | Client::Blocking => quote!(#request.headers(extra_headers.clone())),
+    }
+}
+
+/// Adds the trace context headers of the current span for `propagate_trace`,
+/// using the builder's `set` method to add a header.
+fn with_trace(request: TokenStream, options: &RpcAttribute, set: TokenStream) -> TokenStream {
+    if !options.propagate_trace {
+        return request;
+    }
+    quote! {
+        ::wrpc::trace::headers()
+            .into_iter()
+            .fold(#request, |request, (name, value)| request.#set(&name, &value))
+    }
+}
+
+/// Expression creating the `reqwest` client. `cookies` share one with a
+/// cookie store instead.
+fn reqwest_client(options: &RpcAttribute, client: Client) -> TokenStream {
+    match (client, options.cookies) {
+        (Client::Blocking, true) => quote!(::wrpc::cookies::blocking_client()),
+        (Client::Blocking, false) => quote!(::reqwest::blocking::Client::new()),
+        (Client::Wasm | Client::Reqwest, true) => quote!(::wrpc::cookies::client()),
+        (Client::Wasm | Client::Reqwest, false) => quote!(::reqwest::Client::new()),
+    }
+}
+
+/// Reads the whole response body and hands it to `decode` as `bytes`.
+fn decode_bytes(
+    return_type: &ArgumentType,
+    bytes: &TokenStream,
+    client: Client,
+    decode: TokenStream,
+) -> TokenStream {
+    let awaited = client.awaited();
+    quote_spanned! {return_type.span() =>
+        .#bytes()
+        #awaited
+        .map_err(::wrpc::Error::Request)
+        .and_then(|bytes| #decode)
+    }
+}
+
+// The cfg gates below must stay complementary: for every combination of
+// `target_arch = "wasm32"` and `client`, exactly one client function and at
+// most one handler may exist.
+//
+// | wasm32 | client | handler | client function |
+// |--------|--------|---------|-----------------|
+// | no     | no     | yes     | reqwest         |
+// | no     | yes    | yes     | reqwest         |
+// | yes    | no     | yes     | reqwasm         |
+// | yes    | yes    | no      | reqwasm         |
+//
+// With the `mock` feature, both client functions are additionally gated on
+// `not(test)` and a stub takes their place in test builds. With
+// `client_only`, the handler is left out in every row.
+
+/// Gate for the server side handler. It's only removed for client side WASM
+/// builds.
+pub fn handler_cfg() -> TokenStream {
+    quote!(#[cfg(any(not(target_arch = "wasm32"), not(client)))])
+}
+
+/// Gate for the `reqwasm` based client function.
+pub fn wasm_cfg() -> TokenStream {
+    quote!(#[cfg(target_arch = "wasm32")])
+}
+
+/// Gate for the `reqwest` based client function.
+pub fn native_cfg() -> TokenStream {
+    quote!(#[cfg(not(target_arch = "wasm32"))])
+}
+
+/// Allows the lints a generated function with `args` arguments trips in the
+/// user's crate. Client options add arguments the user never wrote, so
+/// Clippy's limit of seven is easily exceeded.
+fn allowed_lints(args: usize) -> TokenStream {
+    if args > 7 {
+        quote!(#[allow(clippy::too_many_arguments)])
+    } else {
+        quote!()
+    }
+}
+
+/// Starts a `reqwasm` request of the handler's method. Methods without a
+/// constructor of their own, like `trace`, are set on a plain request.
+fn wasm_method(options: &RpcAttribute, url: TokenStream) -> TokenStream {
+    const CONSTRUCTORS: &[&str] = &["get", "post", "put", "delete", "patch"];
+    let method = &options.method;
+    if CONSTRUCTORS
+        .iter()
+        .any(|&constructor| method == constructor)
+    {
+        quote!(::reqwasm::http::Request::#method(#url))
+    } else {
+        let method = format_ident!("{}", method.to_string().to_uppercase());
+        quote!(::reqwasm::http::Request::new(#url).method(::reqwasm::http::Method::#method))
+    }
+}
+
+/// Starts a request of the handler's method from `__wrpc_client`, which may
+/// be async or blocking. Methods without a shorthand on the client, like
+/// `trace`, go through `request`.
+fn reqwest_method(options: &RpcAttribute, url: TokenStream) -> TokenStream {
+    const SHORTHANDS: &[&str] = &["get", "post", "put", "delete", "patch"];
+    let method = &options.method;
+    if SHORTHANDS.iter().any(|&shorthand| method == shorthand) {
+        quote!(__wrpc_client.#method(#url))
+    } else {
+        let method = format_ident!("{}", method.to_string().to_uppercase());
+        quote!(__wrpc_client.request(::reqwest::Method::#method, #url))
+    }
+}
+
+/// The reference used for borrowed client arguments. With `named_lifetimes`
+/// all of them share the `'wrpc` lifetime declared on the client function.
+fn reference(options: &RpcAttribute) -> TokenStream {
+    if options.named_lifetimes {
+        quote!(&'wrpc)
+    } else {
+        quote!(&)
+    }
+}
+
+/// Whether the client returns a stream of the response body instead of a single
+/// decoded value.
+fn is_stream(return_type: &ArgumentType, options: &RpcAttribute) -> bool {
+    matches!(
+        options.return_override.as_ref().unwrap_or(return_type),
+        ArgumentType::Stream | ArgumentType::NdJson(_)
+    )
+}
+
+/// Why there's no WASM client function, if there isn't one. It's replaced by a
+/// `compile_error!` for WASM builds.
+fn wasm_unsupported(return_type: &ArgumentType, options: &RpcAttribute) -> Option<&'static str> {
+    match return_type {
+        ArgumentType::NdJson(_) => Some("`returns(ndjson(T))` isn't supported on WASM yet"),
+        ArgumentType::Stream => Some("`returns(stream)` isn't supported on WASM yet"),
+        // Browsers refuse to send these, `fetch` rejects them outright
+        _ if options.method == "trace" => Some("Browsers can't send `trace` requests"),
+        _ if options.method == "connect" => Some("Browsers can't send `connect` requests"),
+        _ => None,
+    }
+}
+
+/// Whether the client function can fail with more than a request error and
+/// therefore returns `wrpc::Error`.
+fn returns_wrpc_error(return_type: &ArgumentType, options: &RpcAttribute) -> bool {
+    matches!(
+        return_type,
+        ArgumentType::Bincode(_) | ArgumentType::Text(_)
+    ) || matches!(return_type, ArgumentType::Json(_)) && options.format.decode().is_some()
+        || options.expect_version.is_some()
+        || options.api_error.is_some()
+}
+
+/// Converts the request error into the configured framework error, if any.
+/// The framework error needs to implement `From` for the underlying client's
+/// error type.
+fn wrap_result(options: &RpcAttribute, call: TokenStream) -> TokenStream {
+    if options.framework_error.is_some() {
+        quote!(::std::result::Result::Ok(#call?))
+    } else {
+        call
+    }
+}
+
+/// `get_user` as `GetUser`, for the name of the `builder` struct.
+fn pascal_case(name: &str) -> String {
+    name.split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// The field of a header type in the struct of `returns(headers(...))`, its
+/// name in snake case, i.e. `last_modified` for `LastModified`. Acronyms stay
+/// in one piece, so `ETag` becomes `etag`.
+fn header_field(ty: &Type) -> Ident {
+    let name = match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident.to_string())
+            .unwrap_or_default(),
+        _ => String::new(),
+    };
+    let mut field = String::new();
+    let mut previous_lowercase = false;
+    for char in name.chars() {
+        if char.is_uppercase() && previous_lowercase {
+            field.push('_');
+        }
+        previous_lowercase = char.is_lowercase() || char.is_ascii_digit();
+        field.extend(char.to_lowercase());
+    }
+    format_ident!("{field}", span = ty.span())
+}
+
+/// Whether `ty` is `String`, which client functions borrow as `&str`. It may
+/// be spelled out, i.e. `std::string::String`.
+fn is_string(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) if path.qself.is_none() => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "String" && segment.arguments.is_none()),
+        _ => false,
+    }
+}
+
+/// Whether `ty` is a `HashMap` or `BTreeMap`, i.e. of a `Path` capturing all
+/// segments by name.
+fn is_map(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "HashMap" || segment.ident == "BTreeMap"),
+        _ => false,
+    }
+}
+
+fn is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident == "Option")
+            .unwrap_or(false),
+        _ => false,
     }
 }
@@ -0,0 +1,286 @@
+//! The TypeScript `fetch` wrapper registered for `ts-export`.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{GenericArgument, Ident, PathArguments, Type};
+
+use super::native_cfg;
+use crate::{
+    argument::ArgumentType,
+    attr::{Credentials, Format, QueryArray, QueryEncoding, RpcAttribute},
+    RpcSignature,
+};
+
+impl RpcSignature {
+    /// Registers the TypeScript wrapper of the request with `wrpc::ts`. Its
+    /// arguments are the client function's, in the same order. Handlers
+    /// whose request can't be reproduced with `fetch` are skipped, i.e.
+    /// generic ones, whose parameters aren't in scope of the registration, or
+    /// binary `format`s and compressed bodies.
+    pub fn ts_function(&self, options: &RpcAttribute) -> TokenStream {
+        if !self.ts_supported(options) {
+            return quote!();
+        }
+        let mut named = Vec::new();
+        let (args, url_args) = self.client_args(options, &quote!());
+        let params = args.iter().enumerate().map(|(index, (name, ty))| {
+            let (source, ty) = self.ts_source(options, name, index < url_args, ty, &mut named);
+            let name = name.to_string();
+            quote!(::wrpc::ts::Param { name: #name, ty: #ty, source: ::wrpc::ts::Source::#source })
+        });
+        let params = params.collect::<Vec<_>>();
+
+        let return_type = options
+            .return_override
+            .as_ref()
+            .unwrap_or(&self.return_type);
+        let (response, returns) = match return_type {
+            ArgumentType::Json(ty) => (quote!(Json), ts_type(ty, &mut named)),
+            ArgumentType::Text(ty) => (quote!(Text), ts_type(ty, &mut named)),
+            ArgumentType::Bytes => (quote!(Bytes), "Uint8Array".to_string()),
+            ArgumentType::Stream | ArgumentType::NdJson(_) => {
+                (quote!(Stream), "ReadableStream<Uint8Array>".to_string())
+            }
+            ArgumentType::ResponseHeaders(_) => {
+                (quote!(Headers), "Record<string, string>".to_string())
+            }
+            ArgumentType::Body => (quote!(Text), "string".to_string()),
+            _ => (quote!(Empty), "void".to_string()),
+        };
+        // `null` stands for a 304, like `None` does for the client function
+        let returns = if options.etag {
+            format!("{returns} | null")
+        } else {
+            returns
+        };
+
+        let mut headers = self
+            .accept(options)
+            .map(|accept| ("Accept".to_string(), accept))
+            .into_iter()
+            .collect::<Vec<_>>();
+        if let Some((body_headers, _)) = self.body_parts(options) {
+            headers.extend(
+                body_headers
+                    .into_iter()
+                    .map(|(name, value)| (name.to_string(), value)),
+            );
+        }
+        headers.extend(
+            options
+                .default_headers
+                .iter()
+                .map(|header| (header.name.clone(), header.value.clone())),
+        );
+        let headers = headers.iter().map(|(name, value)| quote!((#name, #value)));
+
+        let credentials = match options
+            .credentials
+            .or(options.cookies.then_some(Credentials::Include))
+        {
+            Some(Credentials::Include) => quote!(::std::option::Option::Some("include")),
+            Some(Credentials::SameOrigin) => quote!(::std::option::Option::Some("same-origin")),
+            Some(Credentials::Omit) => quote!(::std::option::Option::Some("omit")),
+            None => quote!(::std::option::Option::None),
+        };
+        let not_modified = options.etag;
+
+        let types = named.iter().map(|(name, ty)| {
+            quote! {
+                ::wrpc::ts::Type {
+                    name: #name,
+                    declarations: ::wrpc::ts::declarations::<#ty>,
+                }
+            }
+        });
+
+        let name = self.name.to_string();
+        let method = options.method.to_string();
+        let path = &options.path;
+        let native_cfg = native_cfg();
+        quote! {
+            #native_cfg
+            ::wrpc::inventory::submit! {
+                ::wrpc::ts::Function {
+                    name: #name,
+                    method: #method,
+                    path: #path,
+                    params: &[#(#params),*],
+                    headers: &[#(#headers),*],
+                    credentials: #credentials,
+                    returns: #returns,
+                    response: ::wrpc::ts::Response::#response,
+                    not_modified: #not_modified,
+                    types: &[#(#types),*],
+                }
+            }
+        }
+    }
+
+    /// Whether the request can be reproduced by a `fetch` wrapper. Anything
+    /// computed by Rust code at runtime, like `prefix` or `query_static`
+    /// values, or encoded by it, like binary formats, can't.
+    fn ts_supported(&self, options: &RpcAttribute) -> bool {
+        self.generics.params.is_empty()
+            && options.format == Format::Json
+            && !matches!(
+                options
+                    .return_override
+                    .as_ref()
+                    .unwrap_or(&self.return_type),
+                ArgumentType::Bincode(_)
+            )
+            && options.prefix.is_none()
+            && options.query_static.is_empty()
+            && options.path_defaults.is_empty()
+            && self.path_formats.is_empty()
+            && options.query_key.is_none()
+            && options.query_array != Some(QueryArray::Brackets)
+            && options.query_encoding == QueryEncoding::UrlEncoded
+            && options.compress.is_none()
+    }
+
+    /// Where the client argument `name` goes in the request and its
+    /// TypeScript type. `url` is set for the arguments making up the URL.
+    fn ts_source(
+        &self,
+        options: &RpcAttribute,
+        name: &Ident,
+        url: bool,
+        ty: &TokenStream,
+        named: &mut Vec<(String, Type)>,
+    ) -> (TokenStream, String) {
+        let header = |header: &str| quote!(Header(|| #header));
+        let ts = |named: &mut Vec<(String, Type)>| match syn::parse2::<Type>(ty.clone()) {
+            Ok(ty) => ts_type(&ty, named),
+            Err(_) => "unknown".to_string(),
+        };
+
+        if url {
+            if options.host && name == "host" {
+                return (quote!(Host), ts(named));
+            }
+            if self.path_map().is_some_and(|(map, _)| map == name) {
+                return (quote!(PathMap), ts(named));
+            }
+            if self
+                .path
+                .iter()
+                .flatten()
+                .any(|(segment, _)| segment == name)
+            {
+                return (quote!(Path), ts(named));
+            }
+            if self.query.as_ref().is_some_and(|(query, _)| query == name) {
+                return (quote!(Query), ts(named));
+            }
+            if self.raw_query.as_ref() == Some(name) {
+                return (quote!(RawQuery), ts(named));
+            }
+            return (quote!(QueryPairs), ts(named));
+        }
+        if options.graphql && name == "query" {
+            return (quote!(GraphqlQuery), ts(named));
+        }
+        if options.graphql && name == "variables" {
+            return (quote!(GraphqlVariables), "unknown".to_string());
+        }
+        if self.body.as_ref() == Some(name) {
+            return (quote!(Text), ts(named));
+        }
+        if self.json.as_ref().is_some_and(|(json, _)| json == name) {
+            return (quote!(Json), ts(named));
+        }
+        if self.multipart.as_ref() == Some(name) {
+            return (quote!(Multipart), "[string, Uint8Array][]".to_string());
+        }
+        // TypeScript can't encode typed headers, they're passed encoded
+        if let Some((_, ty)) = self.headers.iter().find(|(header, _)| header == name) {
+            let header = quote!(Header(|| <#ty as ::headers::Header>::name().as_str()));
+            return (header, "string".to_string());
+        }
+        if self.header_map.as_ref() == Some(name) {
+            return (quote!(Headers), ts(named));
+        }
+        if options.extra_headers && name == "extra_headers" {
+            return (quote!(Headers), "HeadersInit".to_string());
+        }
+        if options.locale && name == "locale" {
+            return (header("Accept-Language"), ts(named));
+        }
+        if options.cookies && name == "cookie" {
+            return (header("Cookie"), ts(named));
+        }
+        (header("If-None-Match"), ts(named))
+    }
+}
+
+/// The TypeScript spelling of `ty`. Named types are added to `named`, to be
+/// declared alongside the function. Anything else is `unknown`.
+fn ts_type(ty: &Type, named: &mut Vec<(String, Type)>) -> String {
+    let path = match ty {
+        Type::Reference(reference) => return ts_type(&reference.elem, named),
+        Type::Paren(paren) => return ts_type(&paren.elem, named),
+        Type::Slice(slice) => return array(&slice.elem, named),
+        Type::Array(array_ty) => return array(&array_ty.elem, named),
+        Type::Tuple(tuple) if tuple.elems.is_empty() => return "null".to_string(),
+        Type::Tuple(tuple) => {
+            let elems = tuple
+                .elems
+                .iter()
+                .map(|elem| ts_type(elem, named))
+                .collect::<Vec<_>>();
+            return format!("[{}]", elems.join(", "));
+        }
+        Type::Path(path) if path.qself.is_none() => &path.path,
+        _ => return "unknown".to_string(),
+    };
+    let Some(segment) = path.segments.last() else {
+        return "unknown".to_string();
+    };
+    let args = match &segment.arguments {
+        PathArguments::AngleBracketed(args) => args
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let name = segment.ident.to_string();
+    match (name.as_str(), args.as_slice()) {
+        (
+            "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "i8" | "i16" | "i32" | "i64" | "i128"
+            | "isize" | "f32" | "f64",
+            [],
+        ) => "number".to_string(),
+        ("String" | "str" | "char", []) => "string".to_string(),
+        ("bool", []) => "boolean".to_string(),
+        ("Option", [inner]) => format!("{} | null", ts_type(inner, named)),
+        ("Box" | "Rc" | "Arc" | "Cow", [inner]) => ts_type(inner, named),
+        ("Vec" | "VecDeque" | "HashSet" | "BTreeSet", [inner]) => array(inner, named),
+        ("HashMap" | "BTreeMap", [_, value]) => {
+            format!("Record<string, {}>", ts_type(value, named))
+        }
+        (_, []) => {
+            if !named.iter().any(|(existing, _)| *existing == name) {
+                named.push((name.clone(), ty.clone()));
+            }
+            name
+        }
+        _ => "unknown".to_string(),
+    }
+}
+
+/// An array of `elem`, parenthesized if it's a union.
+fn array(elem: &Type, named: &mut Vec<(String, Type)>) -> String {
+    let elem = ts_type(elem, named);
+    if elem.contains(" | ") {
+        format!("({elem})[]")
+    } else {
+        format!("{elem}[]")
+    }
+}
@@ -0,0 +1,32 @@
+//! The connect function of `ws(path)` handlers.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_quote, Visibility};
+
+use super::reference;
+use crate::{attr::RpcAttribute, RpcSignature};
+
+impl RpcSignature {
+    /// `connect_<handler>`, opening a WebSocket to the handler's URL. It takes
+    /// the path and query arguments, which `validate` makes sure are the only
+    /// ones.
+    pub fn ws_connect(&self, options: &RpcAttribute, vis: &Visibility) -> TokenStream {
+        let name = format_ident!("connect_{}", self.name);
+        let (args, _) = self.client_args(options, &reference(options));
+        let args = args.iter().map(|(name, ty)| quote!(#name: #ty));
+        let mut generics = self.generics.clone();
+        if options.named_lifetimes {
+            generics.params.insert(0, parse_quote!('wrpc));
+        }
+        let (generics, _, where_clause) = generics.split_for_impl();
+        let path = self.request_path(options);
+
+        quote! {
+            #[must_use]
+            #vis async fn #name #generics(#(#args),*) -> ::std::result::Result<::wrpc::ws::WebSocket, ::wrpc::ws::Error> #where_clause {
+                ::wrpc::ws::connect(#path).await
+            }
+        }
+    }
+}
@@ -1,7 +1,11 @@
+use std::path::Path;
+
 use argument::{Argument, ArgumentType};
+use attr::{Extractors, Format, NativeClient, RpcAttribute};
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::{spanned::Spanned, Ident, ItemFn, ReturnType, Signature, Type};
+use proc_macro2::Span;
+use quote::{quote, ToTokens};
+use syn::{Generics, Ident, Item, ItemFn, LitStr, ReturnType, Signature, Type};
 
 extern crate proc_macro;
 
@@ -48,24 +52,416 @@ mod test;
 /// # Configuration
 ///
 /// * `get(path)` - Specifiy this handler's path relative to the root of your
-/// API. Extracted path segments are prefixed with `:`, i.e. `:id`.
+///   API. Extracted path segments are prefixed with `:`, i.e. `:id`. The client
+///   function takes `String` segments as `&str`, so callers needn't allocate.
+///   Segments may declare their type inline, i.e. `:id<u32>`, which fails to
+///   compile unless the handler's `Path` extracts that type for `id`. The type is
+///   stripped from the path the client requests.
+///   Malformed paths fail to compile: relative ones without `prefix(...)`, empty
+///   segments like in `/api//user` and `:`/`*` markers without a name.
+///   A handler extracting `Path<HashMap<String, String>>` gets a client function
+///   taking a reference to the map, which looks up every segment by name and
+///   panics naming the first missing segment.
+///   `post`, `put`, `delete` and `patch` work the same way, as do `trace` and
+///   `connect`, which the clients send through their generic request
+///   constructor. Browsers refuse to send the latter two, so their WASM
+///   clients fail to compile with a `compile_error!`. `connect` handlers aren't
+///   registered for `inventory` or `openapi`, since axum can't route them by
+///   path.
+/// * `ws(path)` - Mark this handler as a WebSocket endpoint, taking a
+///   `WebSocketUpgrade`. Instead of a client function, `connect_<handler>(...)`
+///   is generated, taking the path and query arguments and returning an open
+///   `wrpc::ws::WebSocket`: a `gloo-net` socket on WASM and a `tokio-tungstenite`
+///   stream natively. The URL's scheme becomes `ws://` or `wss://`. Relative paths
+///   only work on WASM, where they're resolved against the page's origin.
+///   Requires the `ws` feature.
 /// * `returns(Type)` - Specify an overriding return type for your client side
-/// function. This must be either `String` or a deserializable type. It's mostly
-/// useful for handlers that return status codes or have an otherwise more
-/// complex return type.
+///   function. This must be either `String` or a deserializable type. It's mostly
+///   useful for handlers that return status codes or have an otherwise more
+///   complex return type. `returns(json(Type))` spells out that `Type` is decoded
+///   as JSON, for handlers whose `Json` is imported under a different name.
+///   `returns(text(Type))` reads the body as text instead and parses it with
+///   `FromStr`, for handlers returning a `String` the client wants as a number
+///   or another parseable type. Parse errors are `wrpc::Error::Decode`.
+///   `returns(status_json(Type))` does the same for handlers returning a
+///   `(StatusCode, Json<Type>)` tuple, making the intent clear at a glance.
+///   Generic types like `returns(Vec<User>)` or `returns(Option<User>)` work too.
+///   The override always wins over the handler's return type, but overriding a
+///   `String` or `&str` return with a JSON type is an error.
+///   Tuple responses like `(StatusCode, Json<Type>)` or `(StatusCode, String)`
+///   don't need an override, their single `Json`, `String` or `Bytes` element is
+///   the body. Tuples with several of them need one.
+/// * `path_default(name = value, ...)` - Make the named path segments optional on
+///   the client side. When `None` is passed, `value` is substituted in the URL.
+/// * `trailing_slash(keep|strip|add)` - Normalize a trailing `/` on the path to
+///   match your router. Defaults to `keep`. The root path `/` is left untouched.
+/// * `leptos` / `leptos(ErrorType)` - Make the client function return
+///   `Result<T, leptos::ServerFnError>` (or `Result<T, ErrorType>`) so it can be
+///   used wherever a server function is expected. The error type must implement
+///   `From` for the underlying client's error, and `Debug` with the `tracing`
+///   feature.
+/// * `named_lifetimes` - Give all borrowed client arguments an explicit, shared
+///   `'wrpc` lifetime instead of relying on elision.
+/// * `query(owned)` - Take the `Query` argument by value instead of by reference.
+/// * `body(owned)` - Take the `Json` body argument by value instead of by
+///   reference, i.e. to pass a struct built inline. `Vec<T>` bodies are taken as
+///   they are rather than as slices.
+/// * `returns_bincode(Type)` - Decode the response body with `bincode` instead of
+///   JSON. The client function returns `Result<Type, wrpc::Error>`, since decoding
+///   errors aren't covered by the HTTP client's error type.
+/// * `format(json|cbor|msgpack)` - Select the serialization format for structured
+///   bodies and responses. Defaults to `json`. `cbor` and `msgpack` require the
+///   feature of the same name, send `application/cbor` or `application/msgpack`
+///   bodies and make the client function return `Result<T, wrpc::Error>`.
+///   `Cbor<T>` and `MsgPack<T>` extractors are recognized like `Json<T>`.
+///   Structured responses are requested with a matching `Accept` header, so
+///   servers negotiating the content type answer in a format the client can
+///   parse.
+/// * `expect_version("2")` - Fail the call with `wrpc::Error::Version` unless the
+///   response carries a matching `X-Api-Version` header. Guards against clients
+///   talking to a server built from a different version of the API.
+/// * `pretty_body` or `json(pretty)` - Serialize JSON request bodies with
+///   `serde_json::to_string_pretty` instead of compactly, i.e. for readable request
+///   logs during development. Other formats are unaffected.
+/// * `path_helper` - Also generate `<handler>_path(...)`, taking the path and
+///   query arguments and returning the relative path the client would request.
+///   Useful for links in WASM UIs.
+/// * `builder` - Also generate a `<Handler>Request` builder, i.e. `GetUserRequest`
+///   for `get_user`, and an async `send()` calling the client function. `new(...)`
+///   takes the required path and query arguments, the optional ones, i.e. an
+///   `Option<Query<T>>` or segments with a `path_default`, have a setter each.
+///   Only handlers whose client function takes nothing but path and `Query`
+///   arguments are supported.
+/// * `request_helper` - Also generate `call_<handler>_request(...)`, taking the
+///   same arguments as the client function and returning a
+///   `wrpc::RequestDescription` of the method, URL, headers and serialized body
+///   it would send, without sending anything. Meant for logging and replaying
+///   requests. Multipart bodies aren't supported.
+/// * `raw` - Also generate `call_<handler>_raw(...)`, taking the same arguments as
+///   the client function but returning a `wrpc::Response` as soon as it's
+///   received, without reading the body. It exposes `.status()`, `.header()`,
+///   `.json()`, `.text()` and `.bytes()` the same way on both targets. Not
+///   supported by `native_client(ureq)`.
+/// * `allow_unknown_extractors` - Skip arguments that aren't a known extractor
+///   instead of failing to compile. Useful for custom extractors that only make
+///   sense on the server.
+/// * `extractors(json = Name, query = Name, path = Name)` - Recognize `Json`,
+///   `Query` and `Path` under the names they're imported as, i.e.
+///   `extractors(json = AxJson)` after `use axum::Json as AxJson`. Extractors
+///   are otherwise detected by their own name, so aliases would be unknown.
+/// * `returns_headers` - Return the response headers alongside the body, as
+///   `(wrpc::ClientHeaders, T)`. Handlers returning `(HeaderMap, T)` have their
+///   body type detected from `T`.
+/// * `returns(headers(Type, ...))` - Only decode the listed response headers,
+///   ignoring the body. The client function returns a generated
+///   `<Name>Headers` struct with an `Option` field per header, named after
+///   the snake cased type, i.e. `etag` and `last_modified` for
+///   `returns(headers(ETag, LastModified))`. The types implement
+///   `headers::Header`, so the crate needs the `headers` crate as a dependency.
+///   Can't be combined with `returns_with_status`, `returns_headers` or
+///   `returns_final_url`.
+/// * `fire_and_forget` - Return `()` as soon as the response status and headers
+///   arrive, whatever the handler returns. The body is never read, which suits
+///   ping and telemetry endpoints.
+/// * `retry(count, backoff_ms)` - Resend the request up to `count` times on
+///   connection errors and 5xx responses, doubling the wait after each attempt.
+///   Requires the `retry` feature. Only `get`, `put` and `delete` handlers are
+///   retried unless `retry(count, backoff_ms, always)` is used.
+/// * `wasm_bindgen` - Additionally export the WASM client to JS as
+///   `<handler>_js`, converting arguments and the result with
+///   `serde_wasm_bindgen` instead of JSON strings. Requires `wasm-bindgen`,
+///   `wasm-bindgen-futures` and `serde-wasm-bindgen` dependencies. Header
+///   arguments and `returns_headers` aren't supported.
+/// * `#[path_fmt("{:x}")]` - An attribute on a single segment `Path` argument
+///   rather than an `rpc` option. The client interpolates the segment with the
+///   given format string, i.e. as hex.
+/// * `compress(gzip|deflate)` - Compress `String` and structured request bodies
+///   and set the matching `Content-Encoding` header. Requires the `compress`
+///   feature. The server has to decompress them itself, i.e. with
+///   `tower-http`'s `RequestDecompressionLayer`. Multipart bodies aren't supported.
+/// * `raw_bytes_return` - Return the raw response body as `wrpc::ClientBytes`
+///   (`bytes::Bytes` natively, `Vec<u8>` on WASM) instead of decoding it, whatever
+///   the handler returns. Saves allocating a `String` for callers that parse the
+///   body themselves.
+/// * `query_static("key" => value, ...)` - Append fixed query parameters after
+///   the `Query` argument, if any. Values may be any expression implementing
+///   `Display`, including client arguments, and are percent-encoded.
+/// * `cookies` - Send cookies along with the request. The client function takes
+///   an extra `cookie: Option<&str>` argument that's sent as the `Cookie` header
+///   natively. Without one, the cookies set by earlier responses are sent, since
+///   these handlers share a client with a cookie store. Requires the `cookies`
+///   feature. On WASM the argument is ignored and the browser's cookies
+///   are sent with `credentials: include` instead.
+/// * `etag` - Make conditional requests. The client function takes an extra
+///   `etag: Option<&str>` argument that's sent as the `If-None-Match` header and
+///   returns `Option<T>`, which is `None` for a `304 Not Modified` response, i.e.
+///   when the caller's cached copy is still current.
+/// * `header_map` - Add an `extra_headers: &wrpc::ClientHeaders` argument whose
+///   headers are merged into the request, i.e. for headers shared by many calls.
+///   Not to be confused with handlers taking a `HeaderMap`, whose client functions
+///   take a `&[(&str, &str)]` list instead.
+/// * `locale` - Add a `locale: &str` argument that's sent as the
+///   `Accept-Language` header, i.e. `"de-CH, de;q=0.9"`, for handlers that
+///   localize their response.
+/// * `host` - Add a leading `host: &str` argument and request
+///   `https://{host}{path}`, for APIs served under several hosts, i.e. one per
+///   tenant. Handlers can read the host with `axum::extract::Host`, which isn't a
+///   client argument. Can't be combined with `prefix(...)`, `ws` or absolute
+///   URLs.
+/// * `extra_query` - Add an `extra_query: &[(&str, &str)]` argument whose pairs are
+///   percent-encoded and appended to the query, i.e. for parameters the handler
+///   doesn't extract, like tracking or feature flags. They follow the `Query`
+///   argument's pairs, if there are any.
+/// * `credentials(include|same_origin|omit)` - Set the WASM request's
+///   `credentials` mode, i.e. `include` to send cookies to a cross-origin API.
+///   Unset by default, leaving the browser's default. Ignored natively. Takes
+///   precedence over the `include` implied by `cookies`.
+/// * `returns_final_url` - Return the URL the response came from after following
+///   redirects alongside the body, as `(String, T)`. Combined with
+///   `returns_headers` the client function returns
+///   `(wrpc::ClientHeaders, String, T)`.
+/// * `returns_with_status` - Return the response's status code alongside the
+///   body, as `(wrpc::StatusCode, T)`, i.e. to tell `201 Created` from `200 OK`.
+///   It comes first when combined with `returns_headers` or `returns_final_url`.
+/// * `error(ErrType)` - Decode the JSON body of non-2xx responses as `ErrType` and
+///   fail with `wrpc::Error::Api` carrying it. The client function returns
+///   `Result<T, wrpc::Error<ErrType>>`. Without this option, `wrpc::Error` never
+///   holds an API error. With the `tracing` feature, `ErrType` must implement
+///   `Debug` as well, failed calls are logged with their error.
+/// * `graphql` - Generate a GraphQL-over-HTTP client. It takes a `query: &str`
+///   and `variables: &impl Serialize` instead of the handler's body, sends them
+///   as `{"query": ..., "variables": ...}` and decodes the `data` field of the
+///   response into the return type, usually set with `returns(T)`. Requires the
+///   `graphql` feature.
+/// * `query_encoding(qs|urlencoded)` - Serialize the `Query` argument with
+///   `serde_qs` or `serde_urlencoded`. Defaults to `urlencoded`, which is what
+///   axum's `Query` extractor parses, and the crate must be a dependency of the
+///   calling crate. Neither works for nested structs with axum's `Query`:
+///   `serde_urlencoded` panics serializing them, while `serde_qs` encodes them as
+///   `outer[inner]=value`, which only parses with a `serde_qs` based extractor
+///   on the server.
+/// * `query_array(repeat|brackets)` - Serialize sequence fields of the `Query`
+///   argument, e.g. `ids: Vec<u32>`, as repeated keys instead, i.e. `ids=1&ids=2`
+///   for `repeat` and `ids[]=1&ids[]=2` for `brackets`. Takes precedence over
+///   `query_encoding(...)`. axum's own `Query` can't deserialize either, but
+///   axum-extra's `Query` parses `repeat` and a `serde_qs` based one `brackets`.
+/// * `query_key("filter")` - Nest the serialized `Query` argument under a key, i.e.
+///   `filter[page]=1` instead of `page=1`, for handlers extracting the query as a
+///   field of a larger struct. Needs a `serde_qs` style extractor on the server.
+/// * `prefix(CONST)` - Prepend a shared prefix, i.e. a `&str` const like
+///   `API_PREFIX = "/api/v1"`, to the path of the client function. They're
+///   joined with exactly one `/`. The path itself must not be an absolute URL,
+///   and `inventory` routes and the OpenAPI description keep the unprefixed path,
+///   so nest those routes under the prefix on the server.
+/// * `default_headers("Name=value", ...)` - Send fixed headers, like
+///   `"X-Client=wrpc"`, with every request. They're set before any header
+///   arguments.
+/// * `returns(stream)` - Return the response body as a stream of chunks instead
+///   of buffering it, as
+///   `impl wrpc::Stream<Item = wrpc::Result<bytes::Bytes>> + Send`. Requires the
+///   `stream` feature and is only supported natively for now, the
+///   WASM build fails with a compile error. Stream clients aren't replaced by
+///   `mock`.
+/// * `returns(ndjson(T))` - Decode a newline delimited JSON body line by line, as
+///   `impl wrpc::Stream<Item = Result<T, wrpc::Error>> + Send`. Sends
+///   `Accept: application/x-ndjson`. A line that fails to decode is yielded as
+///   `wrpc::Error::Decode` without ending the stream. Same restrictions as
+///   `returns(stream)`.
+/// * `content_type("...")` - Send the body with this `Content-Type`, e.g.
+///   `text/csv` for a `String` body. Overrides the type implied by `format(...)`
+///   for structured bodies. Only allowed with `post`, `put`, `patch` and `delete`, and not
+///   with multipart bodies.
+/// * `merge_patch` - Send the body of a `patch` handler as a JSON Merge Patch
+///   (RFC 7396), with `Content-Type: application/merge-patch+json`. Only allowed
+///   with `patch`, and not together with `content_type("...")`.
+/// * `accept("...")` - Send this `Accept` header instead of the one implied by
+///   `format(...)`, or for responses that don't imply one, like text. An `Accept`
+///   in `default_headers(...)` takes precedence over both.
+/// * `vis(pub)` - Give the client functions and the items generated alongside
+///   them this visibility instead of the handler's, i.e. a `pub(crate)` handler
+///   with a `pub` client function for use from other crates.
+/// * `path_struct(Params { name: Type, ... })` - Declare the fields of a
+///   `Path<Params>` struct argument, whose definition the macro can't see. The
+///   client function takes one argument per field, which is interpolated into
+///   the path segment of the same name. Every field must be a segment and every
+///   segment a field.
+/// * `native_client(reqwest|ureq)` - Select the HTTP client of the native client
+///   function. Defaults to `reqwest`. With `ureq`, the native client function
+///   is synchronous and returns `Result<T, wrpc::Error>`, while the WASM one is
+///   unchanged. Requires the `ureq` feature and doesn't support `retry`,
+///   `error(...)`, `expect_version`, `returns_with_status`, `returns_headers`,
+///   `returns_final_url`, `returns(stream)`, `returns(ndjson(T))`,
+///   `returns(headers(...))`, `cookies`, `etag`, `header_map`, `builder`,
+///   `raw`, `leptos` or multipart bodies.
+///   Unlike `reqwest`, `ureq` fails with `wrpc::Error::Ureq` on non-2xx
+///   responses.
+/// * `propagate_trace` - Inject the trace context of the current `tracing` span
+///   into the request headers of the native client functions, e.g. `traceparent`
+///   with the W3C Trace Context propagator. Uses whatever propagator is
+///   registered with `opentelemetry::global`. Requires the `trace` feature and is
+///   ignored on WASM.
+/// * `client_only` - Omit the handler entirely and only generate the client
+///   functions, whatever the target. For handlers shared with client crates that
+///   shouldn't depend on `axum` or the handler's other server side dependencies.
+///   Without a handler, nothing is registered by `inventory`, `openapi` or
+///   `ts-export`.
+///
+/// # Features
+///
+/// * `probe` - Additionally generates a `<handler>_probe(base, ...)` function in
+///   test builds that sends a single request to a server running at `base` and
+///   returns the round trip latency. The response is ignored.
+/// * `inventory` - Registers every handler's method, path and route with
+///   `inventory` on non-WASM targets. `wrpc::routes::router()` assembles all
+///   registered routes into an `axum::Router`. Only stateless routers are
+///   supported for now.
+/// * `cbor`, `msgpack` - Pull in `ciborium` and `rmp-serde` respectively, as
+///   needed by `format(cbor)` and `format(msgpack)`.
+/// * `multipart` - Required for handlers taking `Multipart`. Enables
+///   `reqwest`'s `multipart` feature natively and uses the browser's `FormData`
+///   on WASM.
+/// * `retry` - Required by `retry(...)`. Waits with `tokio::time` natively and
+///   `gloo-timers` on WASM.
+/// * `compress` - Required by `compress(...)`. Compresses bodies with `flate2`.
+/// * `openapi` - Registers a description of every handler's method, path and
+///   parameters on non-WASM targets. `wrpc::openapi::collect()` assembles them
+///   into an OpenAPI `paths` object. Types are only named, under `x-rust-type`.
+/// * `ts-export` - Registers how every handler's request is made on non-WASM
+///   targets. `wrpc::ts::emit()` assembles them into a `.ts` module of `fetch`
+///   wrappers taking the client functions' arguments, i.e. from a build script.
+///   Typed headers are passed as their encoded value. Named types are declared
+///   as `unknown`, or from their JSON schema with the `schemars` feature, which
+///   requires them to implement `schemars::JsonSchema`. Handlers whose request
+///   depends on Rust code are skipped, i.e. generic ones and those using a
+///   binary `format`, `compress`, `prefix`, `query_static`, `path_default`,
+///   `#[path_fmt]`, `query_key`, `query_encoding(qs)` or `query_array(brackets)`.
+/// * `cookies` - Required by `cookies`. Enables `reqwest`'s `cookies` feature.
+/// * `mock` - Replaces every client function with a stub in test builds, which
+///   returns the response registered with `wrpc::mock::set_response` or
+///   `wrpc::mock::set_error` for its handler instead of sending a request. The
+///   error has to be the client function's error type, so mocked errors are
+///   easiest with functions returning `wrpc::Error`.
+/// * `spawn` - Additionally generates `spawn_call_<handler>(..., on_result)` on
+///   WASM, which runs the client function with `wasm_bindgen_futures::spawn_local`
+///   and passes its result to `on_result`. Borrowed arguments must be `'static`,
+///   since the call outlives the helper.
+/// * `prefetch` - Additionally generates `prefetch_<handler>(...)` on WASM for `get`
+///   handlers, taking the path and query arguments and returning a
+///   `<link rel="prefetch">` element for the URL the client function would
+///   request. Append it to `<head>` to have the browser fetch the response ahead
+///   of time, i.e. while hydrating a server rendered page.
+/// * `debug_bodies` - Decode JSON responses by hand instead of leaving it to the
+///   HTTP client, so a body that fails to decode ends up in the error. The
+///   source of `wrpc::Error::Decode` is a `wrpc::BodyError` with the first 256
+///   characters of the body. Client functions returning JSON return
+///   `Result<T, wrpc::Error>` instead of `wrpc::Result<T>`.
+/// * `graphql` - Required by `graphql`. Provides the request and response
+///   envelopes in `wrpc::graphql`.
+/// * `stream` - Required by `returns(stream)` and `returns(ndjson(T))`. Enables
+///   `reqwest`'s `stream` feature and re-exports `futures_core::Stream` as
+///   `wrpc::Stream`.
+/// * `blocking` - Additionally generates a synchronous `call_<name>_blocking`
+///   client function using `reqwest::blocking`, for native callers outside an
+///   async runtime. It's skipped for handlers using `retry`, `error(...)`,
+///   `expect_version`, `returns_with_status`, `returns_headers`,
+///   `returns_final_url`, `returns(headers(...))`, `etag`, multipart bodies or
+///   `returns(stream)` and `returns(ndjson(T))`, and isn't replaced by `mock`.
+/// * `client_only` - Makes the `client_only` option the default for every
+///   handler. Cargo unifies features, so this also drops the handlers of a server
+///   built alongside the client, i.e. in the same workspace. The option itself
+///   doesn't have that problem.
+/// * `ureq` - Required by `native_client(ureq)`. Pulls in `ureq` with its `json`
+///   feature on non-WASM targets.
+/// * `trace` - Required by `propagate_trace`. Pulls in `opentelemetry`, `tracing`
+///   and `tracing-opentelemetry` on non-WASM targets.
+/// * `tracing` - Runs every async client function in an `info` span named
+///   `call_<handler>`, with the method, the path and the response status as
+///   fields, and logs whether the call succeeded and how long it took. Failed
+///   calls are logged with their error, so the error types of `error(...)` and
+///   `leptos(...)` must implement `Debug`. The `ureq` and blocking clients
+///   aren't instrumented.
+/// * `ws` - Required by `ws(path)`. Pulls in `gloo-net` on WASM and
+///   `tokio-tungstenite` on other targets.
+///
+/// # Targets
+///
+/// The handler and the two client functions are gated so that exactly one
+/// client function and at most one handler exist for every build. The handler
+/// is only removed when building for WASM with `--cfg client`, or everywhere
+/// with `client_only`.
+///
+/// | `wasm32` | `--cfg client` | handler | client function |
+/// |----------|----------------|---------|-----------------|
+/// | no       | no             | yes     | `reqwest`       |
+/// | no       | yes            | yes     | `reqwest`       |
+/// | yes      | no             | yes     | `reqwasm`       |
+/// | yes      | yes            | no      | `reqwasm`       |
+///
+/// The `reqwasm` client function is `#[doc(hidden)]` under `--cfg docsrs`. docs.rs
+/// builds for a native target, so it documents the `reqwest` client instead.
+///
+/// Any `#[cfg(...)]` attributes on the handler itself are copied onto the
+/// generated client code as well, so a handler behind `#[cfg(feature = "admin")]`
+/// only gets a client function when that feature is enabled.
+///
+/// Both clients follow redirects, `reqwest` up to 10 of them and the browser's
+/// `fetch` by its own rules, and decode the final response. A handler
+/// redirecting to a JSON resource only needs `returns(T)` for its client to
+/// return the resource.
+///
+/// The native client futures are `Send` whenever their arguments are, so they
+/// can be spawned on multi-threaded runtimes.
+///
+/// # Debugging
+///
+/// * `WRPC_DUMP_DIR` - When set at build time, the generated client code of every
+///   handler is formatted with `prettyplease` and written to
+///   `<dir>/<handler>.rs`. Handlers with the same name overwrite each other.
 ///
 /// # Requirements
 ///
 /// * Path inputs with multiple segments must be destructured. This is because
-/// the macro separates these parameters into separate arguments to the client
-/// side function and needs their names.
+///   the macro separates these parameters into separate arguments to the client
+///   side function and needs their names.
+/// * The `Path` argument must extract exactly as many values as the path has
+///   `:param` and `*wildcard` segments, which are substituted by name.
 /// * Text body inputs must be `String`s
-/// * All request-derived inputs must be `Json`, `Query`, `Path` or `String`.
-/// Any other arguments are assumed to be state derived and skipped.
-/// * The return type must be `Json` or `String`/`&str`. `&str` will be turned
-/// into `String` on the client side.
+/// * `Json<Vec<T>>` inputs are taken as `&[T]` on the client side.
+/// * `Json` and `Query` input types must implement `Serialize` and `Json` return
+///   types `DeserializeOwned`, for the client. Missing impls are reported at the
+///   type in the handler's signature, except in generic handlers.
+/// * `Option<Query<T>>` and `Option<Json<T>>` inputs become `Option<&T>`. The
+///   query string or body is only sent for `Some`, and a query without any pairs
+///   doesn't leave a trailing `?` either.
+/// * `RawQuery` inputs become `Option<&str>`, appended to the URL verbatim after a
+///   `?` for `Some`. A handler can't take both `Query` and `RawQuery`.
+/// * All request-derived inputs must be `Json`, `Query`, `RawQuery`, `Path`, `String`,
+///   `TypedHeader`, `HeaderMap` or `Multipart`. `State` and `Extension` arguments are server
+///   side only and skipped. Any other argument is an error, unless the
+///   `allow_unknown_extractors` flag is set, in which case it's skipped too. A
+///   `HeaderMap` becomes a list of `(name, value)` pairs on the client side, a
+///   `Multipart` a list of `wrpc::multipart::Part`s, i.e. `(name, contents)`.
+///   A `TypedHeader<T>` becomes `&T`, encoded with `Header::encode`.
+///   `reqwasm` and `ureq` only take string values, so calls with a value that
+///   isn't valid UTF-8 fail instead of sending it.
+/// * The return type must be `Json`, `String`/`&str`, `StatusCode` or `()`.
+///   Extractors and return types are detected by the last segment of their path,
+///   so `axum::Json<T>` works. Aliased imports like `use axum::Json as J` need
+///   `extractors(json = J)`.
+///   `&str` will be turned into `String` on the client side. `StatusCode` and
+///   `()` produce a client returning `()` that discards the response body. Use
+///   `returns(())` for other body-less responses. Any other return type, like
+///   `Response` or `impl IntoResponse`, is an error unless `returns(...)` names
+///   the type the client gets.
 /// * The full path to the API handler must be specified. wrpc currently can't
-/// have access to your Router, so paths are unknown to the macro.
+///   have access to your Router, so paths are unknown to the macro.
+/// * Generic parameters and where clauses of the handler are kept on the client
+///   function, so any bounds the body needs (e.g. `Serialize`) must be spelled
+///   out there. Generic handlers can't be used with `wasm_bindgen` or
+///   `inventory`.
+/// * Inputs may appear in any order in the handler. The client function always
+///   takes path segments first, then the query, the body and finally headers, so
+///   its parameter order doesn't depend on the handler's.
 ///
 #[proc_macro_attribute]
 pub fn rpc(attr: TokenStream, item: TokenStream) -> TokenStream {
@@ -79,15 +475,31 @@ fn rpc_impl(
     attr: proc_macro2::TokenStream,
     item: proc_macro2::TokenStream,
 ) -> syn::Result<proc_macro2::TokenStream> {
-    let handler: ItemFn = syn::parse2(item)?;
+    let mut handler: ItemFn = syn::parse2(item)?;
+    let options: RpcAttribute = syn::parse2(attr)?;
+    let mut sig = RpcSignature::new(handler.sig.clone(), &options.extractors)?;
+    argument::strip_path_formats(&mut handler.sig);
     let vis = &handler.vis;
-    let sig: RpcSignature = handler.sig.clone().try_into()?;
-    let options = syn::parse2(attr)?;
+    sig.expand_path_struct(&options)?;
+    sig.validate(&options)?;
 
     let client_fn = sig.to_tokens(&options, vis);
+    let client_fn = with_handler_cfgs(&handler, client_fn)?;
+    if let Some(dir) = std::env::var_os("WRPC_DUMP_DIR") {
+        dump(Path::new(&dir), &sig.name, &client_fn)?;
+    }
+    // Pure client crates drop the handler, so they don't need its dependencies
+    let handler = if options.client_only {
+        quote!()
+    } else {
+        let handler_cfg = codegen::handler_cfg();
+        quote! {
+            #handler_cfg
+            #handler
+        }
+    };
 
     let tokens_new = quote! {
-        #[cfg(any(not(target_arch = "wasm32"), not(client)))]
         #handler
 
         #client_fn
@@ -96,59 +508,488 @@ fn rpc_impl(
     Ok(tokens_new)
 }
 
+/// Puts the handler's own `#[cfg(...)]` attributes on every generated item, so
+/// the client only exists where the handler does.
+fn with_handler_cfgs(
+    handler: &ItemFn,
+    tokens: proc_macro2::TokenStream,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let cfgs = handler
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("cfg"))
+        .collect::<Vec<_>>();
+    if cfgs.is_empty() {
+        return Ok(tokens);
+    }
+    let mut file: syn::File = syn::parse2(tokens)?;
+    for item in &mut file.items {
+        let attrs = match item {
+            Item::Const(item) => &mut item.attrs,
+            Item::Fn(item) => &mut item.attrs,
+            Item::Impl(item) => &mut item.attrs,
+            Item::Macro(item) => &mut item.attrs,
+            Item::Struct(item) => &mut item.attrs,
+            _ => continue,
+        };
+        attrs.splice(0..0, cfgs.iter().map(|&cfg| cfg.clone()));
+    }
+    Ok(file.to_token_stream())
+}
+
+/// Writes the generated client code to `<dir>/<handler>.rs` for inspection.
+/// Falls back to the unformatted tokens if they don't parse as a file.
+fn dump(dir: &Path, name: &Ident, tokens: &proc_macro2::TokenStream) -> syn::Result<()> {
+    let code = match syn::parse2(tokens.clone()) {
+        Ok(file) => prettyplease::unparse(&file),
+        Err(_) => tokens.to_string(),
+    };
+    let path = dir.join(format!("{name}.rs"));
+    std::fs::create_dir_all(dir)
+        .and_then(|_| std::fs::write(&path, code))
+        .map_err(|err| {
+            syn::Error::new(
+                name.span(),
+                format!("Failed to write `{}`: {err}", path.display()),
+            )
+        })
+}
+
 #[derive(Debug)]
 struct RpcSignature {
     pub name: Ident,
+    pub generics: Generics,
     pub path: Option<Vec<(Ident, Type)>>,
+    pub path_formats: Vec<(Ident, LitStr)>,
     pub query: Option<(Ident, Type)>,
+    pub optional_query: bool,
+    pub raw_query: Option<Ident>,
     pub body: Option<Ident>,
     pub json: Option<(Ident, Type)>,
+    pub optional_json: bool,
+    pub headers: Vec<(Ident, Type)>,
+    pub header_map: Option<Ident>,
+    pub multipart: Option<Ident>,
+    pub unknown: Vec<Span>,
+    pub return_error: Option<syn::Error>,
     pub return_type: ArgumentType,
 }
 
-impl TryFrom<Signature> for RpcSignature {
-    type Error = syn::Error;
+impl RpcSignature {
+    /// Replaces a `Path<Params>` argument with the fields declared by
+    /// `path_struct(Params { .. })`, so each segment becomes its own client
+    /// argument.
+    fn expand_path_struct(&mut self, options: &RpcAttribute) -> syn::Result<()> {
+        let Some(path_struct) = &options.path_struct else {
+            return Ok(());
+        };
+        let matches = match self.path.as_deref() {
+            Some([(_, Type::Path(ty))]) => ty
+                .path
+                .segments
+                .last()
+                .is_some_and(|segment| segment.ident == path_struct.name),
+            _ => false,
+        };
+        if !matches {
+            return Err(syn::Error::new(
+                path_struct.name.span(),
+                "`path_struct` doesn't match the handler's `Path` type",
+            ));
+        }
 
-    fn try_from(value: Signature) -> Result<Self, Self::Error> {
+        let segments = options.path_params().collect::<Vec<_>>();
+        for (name, _) in &path_struct.fields {
+            if !segments.iter().any(|segment| name == segment) {
+                return Err(syn::Error::new(
+                    name.span(),
+                    "Field isn't a segment of the path",
+                ));
+            }
+        }
+        for segment in segments {
+            if !path_struct.fields.iter().any(|(name, _)| name == segment) {
+                return Err(syn::Error::new(
+                    path_struct.name.span(),
+                    format!("Path segment `:{segment}` has no matching field"),
+                ));
+            }
+        }
+
+        self.path = Some(path_struct.fields.clone());
+        Ok(())
+    }
+
+    /// Checks that the attribute options refer to arguments that actually
+    /// exist on the handler and that every argument is understood.
+    fn validate(&self, options: &RpcAttribute) -> syn::Result<()> {
+        let params = options.path_params().count();
+        let args = self.path.as_ref().map_or(0, Vec::len);
+        if params != args && self.path_map().is_none() {
+            return Err(syn::Error::new(
+                options.path_span,
+                format!(
+                    "The path has {params} parameter(s), but the handler's `Path` extracts {args}"
+                ),
+            ));
+        }
+        for (segment, declared) in &options.path_types {
+            let extracted = self.path.iter().flatten().find(|(name, _)| name == segment);
+            if let Some((_, ty)) = extracted {
+                let (declared, ty) = (quote!(#declared).to_string(), quote!(#ty).to_string());
+                if declared != ty {
+                    return Err(syn::Error::new(
+                        options.path_span,
+                        format!(
+                            "Path segment `:{segment}` is declared as `{declared}`, but the handler extracts `{ty}`"
+                        ),
+                    ));
+                }
+            }
+        }
+        if let (Some(_), Some(name)) = (&self.query, &self.raw_query) {
+            return Err(syn::Error::new(
+                name.span(),
+                "Handlers can't take both `Query` and `RawQuery`",
+            ));
+        }
+        if let (Some(key), None) = (&options.query_key, &self.query) {
+            return Err(syn::Error::new(
+                key.span(),
+                "`query_key` needs a `Query` argument to nest",
+            ));
+        }
+        for default in &options.path_defaults {
+            let exists = self
+                .path
+                .iter()
+                .flatten()
+                .any(|(name, _)| name == &default.name);
+            if !exists {
+                return Err(syn::Error::new(
+                    default.name.span(),
+                    "Default refers to an unknown path segment",
+                ));
+            }
+        }
+        if let (Some(name), Some(_)) = (&self.multipart, &options.retry) {
+            return Err(syn::Error::new(
+                name.span(),
+                "Multipart bodies can't be resent by `retry`",
+            ));
+        }
+        if let (Some(name), true) = (&self.multipart, options.request_helper) {
+            return Err(syn::Error::new(
+                name.span(),
+                "Multipart bodies can't be described by `request_helper`",
+            ));
+        }
+        if let (Some(name), Some(_)) = (&self.multipart, &options.compress) {
+            return Err(syn::Error::new(
+                name.span(),
+                "Multipart bodies can't be compressed",
+            ));
+        }
+        if options.wasm_bindgen {
+            if let Some((name, _)) = self.headers.first() {
+                return Err(syn::Error::new(
+                    name.span(),
+                    "`wasm_bindgen` doesn't support header arguments",
+                ));
+            }
+            if let Some(name) = &self.header_map {
+                return Err(syn::Error::new(
+                    name.span(),
+                    "`wasm_bindgen` doesn't support header arguments",
+                ));
+            }
+            if options.returns_headers {
+                return Err(syn::Error::new(
+                    self.name.span(),
+                    "`wasm_bindgen` can't be combined with `returns_headers`",
+                ));
+            }
+            if options.extra_headers {
+                return Err(syn::Error::new(
+                    self.name.span(),
+                    "`wasm_bindgen` can't be combined with `header_map`",
+                ));
+            }
+            if options.extra_query {
+                return Err(syn::Error::new(
+                    self.name.span(),
+                    "`wasm_bindgen` can't be combined with `extra_query`",
+                ));
+            }
+            if options.graphql {
+                return Err(syn::Error::new(
+                    self.name.span(),
+                    "`wasm_bindgen` can't be combined with `graphql`",
+                ));
+            }
+            if let Some(ArgumentType::ResponseHeaders(_)) = options.return_override {
+                return Err(syn::Error::new(
+                    self.name.span(),
+                    "`wasm_bindgen` can't be combined with `returns(headers(...))`",
+                ));
+            }
+            if !self.generics.params.is_empty() {
+                return Err(syn::Error::new_spanned(
+                    &self.generics,
+                    "`wasm_bindgen` doesn't support generic handlers",
+                ));
+            }
+        }
+        if let (Some(content_type), Some(_)) = (&options.content_type, &self.multipart) {
+            return Err(syn::Error::new(
+                content_type.span(),
+                "Multipart bodies set their own `Content-Type`",
+            ));
+        }
+        let response_headers = matches!(
+            options.return_override,
+            Some(ArgumentType::ResponseHeaders(_))
+        );
+        if response_headers
+            && (options.returns_with_status || options.returns_headers || options.returns_final_url)
+        {
+            return Err(syn::Error::new(
+                self.name.span(),
+                "`returns(headers(...))` can't be combined with `returns_with_status`, `returns_headers` or `returns_final_url`",
+            ));
+        }
+        if options.native_client == NativeClient::Ureq {
+            let return_type = options
+                .return_override
+                .as_ref()
+                .unwrap_or(&self.return_type);
+            let stream = matches!(return_type, ArgumentType::Stream);
+            let ndjson = matches!(return_type, ArgumentType::NdJson(_));
+            let unsupported = [
+                ("retry", options.retry.is_some()),
+                ("error(...)", options.api_error.is_some()),
+                ("expect_version", options.expect_version.is_some()),
+                ("returns_with_status", options.returns_with_status),
+                ("returns_headers", options.returns_headers),
+                ("returns_final_url", options.returns_final_url),
+                ("returns(stream)", stream),
+                ("returns(ndjson(T))", ndjson),
+                ("returns(headers(...))", response_headers),
+                ("cookies", options.cookies),
+                ("etag", options.etag),
+                ("header_map", options.extra_headers),
+                ("builder", options.builder),
+                ("raw", options.raw),
+                ("leptos", options.framework_error.is_some()),
+                ("multipart bodies", self.multipart.is_some()),
+            ];
+            if let Some((option, _)) = unsupported.iter().find(|(_, used)| *used) {
+                return Err(syn::Error::new(
+                    options.method.span(),
+                    format!("`native_client(ureq)` doesn't support {option}"),
+                ));
+            }
+        }
+        if options.builder {
+            if !self.generics.params.is_empty() {
+                return Err(syn::Error::new(
+                    self.name.span(),
+                    "`builder` doesn't support generic handlers",
+                ));
+            }
+            let (args, path_args) = self.client_args(options, &quote!(&));
+            if args.len() > path_args
+                || self.raw_query.is_some()
+                || options.extra_query
+                || options.host
+            {
+                return Err(syn::Error::new(
+                    self.name.span(),
+                    "`builder` only supports path and `Query` arguments",
+                ));
+            }
+        }
+        if options.host {
+            let combined = if options.prefix.is_some() {
+                Some("`prefix`")
+            } else if options.path.contains("://") {
+                Some("an absolute URL")
+            } else if options.ws {
+                Some("`ws`")
+            } else {
+                None
+            };
+            if let Some(combined) = combined {
+                return Err(syn::Error::new(
+                    self.name.span(),
+                    format!("`host` can't be combined with {combined}"),
+                ));
+            }
+        }
+        if let Some(prefix) = &options.prefix {
+            if options.path.contains("://") {
+                return Err(syn::Error::new_spanned(
+                    prefix,
+                    "`prefix` can't be combined with an absolute URL",
+                ));
+            }
+        }
+        if let (ArgumentType::Body, Some(ArgumentType::Json(ty))) =
+            (&self.return_type, &options.return_override)
+        {
+            return Err(syn::Error::new_spanned(
+                ty,
+                "The handler returns text, but `returns(...)` decodes JSON. Return `Json<T>` from the handler or use `returns(text(T))` instead",
+            ));
+        }
+        if options.graphql {
+            if options.format != Format::Json {
+                return Err(syn::Error::new(
+                    self.name.span(),
+                    "`graphql` only supports JSON",
+                ));
+            }
+            let return_type = options.return_override.as_ref();
+            if !matches!(
+                return_type.unwrap_or(&self.return_type),
+                ArgumentType::Json(_)
+            ) {
+                return Err(syn::Error::new(
+                    self.name.span(),
+                    "`graphql` needs the type of the `data` field, e.g. `returns(T)`",
+                ));
+            }
+        }
+        if cfg!(feature = "inventory")
+            && (self.generics.type_params().next().is_some()
+                || self.generics.const_params().next().is_some())
+        {
+            return Err(syn::Error::new_spanned(
+                &self.generics,
+                "Generic handlers can't be registered with `inventory`",
+            ));
+        }
+        if options.ws {
+            let (args, path_args) = self.client_args(options, &quote!(&));
+            if args.len() > path_args {
+                return Err(syn::Error::new(
+                    self.name.span(),
+                    "`ws` handlers only support path and `Query` arguments",
+                ));
+            }
+        }
+        // The response of a `ws` handler is the upgrade, never a body
+        if let (Some(err), None, false) = (&self.return_error, &options.return_override, options.ws)
+        {
+            return Err(err.clone());
+        }
+        if let Some(span) = self.unknown.first() {
+            if !options.allow_unknown_extractors {
+                return Err(syn::Error::new(
+                    *span,
+                    "Unknown extractor. Use `State` or `Extension` for server side state, or `allow_unknown_extractors` to skip it",
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl RpcSignature {
+    /// Collects the client arguments and the return type from the handler's
+    /// signature.
+    fn new(value: Signature, extractors: &Extractors) -> syn::Result<Self> {
         let args: Vec<Argument> = value
             .inputs
             .into_iter()
-            .map(|arg| arg.try_into())
+            .map(|arg| Argument::new(arg, extractors))
             .collect::<Result<_, _>>()?;
-        let ret = {
-            let span = value.output.span();
-            match value.output {
-                ReturnType::Type(_, ty) => Ok(ty),
-                ReturnType::Default => {
-                    Err(syn::Error::new(span, "Rpc functions must have a return"))
+        // Return types the client can't be derived from are only an error
+        // without `returns(...)`, which `validate` knows about
+        let mut return_error = None;
+        let return_type = match value.output {
+            ReturnType::Type(_, ty) => match ArgumentType::new_return(ty.clone(), extractors) {
+                Ok(ArgumentType::Ignored | ArgumentType::Unknown(_)) => {
+                    return_error = Some(syn::Error::new_spanned(
+                        ty,
+                        "The client's return type can't be derived from this type. Specify it with `returns(Type)`",
+                    ));
+                    ArgumentType::Ignored
                 }
-            }
-        }?;
+                Err(err) => {
+                    return_error = Some(err);
+                    ArgumentType::Ignored
+                }
+                Ok(return_type) => return_type,
+            },
+            ReturnType::Default => ArgumentType::Unit,
+        };
 
         let mut signature = RpcSignature {
             name: value.ident,
+            generics: value.generics,
             path: None,
+            path_formats: Vec::new(),
             query: None,
+            optional_query: false,
+            raw_query: None,
             body: None,
             json: None,
-            return_type: ret.try_into()?,
+            optional_json: false,
+            headers: Vec::new(),
+            header_map: None,
+            multipart: None,
+            unknown: Vec::new(),
+            return_error,
+            return_type,
         };
 
         for arg in args {
             match arg {
-                Argument::Json { name, inner_type } => {
+                Argument::Json {
+                    name,
+                    inner_type,
+                    optional,
+                } => {
                     signature.json = Some((name, inner_type));
+                    signature.optional_json = optional;
                 }
-                Argument::Query { name, inner_type } => {
+                Argument::Query {
+                    name,
+                    inner_type,
+                    optional,
+                } => {
                     signature.query = Some((name, inner_type));
+                    signature.optional_query = optional;
+                }
+                Argument::RawQuery { name } => {
+                    signature.raw_query = Some(name);
                 }
-                Argument::Path { inner_types } => {
+                Argument::Path {
+                    inner_types,
+                    format,
+                } => {
+                    if let Some(format) = format {
+                        signature
+                            .path_formats
+                            .push((inner_types[0].0.clone(), format));
+                    }
                     signature.path = Some(inner_types);
                 }
                 Argument::Body { name } => {
                     signature.body = Some(name);
                 }
+                Argument::TypedHeader { name, inner_type } => {
+                    signature.headers.push((name, inner_type));
+                }
+                Argument::HeaderMap { name } => {
+                    signature.header_map = Some(name);
+                }
+                Argument::Multipart { name } => {
+                    signature.multipart = Some(name);
+                }
                 Argument::Ignored => {}
+                Argument::Unknown { span } => signature.unknown.push(span),
             }
         }
 
@@ -1,17 +1,16 @@
 pub mod axum {
     use quote::quote;
 
-    use crate::rpc_impl;
+    use crate::{attr::RpcAttribute, rpc_impl};
 
     #[test]
     pub fn simple_handler_works() {
-        let attr_tokens = quote!(get("/api/simple_handler_works")).into();
+        let attr_tokens = quote!(get("/api/simple_handler_works"));
         let handler_tokens = quote! {
             pub async fn handler() -> String {
                 "hello world".into()
             }
-        }
-        .into();
+        };
 
         let expected = quote! {
             #[cfg(any(not(target_arch = "wasm32"), not(client)))]
@@ -20,6 +19,8 @@ pub mod axum {
             }
 
             #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
             pub async fn call_handler() -> ::wrpc::Result<String> {
                 ::reqwasm::http::Request::get("/api/simple_handler_works")
                     .send()
@@ -29,9 +30,10 @@ pub mod axum {
             }
 
             #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
             pub async fn call_handler() -> ::wrpc::Result<String> {
-                let client = ::reqwest::Client::new();
-                client.get("/api/simple_handler_works")
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get("/api/simple_handler_works")
                     .send()
                     .await?
                     .text()
@@ -47,13 +49,12 @@ pub mod axum {
 
     #[test]
     pub fn string_conversion_works() {
-        let attr_tokens = quote!(get("/api/string_coercion_works")).into();
+        let attr_tokens = quote!(get("/api/string_coercion_works"));
         let handler_tokens = quote! {
             pub async fn handler() -> &'static str {
                 "hello world"
             }
-        }
-        .into();
+        };
 
         let expected = quote! {
             #[cfg(any(not(target_arch = "wasm32"), not(client)))]
@@ -62,6 +63,8 @@ pub mod axum {
             }
 
             #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
             pub async fn call_handler() -> ::wrpc::Result<String> {
                 ::reqwasm::http::Request::get("/api/string_coercion_works")
                     .send()
@@ -71,9 +74,10 @@ pub mod axum {
             }
 
             #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
             pub async fn call_handler() -> ::wrpc::Result<String> {
-                let client = ::reqwest::Client::new();
-                client.get("/api/string_coercion_works")
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get("/api/string_coercion_works")
                     .send()
                     .await?
                     .text()
@@ -89,13 +93,12 @@ pub mod axum {
 
     #[test]
     pub fn json_response_works() {
-        let attr_tokens = quote!(get("/api/json_response_works")).into();
+        let attr_tokens = quote!(get("/api/json_response_works"));
         let handler_tokens = quote! {
             pub async fn handler() -> Json<MyType> {
                 Json(MyType::new())
             }
-        }
-        .into();
+        };
 
         let expected = quote! {
             #[cfg(any(not(target_arch = "wasm32"), not(client)))]
@@ -104,8 +107,11 @@ pub mod axum {
             }
 
             #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
             pub async fn call_handler() -> ::wrpc::Result<MyType> {
                 ::reqwasm::http::Request::get("/api/json_response_works")
+                    .header("Accept", "application/json")
                     .send()
                     .await?
                     .json()
@@ -113,14 +119,21 @@ pub mod axum {
             }
 
             #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
             pub async fn call_handler() -> ::wrpc::Result<MyType> {
-                let client = ::reqwest::Client::new();
-                client.get("/api/json_response_works")
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get("/api/json_response_works")
+                    .header("Accept", "application/json")
                     .send()
                     .await?
                     .json()
                     .await
             }
+
+            const _: fn() = || {
+                fn assert_deserialize<T: ::serde::de::DeserializeOwned>() {}
+                assert_deserialize::<MyType>();
+            };
         };
 
         assert_eq!(
@@ -131,13 +144,12 @@ pub mod axum {
 
     #[test]
     pub fn type_override_works() {
-        let attr_tokens = quote!(get("/api/type_override_works"), returns(MyType)).into();
+        let attr_tokens = quote!(get("/api/type_override_works"), returns(MyType));
         let handler_tokens = quote! {
             pub async fn handler() -> impl IntoResponse {
                 (StatusCode::CREATED, Json(MyType::new()))
             }
-        }
-        .into();
+        };
 
         let expected = quote! {
             #[cfg(any(not(target_arch = "wasm32"), not(client)))]
@@ -146,8 +158,11 @@ pub mod axum {
             }
 
             #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
             pub async fn call_handler() -> ::wrpc::Result<MyType> {
                 ::reqwasm::http::Request::get("/api/type_override_works")
+                    .header("Accept", "application/json")
                     .send()
                     .await?
                     .json()
@@ -155,14 +170,21 @@ pub mod axum {
             }
 
             #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
             pub async fn call_handler() -> ::wrpc::Result<MyType> {
-                let client = ::reqwest::Client::new();
-                client.get("/api/type_override_works")
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get("/api/type_override_works")
+                    .header("Accept", "application/json")
                     .send()
                     .await?
                     .json()
                     .await
             }
+
+            const _: fn() = || {
+                fn assert_deserialize<T: ::serde::de::DeserializeOwned>() {}
+                assert_deserialize::<MyType>();
+            };
         };
 
         assert_eq!(
@@ -173,13 +195,12 @@ pub mod axum {
 
     #[test]
     pub fn string_input_works() {
-        let attr_tokens = quote!(post("/api/simple_input_works")).into();
+        let attr_tokens = quote!(post("/api/simple_input_works"));
         let handler_tokens = quote! {
             pub async fn handler(payload: String) -> String {
                 "hello world".into()
             }
-        }
-        .into();
+        };
 
         let expected = quote! {
             #[cfg(any(not(target_arch = "wasm32"), not(client)))]
@@ -188,6 +209,8 @@ pub mod axum {
             }
 
             #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
             pub async fn call_handler(payload: &str) -> ::wrpc::Result<String> {
                 ::reqwasm::http::Request::post("/api/simple_input_works")
                     .body(::std::string::ToString::to_string(payload))
@@ -198,9 +221,62 @@ pub mod axum {
             }
 
             #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
             pub async fn call_handler(payload: &str) -> ::wrpc::Result<String> {
-                let client = ::reqwest::Client::new();
-                client.post("/api/simple_input_works")
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.post("/api/simple_input_works")
+                    .body(::std::string::ToString::to_string(payload))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn qualified_string_inputs_are_borrowed() {
+        let attr_tokens = quote!(post("/api/qualified_string_inputs_are_borrowed/:team"));
+        let handler_tokens = quote! {
+            pub async fn handler(
+                Path(team): Path<std::string::String>,
+                payload: ::std::string::String,
+            ) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(
+                Path(team): Path<std::string::String>,
+                payload: ::std::string::String,
+            ) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(team: &str, payload: &str) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::post(&::std::format!("/api/qualified_string_inputs_are_borrowed/{}", team))
+                    .body(::std::string::ToString::to_string(payload))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(team: &str, payload: &str) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.post(&::std::format!("/api/qualified_string_inputs_are_borrowed/{}", team))
                     .body(::std::string::ToString::to_string(payload))
                     .send()
                     .await?
@@ -217,13 +293,12 @@ pub mod axum {
 
     #[test]
     pub fn json_input_works() {
-        let attr_tokens = quote!(post("/api/json_input_works")).into();
+        let attr_tokens = quote!(post("/api/json_input_works"));
         let handler_tokens = quote! {
             pub async fn handler(payload: Json<MyType>) -> String {
                 "hello world".into()
             }
-        }
-        .into();
+        };
 
         let expected = quote! {
             #[cfg(any(not(target_arch = "wasm32"), not(client)))]
@@ -232,8 +307,11 @@ pub mod axum {
             }
 
             #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
             pub async fn call_handler(payload: &MyType) -> ::wrpc::Result<String> {
                 ::reqwasm::http::Request::post("/api/json_input_works")
+                    .header("Content-Type", "application/json")
                     .body(::serde_json::to_string(payload).unwrap())
                     .send()
                     .await?
@@ -242,9 +320,11 @@ pub mod axum {
             }
 
             #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
             pub async fn call_handler(payload: &MyType) -> ::wrpc::Result<String> {
-                let client = ::reqwest::Client::new();
-                client.post("/api/json_input_works")
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.post("/api/json_input_works")
+                    .header("Content-Type", "application/json")
                     .body(::serde_json::to_string(payload).unwrap())
                     .send()
                     .await?
@@ -261,13 +341,12 @@ pub mod axum {
 
     #[test]
     pub fn path_segment_works() {
-        let attr_tokens = quote!(get("/api/path_segment_works/:id")).into();
+        let attr_tokens = quote!(get("/api/path_segment_works/:id"));
         let handler_tokens = quote! {
             pub async fn handler(Path(id): Path<u32>) -> String {
                 "hello world".into()
             }
-        }
-        .into();
+        };
 
         let expected = quote! {
             #[cfg(any(not(target_arch = "wasm32"), not(client)))]
@@ -276,6 +355,8 @@ pub mod axum {
             }
 
             #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
             pub async fn call_handler(id: u32) -> ::wrpc::Result<String> {
                 ::reqwasm::http::Request::get(&::std::format!("/api/path_segment_works/{}", id))
                     .send()
@@ -285,9 +366,88 @@ pub mod axum {
             }
 
             #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
             pub async fn call_handler(id: u32) -> ::wrpc::Result<String> {
-                let client = ::reqwest::Client::new();
-                client.get(&::std::format!("/api/path_segment_works/{}", id))
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get(&::std::format!("/api/path_segment_works/{}", id))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn missing_path_argument_errors() {
+        let attr_tokens = quote!(get("/api/missing_path_argument_errors/:id"));
+        let handler_tokens = quote! {
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens)
+                .unwrap_err()
+                .to_string(),
+            "The path has 1 parameter(s), but the handler's `Path` extracts 0"
+        );
+    }
+
+    #[test]
+    pub fn extra_path_argument_errors() {
+        let attr_tokens = quote!(get("/api/extra_path_argument_errors/:team"));
+        let handler_tokens = quote! {
+            pub async fn handler(Path((team, id)): Path<(String, u32)>) -> String {
+                "hello world".into()
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens)
+                .unwrap_err()
+                .to_string(),
+            "The path has 1 parameter(s), but the handler's `Path` extracts 2"
+        );
+    }
+
+    #[test]
+    pub fn wildcard_path_segment_works() {
+        let attr_tokens = quote!(get("/api/files/*rest"));
+        let handler_tokens = quote! {
+            pub async fn handler(Path(rest): Path<String>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Path(rest): Path<String>) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(rest: &str) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get(&::std::format!("/api/files/{}", rest))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(rest: &str) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get(&::std::format!("/api/files/{}", rest))
                     .send()
                     .await?
                     .text()
@@ -303,13 +463,12 @@ pub mod axum {
 
     #[test]
     pub fn multiple_path_segments_work() {
-        let attr_tokens = quote!(get("/api/multiple_path_segments_work/team/:team/id/:id")).into();
+        let attr_tokens = quote!(get("/api/multiple_path_segments_work/team/:team/id/:id"));
         let handler_tokens = quote! {
             pub async fn handler(Path((team, id)): Path<(String, u32)>) -> String {
                 "hello world".into()
             }
-        }
-        .into();
+        };
 
         let expected = quote! {
             #[cfg(any(not(target_arch = "wasm32"), not(client)))]
@@ -318,7 +477,9 @@ pub mod axum {
             }
 
             #[cfg(target_arch = "wasm32")]
-            pub async fn call_handler(team: String, id: u32) -> ::wrpc::Result<String> {
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(team: &str, id: u32) -> ::wrpc::Result<String> {
                 ::reqwasm::http::Request::get(&::std::format!("/api/multiple_path_segments_work/team/{}/id/{}", team, id))
                     .send()
                     .await?
@@ -327,9 +488,142 @@ pub mod axum {
             }
 
             #[cfg(not(target_arch = "wasm32"))]
-            pub async fn call_handler(team: String, id: u32) -> ::wrpc::Result<String> {
-                let client = ::reqwest::Client::new();
-                client.get(&::std::format!("/api/multiple_path_segments_work/team/{}/id/{}", team, id))
+            #[must_use]
+            pub async fn call_handler(team: &str, id: u32) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get(&::std::format!("/api/multiple_path_segments_work/team/{}/id/{}", team, id))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn map_path_works() {
+        let attr_tokens = quote!(get("/api/map_path_works/team/:team/id/:id"));
+        let handler_tokens = quote! {
+            pub async fn handler(Path(params): Path<HashMap<String, String>>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Path(params): Path<HashMap<String, String> >) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(params: &HashMap<String, String>) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get(&::std::format!("/api/map_path_works/team/{}/id/{}", params.get("team").expect("`params` has no entry for the path segment `:team`"), params.get("id").expect("`params` has no entry for the path segment `:id`")))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(params: &HashMap<String, String>) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get(&::std::format!("/api/map_path_works/team/{}/id/{}", params.get("team").expect("`params` has no entry for the path segment `:team`"), params.get("id").expect("`params` has no entry for the path segment `:id`")))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn many_arguments_are_allowed() {
+        let attr_tokens = quote!(get(
+            "/api/many_arguments_are_allowed/:a/:b/:c/:d/:e/:f/:g/:h"
+        ));
+        let handler_tokens = quote! {
+            pub async fn handler(
+                Path((a, b, c, d, e, f, g, h)): Path<(u32, u32, u32, u32, u32, u32, u32, u32)>,
+            ) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(
+                Path((a, b, c, d, e, f, g, h)): Path<(u32, u32, u32, u32, u32, u32, u32, u32)>,
+            ) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            #[allow(clippy::too_many_arguments)]
+            pub async fn call_handler(a: u32, b: u32, c: u32, d: u32, e: u32, f: u32, g: u32, h: u32) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get(&::std::format!("/api/many_arguments_are_allowed/{}/{}/{}/{}/{}/{}/{}/{}", a, b, c, d, e, f, g, h))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            #[allow(clippy::too_many_arguments)]
+            pub async fn call_handler(a: u32, b: u32, c: u32, d: u32, e: u32, f: u32, g: u32, h: u32) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get(&::std::format!("/api/many_arguments_are_allowed/{}/{}/{}/{}/{}/{}/{}/{}", a, b, c, d, e, f, g, h))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn trace_method_works() {
+        let attr_tokens = quote!(trace("/api/trace_method_works/:id"));
+        let handler_tokens = quote! {
+            pub async fn handler(Path(id): Path<u32>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Path(id): Path<u32>) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            ::std::compile_error!("Browsers can't send `trace` requests");
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(id: u32) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.request(::reqwest::Method::TRACE, &::std::format!("/api/trace_method_works/{}", id))
                     .send()
                     .await?
                     .text()
@@ -345,13 +639,12 @@ pub mod axum {
 
     #[test]
     pub fn query_works() {
-        let attr_tokens = quote!(get("/api/query_works")).into();
+        let attr_tokens = quote!(get("/api/query_works"));
         let handler_tokens = quote! {
             pub async fn handler(query: Query<Pagination>) -> String {
                 "hello world".into()
             }
-        }
-        .into();
+        };
 
         let expected = quote! {
             #[cfg(any(not(target_arch = "wasm32"), not(client)))]
@@ -360,8 +653,10 @@ pub mod axum {
             }
 
             #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
             pub async fn call_handler(query: &Pagination) -> ::wrpc::Result<String> {
-                ::reqwasm::http::Request::get(&::std::format!("/api/query_works?{}", ::serde_qs::to_string(query).unwrap()))
+                ::reqwasm::http::Request::get(&::std::format!("/api/query_works?{}", ::serde_urlencoded::to_string(query).unwrap()))
                     .send()
                     .await?
                     .text()
@@ -369,9 +664,10 @@ pub mod axum {
             }
 
             #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
             pub async fn call_handler(query: &Pagination) -> ::wrpc::Result<String> {
-                let client = ::reqwest::Client::new();
-                client.get(&::std::format!("/api/query_works?{}", ::serde_qs::to_string(query).unwrap()))
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get(&::std::format!("/api/query_works?{}", ::serde_urlencoded::to_string(query).unwrap()))
                     .send()
                     .await?
                     .text()
@@ -387,13 +683,12 @@ pub mod axum {
 
     #[test]
     pub fn query_and_path_segments_work() {
-        let attr_tokens = quote!(get("/api/query_and_path_segments_work/:id")).into();
+        let attr_tokens = quote!(get("/api/query_and_path_segments_work/:id"));
         let handler_tokens = quote! {
             pub async fn handler(id: Path<u32>, query: Query<Pagination>) -> String {
                 "hello world".into()
             }
-        }
-        .into();
+        };
 
         let expected = quote! {
             #[cfg(any(not(target_arch = "wasm32"), not(client)))]
@@ -402,8 +697,10 @@ pub mod axum {
             }
 
             #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
             pub async fn call_handler(id: u32, query: &Pagination) -> ::wrpc::Result<String> {
-                ::reqwasm::http::Request::get(&::std::format!("/api/query_and_path_segments_work/{}?{}", id, ::serde_qs::to_string(query).unwrap()))
+                ::reqwasm::http::Request::get(&::std::format!("/api/query_and_path_segments_work/{}?{}", id, ::serde_urlencoded::to_string(query).unwrap()))
                     .send()
                     .await?
                     .text()
@@ -411,9 +708,10 @@ pub mod axum {
             }
 
             #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
             pub async fn call_handler(id: u32, query: &Pagination) -> ::wrpc::Result<String> {
-                let client = ::reqwest::Client::new();
-                client.get(&::std::format!("/api/query_and_path_segments_work/{}?{}", id, ::serde_qs::to_string(query).unwrap()))
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get(&::std::format!("/api/query_and_path_segments_work/{}?{}", id, ::serde_urlencoded::to_string(query).unwrap()))
                     .send()
                     .await?
                     .text()
@@ -429,13 +727,12 @@ pub mod axum {
 
     #[test]
     pub fn destructuring_works() {
-        let attr_tokens = quote!(post("/api/json_input_works")).into();
+        let attr_tokens = quote!(post("/api/json_input_works"));
         let handler_tokens = quote! {
             pub async fn handler(Json(payload): Json<MyType>) -> String {
                 "hello world".into()
             }
-        }
-        .into();
+        };
 
         let expected = quote! {
             #[cfg(any(not(target_arch = "wasm32"), not(client)))]
@@ -444,8 +741,11 @@ pub mod axum {
             }
 
             #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
             pub async fn call_handler(payload: &MyType) -> ::wrpc::Result<String> {
                 ::reqwasm::http::Request::post("/api/json_input_works")
+                    .header("Content-Type", "application/json")
                     .body(::serde_json::to_string(payload).unwrap())
                     .send()
                     .await?
@@ -454,9 +754,11 @@ pub mod axum {
             }
 
             #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
             pub async fn call_handler(payload: &MyType) -> ::wrpc::Result<String> {
-                let client = ::reqwest::Client::new();
-                client.post("/api/json_input_works")
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.post("/api/json_input_works")
+                    .header("Content-Type", "application/json")
                     .body(::serde_json::to_string(payload).unwrap())
                     .send()
                     .await?
@@ -470,4 +772,5753 @@ pub mod axum {
             expected.to_string()
         );
     }
+
+    #[test]
+    pub fn typed_header_works() {
+        let attr_tokens = quote!(get("/api/typed_header_works"));
+        let handler_tokens = quote! {
+            pub async fn handler(TypedHeader(auth): TypedHeader<Authorization<Bearer>>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(TypedHeader(auth): TypedHeader<Authorization<Bearer> >) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(auth: &Authorization<Bearer>) -> ::wrpc::Result<String> {
+                (match ::wrpc::request::header_value(
+                    <Authorization<Bearer> as ::headers::Header>::name().as_str(),
+                    &{
+                        let mut values = ::std::vec::Vec::<::headers::HeaderValue>::new();
+                        ::headers::Header::encode(auth, &mut values);
+                        values
+                    },
+                ) {
+                    ::std::result::Result::Ok(auth) => ::reqwasm::http::Request::get("/api/typed_header_works")
+                        .header(<Authorization<Bearer> as ::headers::Header>::name().as_str(), &auth)
+                        .send()
+                        .await,
+                    ::std::result::Result::Err(err) => ::std::result::Result::Err(err),
+                })?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(auth: &Authorization<Bearer>) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get("/api/typed_header_works")
+                    .headers(
+                        {
+                            let mut values = ::std::vec::Vec::<::headers::HeaderValue>::new();
+                            ::headers::Header::encode(auth, &mut values);
+                            values
+                        }
+                        .into_iter()
+                        .map(|value| (<Authorization<Bearer> as ::headers::Header>::name().clone(), value))
+                        .collect::<::headers::HeaderMap>(),
+                    )
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn header_map_works() {
+        let attr_tokens = quote!(post("/api/header_map_works"));
+        let handler_tokens = quote! {
+            pub async fn handler(headers: HeaderMap, payload: String) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(headers: HeaderMap, payload: String) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(payload: &str, headers: &[(&str, &str)]) -> ::wrpc::Result<String> {
+                headers.iter().fold(::reqwasm::http::Request::post("/api/header_map_works"), |request, (name, value)| request.header(*name, *value))
+                    .body(::std::string::ToString::to_string(payload))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(payload: &str, headers: &[(&str, &str)]) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                headers.iter().fold(__wrpc_client.post("/api/header_map_works"), |request, (name, value)| request.header(*name, *value))
+                    .body(::std::string::ToString::to_string(payload))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn path_default_works() {
+        let attr_tokens = quote!(get("/api/path_default_works/:id"), path_default(id = 0));
+        let handler_tokens = quote! {
+            pub async fn handler(Path(id): Path<u32>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Path(id): Path<u32>) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(id: ::std::option::Option<u32>) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get(&::std::format!("/api/path_default_works/{}", id.as_ref().map_or_else(
+                    || ::std::string::ToString::to_string(&0),
+                    ::std::string::ToString::to_string,
+                )))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(id: ::std::option::Option<u32>) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get(&::std::format!("/api/path_default_works/{}", id.as_ref().map_or_else(
+                    || ::std::string::ToString::to_string(&0),
+                    ::std::string::ToString::to_string,
+                )))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn string_path_default_works() {
+        let attr_tokens = quote!(
+            get("/api/string_path_default_works/:team"),
+            path_default(team = "core")
+        );
+        let handler_tokens = quote! {
+            pub async fn handler(Path(team): Path<String>) -> String {
+                team
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Path(team): Path<String>) -> String {
+                team
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(team: ::std::option::Option<&str>) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get(&::std::format!("/api/string_path_default_works/{}", team.as_ref().map_or_else(
+                    || ::std::string::ToString::to_string(&"core"),
+                    ::std::string::ToString::to_string,
+                )))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(team: ::std::option::Option<&str>) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get(&::std::format!("/api/string_path_default_works/{}", team.as_ref().map_or_else(
+                    || ::std::string::ToString::to_string(&"core"),
+                    ::std::string::ToString::to_string,
+                )))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn path_default_unknown_segment_fails() {
+        let attr_tokens = quote!(get("/api/path_default_works/:id"), path_default(team = 0));
+        let handler_tokens = quote! {
+            pub async fn handler(Path(id): Path<u32>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let err = rpc_impl(attr_tokens, handler_tokens).unwrap_err();
+        assert_eq!(err.to_string(), "Default refers to an unknown path segment");
+    }
+
+    #[test]
+    pub fn trailing_slash_strip_works() {
+        let attr_tokens = quote!(
+            get("/api/trailing_slash_strip_works/"),
+            trailing_slash(strip)
+        );
+        let handler_tokens = quote! {
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get("/api/trailing_slash_strip_works")
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get("/api/trailing_slash_strip_works")
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn trailing_slash_modes_work() {
+        let path = |attr_tokens| syn::parse2::<RpcAttribute>(attr_tokens).unwrap().path;
+
+        assert_eq!(path(quote!(get("/api/thing/"))), "/api/thing/");
+        assert_eq!(
+            path(quote!(get("/api/thing"), trailing_slash(keep))),
+            "/api/thing"
+        );
+        assert_eq!(
+            path(quote!(get("/api/thing/"), trailing_slash(keep))),
+            "/api/thing/"
+        );
+        assert_eq!(
+            path(quote!(get("/api/thing/"), trailing_slash(strip))),
+            "/api/thing"
+        );
+        assert_eq!(
+            path(quote!(get("/api/thing"), trailing_slash(strip))),
+            "/api/thing"
+        );
+        assert_eq!(
+            path(quote!(get("/api/thing"), trailing_slash(add))),
+            "/api/thing/"
+        );
+        assert_eq!(
+            path(quote!(get("/api/thing/"), trailing_slash(add))),
+            "/api/thing/"
+        );
+
+        assert_eq!(path(quote!(get("/"), trailing_slash(keep))), "/");
+        assert_eq!(path(quote!(get("/"), trailing_slash(strip))), "/");
+        assert_eq!(path(quote!(get("/"), trailing_slash(add))), "/");
+    }
+
+    #[test]
+    pub fn leptos_works() {
+        let attr_tokens = quote!(get("/api/leptos_works"), leptos);
+        let handler_tokens = quote! {
+            pub async fn handler() -> Json<MyType> {
+                Json(MyType::new())
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler() -> Json<MyType> {
+                Json(MyType::new())
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler() -> ::std::result::Result<MyType, ::leptos::ServerFnError> {
+                ::std::result::Result::Ok(::reqwasm::http::Request::get("/api/leptos_works")
+                    .header("Accept", "application/json")
+                    .send()
+                    .await?
+                    .json()
+                    .await?)
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler() -> ::std::result::Result<MyType, ::leptos::ServerFnError> {
+                let __wrpc_client = ::reqwest::Client::new();
+                ::std::result::Result::Ok(__wrpc_client.get("/api/leptos_works")
+                    .header("Accept", "application/json")
+                    .send()
+                    .await?
+                    .json()
+                    .await?)
+            }
+
+            const _: fn() = || {
+                fn assert_deserialize<T: ::serde::de::DeserializeOwned>() {}
+                assert_deserialize::<MyType>();
+            };
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn leptos_custom_error_works() {
+        let attr_tokens = quote!(get("/api/leptos_custom_error_works"), leptos(AppError));
+        let handler_tokens = quote! {
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler() -> ::std::result::Result<String, AppError> {
+                ::std::result::Result::Ok(::reqwasm::http::Request::get("/api/leptos_custom_error_works")
+                    .send()
+                    .await?
+                    .text()
+                    .await?)
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler() -> ::std::result::Result<String, AppError> {
+                let __wrpc_client = ::reqwest::Client::new();
+                ::std::result::Result::Ok(__wrpc_client.get("/api/leptos_custom_error_works")
+                    .send()
+                    .await?
+                    .text()
+                    .await?)
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn query_and_json_body_work() {
+        let attr_tokens = quote!(put("/api/query_and_json_body_work/:id"));
+        let handler_tokens = quote! {
+            pub async fn handler(Path(id): Path<u32>, Query(filter): Query<Filter>, Json(payload): Json<MyType>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Path(id): Path<u32>, Query(filter): Query<Filter>, Json(payload): Json<MyType>) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(id: u32, filter: &Filter, payload: &MyType) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::put(&::std::format!("/api/query_and_json_body_work/{}?{}", id, ::serde_urlencoded::to_string(filter).unwrap()))
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string(payload).unwrap())
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(id: u32, filter: &Filter, payload: &MyType) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.put(&::std::format!("/api/query_and_json_body_work/{}?{}", id, ::serde_urlencoded::to_string(filter).unwrap()))
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string(payload).unwrap())
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn named_lifetimes_work() {
+        let attr_tokens = quote!(post("/api/named_lifetimes_work"), named_lifetimes);
+        let handler_tokens = quote! {
+            pub async fn handler(query: Query<Pagination>, Json(payload): Json<MyType>, headers: HeaderMap) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(query: Query<Pagination>, Json(payload): Json<MyType>, headers: HeaderMap) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler<'wrpc>(query: &'wrpc Pagination, payload: &'wrpc MyType, headers: &'wrpc [(&'wrpc str, &'wrpc str)]) -> ::wrpc::Result<String> {
+                headers.iter().fold(::reqwasm::http::Request::post(&::std::format!("/api/named_lifetimes_work?{}", ::serde_urlencoded::to_string(query).unwrap())), |request, (name, value)| request.header(*name, *value))
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string(payload).unwrap())
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler<'wrpc>(query: &'wrpc Pagination, payload: &'wrpc MyType, headers: &'wrpc [(&'wrpc str, &'wrpc str)]) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                headers.iter().fold(__wrpc_client.post(&::std::format!("/api/named_lifetimes_work?{}", ::serde_urlencoded::to_string(query).unwrap())), |request, (name, value)| request.header(*name, *value))
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string(payload).unwrap())
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn status_code_return_works() {
+        let attr_tokens = quote!(delete("/api/status_code_return_works/:id"));
+        let handler_tokens = quote! {
+            pub async fn handler(Path(id): Path<u32>) -> StatusCode {
+                StatusCode::NO_CONTENT
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Path(id): Path<u32>) -> StatusCode {
+                StatusCode::NO_CONTENT
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(id: u32) -> ::wrpc::Result<()> {
+                ::reqwasm::http::Request::delete(&::std::format!("/api/status_code_return_works/{}", id))
+                    .send()
+                    .await
+                    .map(|_| ())
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(id: u32) -> ::wrpc::Result<()> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.delete(&::std::format!("/api/status_code_return_works/{}", id))
+                    .send()
+                    .await
+                    .map(|_| ())
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn unit_override_works() {
+        let attr_tokens = quote!(post("/api/unit_override_works"), returns(()));
+        let handler_tokens = quote! {
+            pub async fn handler(payload: String) -> impl IntoResponse {
+                StatusCode::ACCEPTED
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(payload: String) -> impl IntoResponse {
+                StatusCode::ACCEPTED
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(payload: &str) -> ::wrpc::Result<()> {
+                ::reqwasm::http::Request::post("/api/unit_override_works")
+                    .body(::std::string::ToString::to_string(payload))
+                    .send()
+                    .await
+                    .map(|_| ())
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(payload: &str) -> ::wrpc::Result<()> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.post("/api/unit_override_works")
+                    .body(::std::string::ToString::to_string(payload))
+                    .send()
+                    .await
+                    .map(|_| ())
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn owned_query_works() {
+        let attr_tokens = quote!(get("/api/owned_query_works"), query(owned));
+        let handler_tokens = quote! {
+            pub async fn handler(query: Query<Pagination>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(query: Query<Pagination>) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(query: Pagination) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get(&::std::format!("/api/owned_query_works?{}", ::serde_urlencoded::to_string(&query).unwrap()))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(query: Pagination) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get(&::std::format!("/api/owned_query_works?{}", ::serde_urlencoded::to_string(&query).unwrap()))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn owned_body_works() {
+        let attr_tokens = quote!(post("/api/owned_body_works"), body(owned));
+        let handler_tokens = quote! {
+            pub async fn handler(payload: Json<Vec<MyType>>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(payload: Json<Vec<MyType> >) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(payload: Vec<MyType>) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::post("/api/owned_body_works")
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string(&payload).unwrap())
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(payload: Vec<MyType>) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.post("/api/owned_body_works")
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string(&payload).unwrap())
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn bincode_return_works() {
+        let attr_tokens = quote!(get("/api/bincode_return_works"), returns_bincode(MyType));
+        let handler_tokens = quote! {
+            pub async fn handler() -> Vec<u8> {
+                bincode::serialize(&MyType::new()).unwrap()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler() -> Vec<u8> {
+                bincode::serialize(&MyType::new()).unwrap()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler() -> ::std::result::Result<MyType, ::wrpc::Error> {
+                ::reqwasm::http::Request::get("/api/bincode_return_works")
+                    .send()
+                    .await?
+                    .binary()
+                    .await
+                    .map_err(::wrpc::Error::Request)
+                    .and_then(|bytes| ::bincode::deserialize(&bytes).map_err(::wrpc::Error::decode))
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler() -> ::std::result::Result<MyType, ::wrpc::Error> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get("/api/bincode_return_works")
+                    .send()
+                    .await?
+                    .bytes()
+                    .await
+                    .map_err(::wrpc::Error::Request)
+                    .and_then(|bytes| ::bincode::deserialize(&bytes).map_err(::wrpc::Error::decode))
+            }
+
+            const _: fn() = || {
+                fn assert_deserialize<T: ::serde::de::DeserializeOwned>() {}
+                assert_deserialize::<MyType>();
+            };
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn cbor_format_works() {
+        let attr_tokens = quote!(post("/api/cbor_format_works"), format(cbor));
+        let handler_tokens = quote! {
+            pub async fn handler(Cbor(payload): Cbor<MyType>) -> Cbor<MyType> {
+                Cbor(payload)
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Cbor(payload): Cbor<MyType>) -> Cbor<MyType> {
+                Cbor(payload)
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(payload: &MyType) -> ::std::result::Result<MyType, ::wrpc::Error> {
+                ::reqwasm::http::Request::post("/api/cbor_format_works")
+                    .header("Accept", "application/cbor")
+                    .header("Content-Type", "application/cbor")
+                    .body({
+                        let mut bytes = ::std::vec::Vec::new();
+                        ::wrpc::ciborium::ser::into_writer(payload, &mut bytes).unwrap();
+                        bytes
+                    })
+                    .send()
+                    .await?
+                    .binary()
+                    .await
+                    .map_err(::wrpc::Error::Request)
+                    .and_then(|bytes| ::wrpc::ciborium::de::from_reader(&bytes[..]).map_err(::wrpc::Error::decode))
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(payload: &MyType) -> ::std::result::Result<MyType, ::wrpc::Error> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.post("/api/cbor_format_works")
+                    .header("Accept", "application/cbor")
+                    .header("Content-Type", "application/cbor")
+                    .body({
+                        let mut bytes = ::std::vec::Vec::new();
+                        ::wrpc::ciborium::ser::into_writer(payload, &mut bytes).unwrap();
+                        bytes
+                    })
+                    .send()
+                    .await?
+                    .bytes()
+                    .await
+                    .map_err(::wrpc::Error::Request)
+                    .and_then(|bytes| ::wrpc::ciborium::de::from_reader(&bytes[..]).map_err(::wrpc::Error::decode))
+            }
+
+            const _: fn() = || {
+                fn assert_deserialize<T: ::serde::de::DeserializeOwned>() {}
+                assert_deserialize::<MyType>();
+            };
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn msgpack_format_works() {
+        let attr_tokens = quote!(post("/api/msgpack_format_works"), format(msgpack));
+        let handler_tokens = quote! {
+            pub async fn handler(MsgPack(payload): MsgPack<MyType>) -> MsgPack<MyType> {
+                MsgPack(payload)
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(MsgPack(payload): MsgPack<MyType>) -> MsgPack<MyType> {
+                MsgPack(payload)
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(payload: &MyType) -> ::std::result::Result<MyType, ::wrpc::Error> {
+                ::reqwasm::http::Request::post("/api/msgpack_format_works")
+                    .header("Accept", "application/msgpack")
+                    .header("Content-Type", "application/msgpack")
+                    .body(::wrpc::rmp_serde::to_vec(payload).unwrap())
+                    .send()
+                    .await?
+                    .binary()
+                    .await
+                    .map_err(::wrpc::Error::Request)
+                    .and_then(|bytes| ::wrpc::rmp_serde::from_slice(&bytes).map_err(::wrpc::Error::decode))
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(payload: &MyType) -> ::std::result::Result<MyType, ::wrpc::Error> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.post("/api/msgpack_format_works")
+                    .header("Accept", "application/msgpack")
+                    .header("Content-Type", "application/msgpack")
+                    .body(::wrpc::rmp_serde::to_vec(payload).unwrap())
+                    .send()
+                    .await?
+                    .bytes()
+                    .await
+                    .map_err(::wrpc::Error::Request)
+                    .and_then(|bytes| ::wrpc::rmp_serde::from_slice(&bytes).map_err(::wrpc::Error::decode))
+            }
+
+            const _: fn() = || {
+                fn assert_deserialize<T: ::serde::de::DeserializeOwned>() {}
+                assert_deserialize::<MyType>();
+            };
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn expect_version_works() {
+        let attr_tokens = quote!(get("/api/expect_version_works"), expect_version("2"));
+        let handler_tokens = quote! {
+            pub async fn handler() -> Json<MyType> {
+                Json(MyType::default())
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler() -> Json<MyType> {
+                Json(MyType::default())
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler() -> ::std::result::Result<MyType, ::wrpc::Error> {
+                ::reqwasm::http::Request::get("/api/expect_version_works")
+                    .header("Accept", "application/json")
+                    .send()
+                    .await
+                    .map_err(::wrpc::Error::Request)
+                    .and_then(|response| ::wrpc::response::expect_version(response, "2"))?
+                    .json()
+                    .await
+                    .map_err(::wrpc::Error::Request)
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler() -> ::std::result::Result<MyType, ::wrpc::Error> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get("/api/expect_version_works")
+                    .header("Accept", "application/json")
+                    .send()
+                    .await
+                    .map_err(::wrpc::Error::Request)
+                    .and_then(|response| ::wrpc::response::expect_version(response, "2"))?
+                    .json()
+                    .await
+                    .map_err(::wrpc::Error::Request)
+            }
+
+            const _: fn() = || {
+                fn assert_deserialize<T: ::serde::de::DeserializeOwned>() {}
+                assert_deserialize::<MyType>();
+            };
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn pretty_body_works() {
+        let attr_tokens = quote!(post("/api/pretty_body_works"), pretty_body);
+        let handler_tokens = quote! {
+            pub async fn handler(Json(payload): Json<MyType>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Json(payload): Json<MyType>) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(payload: &MyType) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::post("/api/pretty_body_works")
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string_pretty(payload).unwrap())
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(payload: &MyType) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.post("/api/pretty_body_works")
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string_pretty(payload).unwrap())
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn json_pretty_works() {
+        let attr_tokens = quote!(post("/api/json_pretty_works"), json(pretty));
+        let handler_tokens = quote! {
+            pub async fn handler(Json(payload): Json<MyType>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Json(payload): Json<MyType>) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(payload: &MyType) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::post("/api/json_pretty_works")
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string_pretty(payload).unwrap())
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(payload: &MyType) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.post("/api/json_pretty_works")
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string_pretty(payload).unwrap())
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn json_unknown_style_errors() {
+        let attr_tokens = quote!(post("/api/json_unknown_style_errors"), json(compact));
+        let handler_tokens = quote! {
+            pub async fn handler(Json(payload): Json<MyType>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let err = rpc_impl(attr_tokens, handler_tokens).unwrap_err();
+        assert_eq!(err.to_string(), "Expected `pretty`");
+    }
+
+    #[test]
+    pub fn accept_override_works() {
+        let attr_tokens = quote!(get("/api/accept_override_works"), accept("text/csv"));
+        let handler_tokens = quote! {
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get("/api/accept_override_works")
+                    .header("Accept", "text/csv")
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get("/api/accept_override_works")
+                    .header("Accept", "text/csv")
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn explicit_json_return_works() {
+        let attr_tokens = quote!(
+            get("/api/explicit_json_return_works"),
+            returns(json(MyType))
+        );
+        let handler_tokens = quote! {
+            pub async fn handler() -> J<MyType> {
+                J(MyType::default())
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler() -> J<MyType> {
+                J(MyType::default())
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<MyType> {
+                ::reqwasm::http::Request::get("/api/explicit_json_return_works")
+                    .header("Accept", "application/json")
+                    .send()
+                    .await?
+                    .json()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<MyType> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get("/api/explicit_json_return_works")
+                    .header("Accept", "application/json")
+                    .send()
+                    .await?
+                    .json()
+                    .await
+            }
+
+            const _: fn() = || {
+                fn assert_deserialize<T: ::serde::de::DeserializeOwned>() {}
+                assert_deserialize::<MyType>();
+            };
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn extractor_aliases_work() {
+        let attr_tokens = quote!(
+            post("/api/extractor_aliases_work/:id"),
+            extractors(json = AxJson, query = AxQuery, path = AxPath)
+        );
+        let handler_tokens = quote! {
+            pub async fn handler(
+                AxPath(id): AxPath<u32>,
+                AxQuery(query): AxQuery<MyQuery>,
+                AxJson(payload): AxJson<MyType>,
+            ) -> AxJson<User> {
+                AxJson(User::default())
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(
+                AxPath(id): AxPath<u32>,
+                AxQuery(query): AxQuery<MyQuery>,
+                AxJson(payload): AxJson<MyType>,
+            ) -> AxJson<User> {
+                AxJson(User::default())
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(id: u32, query: &MyQuery, payload: &MyType) -> ::wrpc::Result<User> {
+                ::reqwasm::http::Request::post(&::std::format!(
+                    "/api/extractor_aliases_work/{}?{}",
+                    id,
+                    ::serde_urlencoded::to_string(query).unwrap()
+                ))
+                    .header("Accept", "application/json")
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string(payload).unwrap())
+                    .send()
+                    .await?
+                    .json()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(id: u32, query: &MyQuery, payload: &MyType) -> ::wrpc::Result<User> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.post(&::std::format!(
+                    "/api/extractor_aliases_work/{}?{}",
+                    id,
+                    ::serde_urlencoded::to_string(query).unwrap()
+                ))
+                    .header("Accept", "application/json")
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string(payload).unwrap())
+                    .send()
+                    .await?
+                    .json()
+                    .await
+            }
+
+            const _: fn() = || {
+                fn assert_deserialize<T: ::serde::de::DeserializeOwned>() {}
+                assert_deserialize::<User>();
+            };
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn unknown_extractor_alias_kind_errors() {
+        let attr_tokens = quote!(
+            get("/api/unknown_extractor_alias_kind_errors"),
+            extractors(form = AxForm)
+        );
+        let handler_tokens = quote! {
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens)
+                .unwrap_err()
+                .to_string(),
+            "Expected `json`, `query` or `path`"
+        );
+    }
+
+    #[test]
+    pub fn text_return_works() {
+        let attr_tokens = quote!(get("/api/text_return_works"), returns(text(u32)));
+        let handler_tokens = quote! {
+            pub async fn handler() -> String {
+                "42".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler() -> String {
+                "42".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler() -> ::std::result::Result<u32, ::wrpc::Error> {
+                ::reqwasm::http::Request::get("/api/text_return_works")
+                    .send()
+                    .await?
+                    .text()
+                    .await
+                    .map_err(::wrpc::Error::Request)
+                    .and_then(|text| text.parse::<u32>().map_err(::wrpc::Error::decode))
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler() -> ::std::result::Result<u32, ::wrpc::Error> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get("/api/text_return_works")
+                    .send()
+                    .await?
+                    .text()
+                    .await
+                    .map_err(::wrpc::Error::Request)
+                    .and_then(|text| text.parse::<u32>().map_err(::wrpc::Error::decode))
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn bare_return_is_json() {
+        let handler_tokens = quote! {
+            pub async fn handler() -> J<MyType> {
+                J(MyType::default())
+            }
+        };
+
+        let bare = rpc_impl(
+            quote!(get("/api/bare_return_is_json"), returns(MyType)),
+            handler_tokens.clone(),
+        );
+        let json = rpc_impl(
+            quote!(get("/api/bare_return_is_json"), returns(json(MyType))),
+            handler_tokens,
+        );
+        assert_eq!(bare.unwrap().to_string(), json.unwrap().to_string());
+    }
+
+    #[test]
+    pub fn status_json_return_works() {
+        let attr_tokens = quote!(
+            get("/api/status_json_return_works"),
+            returns(status_json(User))
+        );
+        let handler_tokens = quote! {
+            pub async fn handler() -> (StatusCode, Json<User>) {
+                (StatusCode::CREATED, Json(User::default()))
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler() -> (StatusCode, Json<User>) {
+                (StatusCode::CREATED, Json(User::default()))
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<User> {
+                ::reqwasm::http::Request::get("/api/status_json_return_works")
+                    .header("Accept", "application/json")
+                    .send()
+                    .await?
+                    .json()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<User> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get("/api/status_json_return_works")
+                    .header("Accept", "application/json")
+                    .send()
+                    .await?
+                    .json()
+                    .await
+            }
+
+            const _: fn() = || {
+                fn assert_deserialize<T: ::serde::de::DeserializeOwned>() {}
+                assert_deserialize::<User >();
+            };
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn generic_return_override_works() {
+        let attr_tokens = quote!(
+            get("/api/generic_return_override_works"),
+            returns(Vec<User>)
+        );
+        let handler_tokens = quote! {
+            pub async fn handler() -> impl IntoResponse {
+                Json(vec![User::default()])
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler() -> impl IntoResponse {
+                Json(vec![User::default()])
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<Vec<User> > {
+                ::reqwasm::http::Request::get("/api/generic_return_override_works")
+                    .header("Accept", "application/json")
+                    .send()
+                    .await?
+                    .json()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<Vec<User> > {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get("/api/generic_return_override_works")
+                    .header("Accept", "application/json")
+                    .send()
+                    .await?
+                    .json()
+                    .await
+            }
+
+            const _: fn() = || {
+                fn assert_deserialize<T: ::serde::de::DeserializeOwned>() {}
+                assert_deserialize::<Vec<User> >();
+            };
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn optional_return_override_works() {
+        let attr_tokens = quote!(
+            get("/api/optional_return_override_works"),
+            returns(Option<User>)
+        );
+        let handler_tokens = quote! {
+            pub async fn handler() -> impl IntoResponse {
+                Json(None::<User>)
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler() -> impl IntoResponse {
+                Json(None::<User>)
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<Option<User> > {
+                ::reqwasm::http::Request::get("/api/optional_return_override_works")
+                    .header("Accept", "application/json")
+                    .send()
+                    .await?
+                    .json()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<Option<User> > {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get("/api/optional_return_override_works")
+                    .header("Accept", "application/json")
+                    .send()
+                    .await?
+                    .json()
+                    .await
+            }
+
+            const _: fn() = || {
+                fn assert_deserialize<T: ::serde::de::DeserializeOwned>() {}
+                assert_deserialize::<Option<User> >();
+            };
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn unknown_return_kind_errors() {
+        let attr_tokens = quote!(get("/api/unknown_return_kind_errors"), returns(xml(MyType)));
+        let handler_tokens = quote! {
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+        };
+
+        let err = rpc_impl(attr_tokens, handler_tokens).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Expected `json(T)`, `text(T)`, `ndjson(T)`, `status_json(T)`, `headers(...)` or a type"
+        );
+    }
+
+    #[test]
+    pub fn unknown_return_type_errors() {
+        let attr_tokens = quote!(get("/api/unknown_return_type_errors"));
+        let handler_tokens = quote! {
+            pub async fn handler() -> Response {
+                "hello world".into_response()
+            }
+        };
+
+        let err = rpc_impl(attr_tokens, handler_tokens).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The client's return type can't be derived from this type. Specify it with `returns(Type)`"
+        );
+    }
+
+    #[test]
+    pub fn unknown_return_type_with_override_works() {
+        let attr_tokens = quote!(
+            get("/api/unknown_return_type_with_override_works"),
+            returns(String)
+        );
+        let handler_tokens = quote! {
+            pub async fn handler() -> (StatusCode, Html<String>) {
+                (StatusCode::OK, Html("hello world".into()))
+            }
+        };
+
+        assert!(rpc_impl(attr_tokens, handler_tokens).is_ok());
+    }
+
+    #[test]
+    pub fn status_tuple_json_return_works() {
+        let attr_tokens = quote!(get("/api/status_tuple_json_return_works"));
+        let handler_tokens = quote! {
+            pub async fn handler() -> (StatusCode, Json<User>) {
+                (StatusCode::CREATED, Json(User::default()))
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler() -> (StatusCode, Json<User>) {
+                (StatusCode::CREATED, Json(User::default()))
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<User> {
+                ::reqwasm::http::Request::get("/api/status_tuple_json_return_works")
+                    .header("Accept", "application/json")
+                    .send()
+                    .await?
+                    .json()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<User> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get("/api/status_tuple_json_return_works")
+                    .header("Accept", "application/json")
+                    .send()
+                    .await?
+                    .json()
+                    .await
+            }
+
+            const _: fn() = || {
+                fn assert_deserialize<T: ::serde::de::DeserializeOwned>() {}
+                assert_deserialize::<User>();
+            };
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn status_tuple_text_return_works() {
+        let attr_tokens = quote!(get("/api/status_tuple_text_return_works"));
+        let handler_tokens = quote! {
+            pub async fn handler() -> (StatusCode, String) {
+                (StatusCode::ACCEPTED, "hello world".into())
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler() -> (StatusCode, String) {
+                (StatusCode::ACCEPTED, "hello world".into())
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get("/api/status_tuple_text_return_works")
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get("/api/status_tuple_text_return_works")
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn ambiguous_tuple_return_errors() {
+        let attr_tokens = quote!(get("/api/ambiguous_tuple_return_errors"));
+        let handler_tokens = quote! {
+            pub async fn handler() -> (StatusCode, Json<User>, Json<Team>) {
+                todo!()
+            }
+        };
+
+        let err = rpc_impl(attr_tokens, handler_tokens).unwrap_err();
+        assert_eq!(err.to_string(), "Tuple response has more than one body");
+    }
+
+    #[test]
+    pub fn path_helper_works() {
+        let attr_tokens = quote!(get("/api/path_helper_works/:id"), path_helper);
+        let handler_tokens = quote! {
+            pub async fn handler(Path(id): Path<u32>, Query(query): Query<MyQuery>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Path(id): Path<u32>, Query(query): Query<MyQuery>) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(id: u32, query: &MyQuery) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get(&::std::format!("/api/path_helper_works/{}?{}", id, ::serde_urlencoded::to_string(query).unwrap()))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(id: u32, query: &MyQuery) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get(&::std::format!("/api/path_helper_works/{}?{}", id, ::serde_urlencoded::to_string(query).unwrap()))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            pub fn handler_path(id: u32, query: &MyQuery) -> ::std::string::String {
+                ::std::format!("/api/path_helper_works/{}?{}", id, ::serde_urlencoded::to_string(query).unwrap())
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn request_helper_works() {
+        let attr_tokens = quote!(
+            post("/api/request_helper_works/:id"),
+            default_headers("X-Client=wrpc"),
+            request_helper
+        );
+        let handler_tokens = quote! {
+            pub async fn handler(Path(id): Path<u32>, Json(payload): Json<MyType>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Path(id): Path<u32>, Json(payload): Json<MyType>) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(id: u32, payload: &MyType) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::post(&::std::format!("/api/request_helper_works/{}", id))
+                    .header("X-Client", "wrpc")
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string(payload).unwrap())
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(id: u32, payload: &MyType) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.post(&::std::format!("/api/request_helper_works/{}", id))
+                    .header("X-Client", "wrpc")
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string(payload).unwrap())
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            pub fn call_handler_request(id: u32, payload: &MyType) -> ::wrpc::RequestDescription {
+                let mut __wrpc_headers = ::std::vec::Vec::new();
+                __wrpc_headers.push((
+                    ::std::string::ToString::to_string("X-Client"),
+                    ::std::string::ToString::to_string("wrpc"),
+                ));
+                __wrpc_headers.push((
+                    ::std::string::ToString::to_string("Content-Type"),
+                    ::std::string::ToString::to_string("application/json"),
+                ));
+                ::wrpc::RequestDescription {
+                    method: ::std::string::ToString::to_string("POST"),
+                    url: ::std::format!("/api/request_helper_works/{}", id),
+                    headers: __wrpc_headers,
+                    body: ::std::option::Option::Some(
+                        ::std::convert::Into::<::std::vec::Vec<u8>>::into(::serde_json::to_string(payload).unwrap())
+                    ),
+                }
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn inline_path_type_works() {
+        let attr_tokens = quote!(get("/api/inline_path_type_works/:id<u32>/:name<String>"));
+        let handler_tokens = quote! {
+            pub async fn handler(Path((id, name)): Path<(u32, String)>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Path((id, name)): Path<(u32, String)>) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(id: u32, name: &str) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get(&::std::format!("/api/inline_path_type_works/{}/{}", id, name))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(id: u32, name: &str) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get(&::std::format!("/api/inline_path_type_works/{}/{}", id, name))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn inline_path_type_mismatch_errors() {
+        let attr_tokens = quote!(get("/api/inline_path_type_mismatch_errors/:id<u64>"));
+        let handler_tokens = quote! {
+            pub async fn handler(Path(id): Path<u32>) -> String {
+                "hello world".into()
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens)
+                .unwrap_err()
+                .to_string(),
+            "Path segment `:id` is declared as `u64`, but the handler extracts `u32`"
+        );
+    }
+
+    #[test]
+    pub fn response_headers_works() {
+        let attr_tokens = quote!(
+            get("/api/response_headers_works/:id"),
+            returns(headers(ETag, LastModified))
+        );
+        let handler_tokens = quote! {
+            pub async fn handler(Path(id): Path<u32>) -> impl IntoResponse {
+                StatusCode::OK
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Path(id): Path<u32>) -> impl IntoResponse {
+                StatusCode::OK
+            }
+
+            #[derive(Debug, Clone)]
+            pub struct HandlerHeaders {
+                pub etag: ::std::option::Option<ETag>,
+                pub last_modified: ::std::option::Option<LastModified>,
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(id: u32) -> ::wrpc::Result<HandlerHeaders> {
+                {
+                    let response = ::reqwasm::http::Request::get(&::std::format!("/api/response_headers_works/{}", id))
+                        .send()
+                        .await?;
+                    ::std::result::Result::Ok(HandlerHeaders {
+                        etag: ::wrpc::response::header(&response, <ETag as ::headers::Header>::name().as_str())
+                            .and_then(|value| ::headers::HeaderValue::from_str(&value).ok())
+                            .and_then(|value| <ETag as ::headers::Header>::decode(&mut ::std::iter::once(&value)).ok()),
+                        last_modified: ::wrpc::response::header(&response, <LastModified as ::headers::Header>::name().as_str())
+                            .and_then(|value| ::headers::HeaderValue::from_str(&value).ok())
+                            .and_then(|value| <LastModified as ::headers::Header>::decode(&mut ::std::iter::once(&value)).ok()),
+                    })
+                }
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(id: u32) -> ::wrpc::Result<HandlerHeaders> {
+                let __wrpc_client = ::reqwest::Client::new();
+                {
+                    let response = __wrpc_client.get(&::std::format!("/api/response_headers_works/{}", id))
+                        .send()
+                        .await?;
+                    ::std::result::Result::Ok(HandlerHeaders {
+                        etag: ::wrpc::response::header(&response, <ETag as ::headers::Header>::name().as_str())
+                            .and_then(|value| ::headers::HeaderValue::from_str(&value).ok())
+                            .and_then(|value| <ETag as ::headers::Header>::decode(&mut ::std::iter::once(&value)).ok()),
+                        last_modified: ::wrpc::response::header(&response, <LastModified as ::headers::Header>::name().as_str())
+                            .and_then(|value| ::headers::HeaderValue::from_str(&value).ok())
+                            .and_then(|value| <LastModified as ::headers::Header>::decode(&mut ::std::iter::once(&value)).ok()),
+                    })
+                }
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn response_headers_with_companions_errors() {
+        let attr_tokens = quote!(
+            get("/api/response_headers_with_companions_errors"),
+            returns(headers(ETag)),
+            returns_with_status
+        );
+        let handler_tokens = quote! {
+            pub async fn handler() -> impl IntoResponse {
+                StatusCode::OK
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens)
+                .unwrap_err()
+                .to_string(),
+            "`returns(headers(...))` can't be combined with `returns_with_status`, `returns_headers` or `returns_final_url`"
+        );
+    }
+
+    #[test]
+    pub fn raw_works() {
+        let attr_tokens = quote!(get("/api/raw_works/:id"), raw);
+        let handler_tokens = quote! {
+            pub async fn handler(Path(id): Path<u32>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Path(id): Path<u32>) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(id: u32) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get(&::std::format!("/api/raw_works/{}", id))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(id: u32) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get(&::std::format!("/api/raw_works/{}", id))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler_raw(id: u32) -> ::wrpc::Result<::wrpc::Response> {
+                ::reqwasm::http::Request::get(&::std::format!("/api/raw_works/{}", id))
+                    .send()
+                    .await
+                    .map(::wrpc::Response::from)
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler_raw(id: u32) -> ::wrpc::Result<::wrpc::Response> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get(&::std::format!("/api/raw_works/{}", id))
+                    .send()
+                    .await
+                    .map(::wrpc::Response::from)
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn raw_with_api_error_works() {
+        let attr_tokens = quote!(get("/api/raw_with_api_error_works"), error(ApiError), raw);
+        let handler_tokens = quote! {
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler() -> ::std::result::Result<String, ::wrpc::Error<ApiError>> {
+                ::wrpc::response::api_error(::reqwasm::http::Request::get("/api/raw_with_api_error_works")
+                    .send()
+                    .await)
+                    .await?
+                    .text()
+                    .await
+                    .map_err(::wrpc::Error::Request)
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler() -> ::std::result::Result<String, ::wrpc::Error<ApiError>> {
+                let __wrpc_client = ::reqwest::Client::new();
+                ::wrpc::response::api_error(__wrpc_client.get("/api/raw_with_api_error_works")
+                    .send()
+                    .await)
+                    .await?
+                    .text()
+                    .await
+                    .map_err(::wrpc::Error::Request)
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler_raw() -> ::std::result::Result<::wrpc::Response, ::wrpc::Error<ApiError>> {
+                ::wrpc::response::api_error(::reqwasm::http::Request::get("/api/raw_with_api_error_works")
+                    .send()
+                    .await)
+                    .await
+                    .map(::wrpc::Response::from)
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler_raw() -> ::std::result::Result<::wrpc::Response, ::wrpc::Error<ApiError>> {
+                let __wrpc_client = ::reqwest::Client::new();
+                ::wrpc::response::api_error(__wrpc_client.get("/api/raw_with_api_error_works")
+                    .send()
+                    .await)
+                    .await
+                    .map(::wrpc::Response::from)
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn request_helper_with_multipart_errors() {
+        let attr_tokens = quote!(
+            post("/api/request_helper_with_multipart_errors"),
+            request_helper
+        );
+        let handler_tokens = quote! {
+            pub async fn handler(multipart: Multipart) -> String {
+                "hello world".into()
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens)
+                .unwrap_err()
+                .to_string(),
+            "Multipart bodies can't be described by `request_helper`"
+        );
+    }
+
+    #[test]
+    pub fn state_and_extension_are_skipped() {
+        let attr_tokens = quote!(get("/api/state_and_extension_are_skipped"));
+        let handler_tokens = quote! {
+            pub async fn handler(State(state): State<AppState>, Extension(db): Extension<Db>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(State(state): State<AppState>, Extension(db): Extension<Db>) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get("/api/state_and_extension_are_skipped")
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get("/api/state_and_extension_are_skipped")
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn unknown_extractor_errors() {
+        let attr_tokens = quote!(post("/api/unknown_extractor_errors"));
+        let handler_tokens = quote! {
+            pub async fn handler(Jsonn(payload): Jsonn<MyType>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let err = rpc_impl(attr_tokens, handler_tokens).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unknown extractor. Use `State` or `Extension` for server side state, or `allow_unknown_extractors` to skip it"
+        );
+    }
+
+    #[test]
+    pub fn allow_unknown_extractors_works() {
+        let attr_tokens = quote!(
+            get("/api/allow_unknown_extractors_works"),
+            allow_unknown_extractors
+        );
+        let handler_tokens = quote! {
+            pub async fn handler(ConnectInfo(addr): ConnectInfo<SocketAddr>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(ConnectInfo(addr): ConnectInfo<SocketAddr>) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get("/api/allow_unknown_extractors_works")
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get("/api/allow_unknown_extractors_works")
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn allow_unknown_tuple_argument_works() {
+        let attr_tokens = quote!(
+            get("/api/allow_unknown_tuple_argument_works"),
+            allow_unknown_extractors
+        );
+        let handler_tokens = quote! {
+            pub async fn handler(x: (u32, u32)) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(x: (u32, u32)) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get("/api/allow_unknown_tuple_argument_works")
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get("/api/allow_unknown_tuple_argument_works")
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn returns_headers_works() {
+        let attr_tokens = quote!(get("/api/returns_headers_works"), returns_headers);
+        let handler_tokens = quote! {
+            pub async fn handler() -> (HeaderMap, Json<User>) {
+                (HeaderMap::new(), Json(User::default()))
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler() -> (HeaderMap, Json<User>) {
+                (HeaderMap::new(), Json(User::default()))
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<(::wrpc::ClientHeaders, User)> {
+                {
+                    let response = ::reqwasm::http::Request::get("/api/returns_headers_works")
+                    .header("Accept", "application/json")
+                        .send()
+                        .await?;
+                    let headers = ::wrpc::response::headers(&response);
+                    response.json().await.map(|body| (headers, body))
+                }
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<(::wrpc::ClientHeaders, User)> {
+                let __wrpc_client = ::reqwest::Client::new();
+                {
+                    let response = __wrpc_client.get("/api/returns_headers_works")
+                    .header("Accept", "application/json")
+                        .send()
+                        .await?;
+                    let headers = ::wrpc::response::headers(&response);
+                    response.json().await.map(|body| (headers, body))
+                }
+            }
+
+            const _: fn() = || {
+                fn assert_deserialize<T: ::serde::de::DeserializeOwned>() {}
+                assert_deserialize::<User>();
+            };
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn must_use_is_emitted() {
+        let attr_tokens = quote!(get("/api/must_use_is_emitted"));
+        let handler_tokens = quote! {
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+        };
+
+        let output = rpc_impl(attr_tokens, handler_tokens).unwrap().to_string();
+        let must_use = quote!(#[must_use] pub async fn call_handler).to_string();
+        assert_eq!(output.matches(&must_use).count(), 2);
+    }
+
+    #[test]
+    pub fn fire_and_forget_works() {
+        let attr_tokens = quote!(post("/api/fire_and_forget_works"), fire_and_forget);
+        let handler_tokens = quote! {
+            pub async fn handler(Json(event): Json<Event>) -> Json<Receipt> {
+                Json(Receipt::default())
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Json(event): Json<Event>) -> Json<Receipt> {
+                Json(Receipt::default())
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(event: &Event) -> ::wrpc::Result<()> {
+                ::reqwasm::http::Request::post("/api/fire_and_forget_works")
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string(event).unwrap())
+                    .send()
+                    .await
+                    .map(|_| ())
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(event: &Event) -> ::wrpc::Result<()> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.post("/api/fire_and_forget_works")
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string(event).unwrap())
+                    .send()
+                    .await
+                    .map(|_| ())
+            }
+        };
+
+        let output = rpc_impl(attr_tokens, handler_tokens).unwrap().to_string();
+        assert!(!output.contains(". json ()"));
+        assert_eq!(output, expected.to_string());
+    }
+
+    #[test]
+    pub fn fire_and_forget_conflicts_with_returns() {
+        let attr_tokens = quote!(
+            post("/api/fire_and_forget_conflicts"),
+            returns(Receipt),
+            fire_and_forget
+        );
+        let handler_tokens = quote! {
+            pub async fn handler() -> impl IntoResponse {
+                StatusCode::OK
+            }
+        };
+
+        let err = rpc_impl(attr_tokens, handler_tokens).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`fire_and_forget` can't be combined with a return override"
+        );
+    }
+
+    #[test]
+    pub fn docs_hidden_on_one_branch() {
+        let attr_tokens = quote!(get("/api/docs_hidden_on_one_branch"));
+        let handler_tokens = quote! {
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+        };
+
+        let output = rpc_impl(attr_tokens, handler_tokens).unwrap().to_string();
+        let hidden = quote! {
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+        };
+        assert_eq!(output.matches("doc (hidden)").count(), 1);
+        assert!(output.contains(&hidden.to_string()));
+    }
+
+    #[test]
+    pub fn multipart_works() {
+        let attr_tokens = quote!(post("/api/multipart_works"));
+        let handler_tokens = quote! {
+            pub async fn handler(multipart: Multipart) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(multipart: Multipart) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(multipart: ::std::vec::Vec<::wrpc::multipart::Part>) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::post("/api/multipart_works")
+                    .body(::wrpc::multipart::form(multipart))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(multipart: ::std::vec::Vec<::wrpc::multipart::Part>) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.post("/api/multipart_works")
+                    .multipart(::wrpc::multipart::form(multipart))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn json_vec_body_works() {
+        let attr_tokens = quote!(post("/api/json_vec_body_works"));
+        let handler_tokens = quote! {
+            pub async fn handler(Json(users): Json<Vec<User>>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Json(users): Json<Vec<User> >) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(users: &[User]) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::post("/api/json_vec_body_works")
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string(users).unwrap())
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(users: &[User]) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.post("/api/json_vec_body_works")
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string(users).unwrap())
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn optional_query_and_json_works() {
+        let attr_tokens = quote!(patch("/api/optional_query_and_json_works"));
+        let handler_tokens = quote! {
+            pub async fn handler(query: Option<Query<Pagination>>, payload: Option<Json<MyType>>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(query: Option<Query<Pagination> >, payload: Option<Json<MyType> >) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(
+                query: ::std::option::Option<&Pagination>,
+                payload: ::std::option::Option<&MyType>
+            ) -> ::wrpc::Result<String> {
+                payload
+                    .into_iter()
+                    .fold(
+                        ::reqwasm::http::Request::patch(&::std::format!(
+                            "/api/optional_query_and_json_works{}",
+                            ::wrpc::query::optional(
+                                query.map(|query| ::serde_urlencoded::to_string(query).unwrap()),
+                                false
+                            )
+                        )),
+                        |request, payload| request.header("Content-Type", "application/json").body(::serde_json::to_string(payload).unwrap())
+                    )
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(
+                query: ::std::option::Option<&Pagination>,
+                payload: ::std::option::Option<&MyType>
+            ) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                payload
+                    .into_iter()
+                    .fold(
+                        __wrpc_client.patch(&::std::format!(
+                            "/api/optional_query_and_json_works{}",
+                            ::wrpc::query::optional(
+                                query.map(|query| ::serde_urlencoded::to_string(query).unwrap()),
+                                false
+                            )
+                        )),
+                        |request, payload| request.header("Content-Type", "application/json").body(::serde_json::to_string(payload).unwrap())
+                    )
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn retry_works() {
+        let attr_tokens = quote!(get("/api/retry_works/:id"), retry(3, 100));
+        let handler_tokens = quote! {
+            pub async fn handler(Path(id): Path<u32>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Path(id): Path<u32>) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(id: u32) -> ::wrpc::Result<String> {
+                ::wrpc::retry::send(3, 100, || ::reqwasm::http::Request::get(&::std::format!("/api/retry_works/{}", id)))
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(id: u32) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                ::wrpc::retry::send(3, 100, || __wrpc_client.get(&::std::format!("/api/retry_works/{}", id)))
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn retry_requires_idempotent_method() {
+        let attr_tokens = quote!(post("/api/retry_requires_idempotent_method"), retry(3, 100));
+        let handler_tokens = quote! {
+            pub async fn handler(payload: String) -> String {
+                payload
+            }
+        };
+
+        let err = rpc_impl(attr_tokens, handler_tokens).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Only idempotent methods are retried. Use `retry(count, backoff_ms, always)` to retry anyway"
+        );
+
+        let attr_tokens = quote!(
+            post("/api/retry_requires_idempotent_method"),
+            retry(3, 100, always)
+        );
+        let handler_tokens = quote! {
+            pub async fn handler(payload: String) -> String {
+                payload
+            }
+        };
+        let output = rpc_impl(attr_tokens, handler_tokens).unwrap().to_string();
+        assert!(output.contains(":: wrpc :: retry :: send (3 , 100 , ||"));
+    }
+
+    #[test]
+    pub fn wasm_bindgen_works() {
+        let attr_tokens = quote!(post("/api/wasm_bindgen_works/:id"), wasm_bindgen);
+        let handler_tokens = quote! {
+            pub async fn handler(Path(id): Path<u32>, Json(payload): Json<MyType>) -> Json<MyType> {
+                Json(payload)
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Path(id): Path<u32>, Json(payload): Json<MyType>) -> Json<MyType> {
+                Json(payload)
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(id: u32, payload: &MyType) -> ::wrpc::Result<MyType> {
+                ::reqwasm::http::Request::post(&::std::format!("/api/wasm_bindgen_works/{}", id))
+                    .header("Accept", "application/json")
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string(payload).unwrap())
+                    .send()
+                    .await?
+                    .json()
+                    .await
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[::wasm_bindgen::prelude::wasm_bindgen(js_name = "handler")]
+            pub async fn handler_js(
+                id: ::wasm_bindgen::JsValue,
+                payload: ::wasm_bindgen::JsValue
+            ) -> ::std::result::Result<::wasm_bindgen::JsValue, ::wasm_bindgen::JsValue> {
+                let id: u32 = ::serde_wasm_bindgen::from_value(id)?;
+                let payload: MyType = ::serde_wasm_bindgen::from_value(payload)?;
+                let result = call_handler(id, &payload)
+                    .await
+                    .map_err(|err| ::wasm_bindgen::JsValue::from_str(&err.to_string()))?;
+                ::std::result::Result::Ok(::serde_wasm_bindgen::to_value(&result)?)
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(id: u32, payload: &MyType) -> ::wrpc::Result<MyType> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.post(&::std::format!("/api/wasm_bindgen_works/{}", id))
+                    .header("Accept", "application/json")
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string(payload).unwrap())
+                    .send()
+                    .await?
+                    .json()
+                    .await
+            }
+
+            const _: fn() = || {
+                fn assert_deserialize<T: ::serde::de::DeserializeOwned>() {}
+                assert_deserialize::<MyType>();
+            };
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn path_fmt_works() {
+        let attr_tokens = quote!(get("/api/path_fmt_works/:id"));
+        let handler_tokens = quote! {
+            pub async fn handler(#[path_fmt("{:x}")] Path(id): Path<u64>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Path(id): Path<u64>) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(id: u64) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get(&::std::format!("/api/path_fmt_works/{:x}", id))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(id: u64) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get(&::std::format!("/api/path_fmt_works/{:x}", id))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn path_fmt_requires_one_placeholder() {
+        let attr_tokens = quote!(get("/api/path_fmt_requires_one_placeholder/:id"));
+        let handler_tokens = quote! {
+            pub async fn handler(#[path_fmt("{:x}-{}")] Path(id): Path<u64>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let err = rpc_impl(attr_tokens, handler_tokens).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`path_fmt` must contain exactly one placeholder"
+        );
+    }
+
+    #[test]
+    pub fn compress_works() {
+        let attr_tokens = quote!(post("/api/compress_works"), compress(gzip));
+        let handler_tokens = quote! {
+            pub async fn handler(Json(payload): Json<MyType>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Json(payload): Json<MyType>) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(payload: &MyType) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::post("/api/compress_works")
+                    .header("Content-Type", "application/json")
+                    .header("Content-Encoding", "gzip")
+                    .body(::wrpc::compress::gzip(::serde_json::to_string(payload).unwrap()))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(payload: &MyType) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.post("/api/compress_works")
+                    .header("Content-Type", "application/json")
+                    .header("Content-Encoding", "gzip")
+                    .body(::wrpc::compress::gzip(::serde_json::to_string(payload).unwrap()))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn compress_multipart_errors() {
+        let attr_tokens = quote!(post("/api/compress_multipart_errors"), compress(deflate));
+        let handler_tokens = quote! {
+            pub async fn handler(multipart: Multipart) {}
+        };
+
+        let err = rpc_impl(attr_tokens, handler_tokens).unwrap_err();
+        assert_eq!(err.to_string(), "Multipart bodies can't be compressed");
+    }
+
+    #[test]
+    pub fn raw_bytes_return_works() {
+        let attr_tokens = quote!(get("/api/raw_bytes_return_works"), raw_bytes_return);
+        let handler_tokens = quote! {
+            pub async fn handler() -> Json<MyType> {
+                Json(MyType::default())
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler() -> Json<MyType> {
+                Json(MyType::default())
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<::wrpc::ClientBytes> {
+                ::reqwasm::http::Request::get("/api/raw_bytes_return_works")
+                    .send()
+                    .await?
+                    .binary()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<::wrpc::ClientBytes> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get("/api/raw_bytes_return_works")
+                    .send()
+                    .await?
+                    .bytes()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn query_static_works() {
+        let attr_tokens =
+            quote!(get("/api/query_static_works"), query_static("format" => "json", "v" => 2));
+        let handler_tokens = quote! {
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get(&::std::format!("/api/query_static_works?format={}&v={}", ::wrpc::query::encode(&"json"), ::wrpc::query::encode(&2)))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get(&::std::format!("/api/query_static_works?format={}&v={}", ::wrpc::query::encode(&"json"), ::wrpc::query::encode(&2)))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn query_static_with_query_works() {
+        let attr_tokens =
+            quote!(get("/api/query_static_with_query_works/:id"), query_static("v" => id));
+        let handler_tokens = quote! {
+            pub async fn handler(Path(id): Path<u32>, query: Query<Pagination>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Path(id): Path<u32>, query: Query<Pagination>) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(id: u32, query: &Pagination) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get(&::std::format!("/api/query_static_with_query_works/{}?{}&v={}", id, ::serde_urlencoded::to_string(query).unwrap(), ::wrpc::query::encode(&id)))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(id: u32, query: &Pagination) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get(&::std::format!("/api/query_static_with_query_works/{}?{}&v={}", id, ::serde_urlencoded::to_string(query).unwrap(), ::wrpc::query::encode(&id)))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn query_static_with_optional_query_works() {
+        let attr_tokens =
+            quote!(get("/api/query_static_with_optional_query_works"), query_static("v" => 2));
+        let handler_tokens = quote! {
+            pub async fn handler(query: Option<Query<Pagination>>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(query: Option<Query<Pagination> >) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(query: ::std::option::Option<&Pagination>) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get(&::std::format!(
+                    "/api/query_static_with_optional_query_works{}v={}",
+                    ::wrpc::query::optional(
+                        query.map(|query| ::serde_urlencoded::to_string(query).unwrap()),
+                        true
+                    ),
+                    ::wrpc::query::encode(&2)
+                ))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(query: ::std::option::Option<&Pagination>) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get(&::std::format!(
+                    "/api/query_static_with_optional_query_works{}v={}",
+                    ::wrpc::query::optional(
+                        query.map(|query| ::serde_urlencoded::to_string(query).unwrap()),
+                        true
+                    ),
+                    ::wrpc::query::encode(&2)
+                ))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn query_static_invalid_key_errors() {
+        let attr_tokens =
+            quote!(get("/api/query_static_invalid_key_errors"), query_static("a b" => 1));
+        let handler_tokens = quote! {
+            pub async fn handler() {}
+        };
+
+        let err = rpc_impl(attr_tokens, handler_tokens).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Query keys may only contain ASCII letters, digits, `-`, `_`, `.` and `~`"
+        );
+    }
+
+    #[test]
+    pub fn dump_dir_works() {
+        let attr_tokens = quote!(get("/api/dump_dir_works"));
+        let handler_tokens = quote! {
+            pub async fn dump_dir_works() -> String {
+                "hello world".into()
+            }
+        };
+
+        let dir = tempfile::tempdir().unwrap();
+        std::env::set_var("WRPC_DUMP_DIR", dir.path());
+        let result = rpc_impl(attr_tokens, handler_tokens);
+        std::env::remove_var("WRPC_DUMP_DIR");
+        result.unwrap();
+
+        let dumped = std::fs::read_to_string(dir.path().join("dump_dir_works.rs")).unwrap();
+        assert!(dumped.contains("pub async fn call_dump_dir_works() -> ::wrpc::Result<String> {"));
+        assert!(!dumped.contains("pub async fn dump_dir_works()"));
+    }
+
+    #[test]
+    pub fn cookies_works() {
+        let attr_tokens = quote!(get("/api/cookies_works"), cookies);
+        let handler_tokens = quote! {
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(cookie: ::std::option::Option<&str>) -> ::wrpc::Result<String> {
+                let _ = cookie;
+                ::reqwasm::http::Request::get("/api/cookies_works")
+                    .credentials(::reqwasm::http::RequestCredentials::Include)
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(cookie: ::std::option::Option<&str>) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::wrpc::cookies::client();
+                cookie
+                    .into_iter()
+                    .fold(__wrpc_client.get("/api/cookies_works"), |request, cookie| request.header("Cookie", cookie))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn etag_works() {
+        let attr_tokens = quote!(get("/api/etag_works"), etag);
+        let handler_tokens = quote! {
+            pub async fn handler() -> Json<User> {
+                Json(User::default())
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler() -> Json<User> {
+                Json(User::default())
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(etag: ::std::option::Option<&str>) -> ::wrpc::Result<::std::option::Option<User> > {
+                {
+                    let response = etag
+                        .into_iter()
+                        .fold(::reqwasm::http::Request::get("/api/etag_works")
+                    .header("Accept", "application/json"), |request, etag| request.header("If-None-Match", etag))
+                        .send()
+                        .await?;
+                    if ::wrpc::response::not_modified(&response) {
+                        ::std::result::Result::Ok(::std::option::Option::None)
+                    } else {
+                        ::std::result::Result::map(response.json().await, ::std::option::Option::Some)
+                    }
+                }
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(etag: ::std::option::Option<&str>) -> ::wrpc::Result<::std::option::Option<User> > {
+                let __wrpc_client = ::reqwest::Client::new();
+                {
+                    let response = etag
+                        .into_iter()
+                        .fold(__wrpc_client.get("/api/etag_works")
+                    .header("Accept", "application/json"), |request, etag| request.header("If-None-Match", etag))
+                        .send()
+                        .await?;
+                    if ::wrpc::response::not_modified(&response) {
+                        ::std::result::Result::Ok(::std::option::Option::None)
+                    } else {
+                        ::std::result::Result::map(response.json().await, ::std::option::Option::Some)
+                    }
+                }
+            }
+
+            const _: fn() = || {
+                fn assert_deserialize<T: ::serde::de::DeserializeOwned>() {}
+                assert_deserialize::<User>();
+            };
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn host_works() {
+        let attr_tokens = quote!(get("/api/host_works/:id"), host);
+        let handler_tokens = quote! {
+            pub async fn handler(Host(host): Host, Path(id): Path<u32>) -> String {
+                format!("{host} {id}")
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Host(host): Host, Path(id): Path<u32>) -> String {
+                format!("{host} {id}")
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(host: &str, id: u32) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get(&::std::format!("https://{}/api/host_works/{}", host, id))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(host: &str, id: u32) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get(&::std::format!("https://{}/api/host_works/{}", host, id))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn host_with_prefix_errors() {
+        let attr_tokens = quote!(get("/api/host_with_prefix_errors"), host, prefix(API));
+        let handler_tokens = quote! {
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens)
+                .unwrap_err()
+                .to_string(),
+            "`host` can't be combined with `prefix`"
+        );
+    }
+
+    #[test]
+    pub fn locale_works() {
+        let attr_tokens = quote!(get("/api/locale_works/:id"), locale);
+        let handler_tokens = quote! {
+            pub async fn handler(Path(id): Path<u32>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Path(id): Path<u32>) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(id: u32, locale: &str) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get(&::std::format!("/api/locale_works/{}", id))
+                    .header("Accept-Language", locale)
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(id: u32, locale: &str) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get(&::std::format!("/api/locale_works/{}", id))
+                    .header("Accept-Language", locale)
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn header_map_option_works() {
+        let attr_tokens = quote!(get("/api/header_map_option_works"), header_map);
+        let handler_tokens = quote! {
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(extra_headers: &::wrpc::ClientHeaders) -> ::wrpc::Result<String> {
+                extra_headers
+                    .entries()
+                    .fold(::reqwasm::http::Request::get("/api/header_map_option_works"), |request, (name, value)| request.header(&name, &value))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(extra_headers: &::wrpc::ClientHeaders) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get("/api/header_map_option_works")
+                    .headers(extra_headers.clone())
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn credentials_works() {
+        let attr_tokens = quote!(get("/api/credentials_works"), credentials(include));
+        let handler_tokens = quote! {
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get("/api/credentials_works")
+                    .credentials(::reqwasm::http::RequestCredentials::Include)
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get("/api/credentials_works")
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn returns_final_url_works() {
+        let attr_tokens = quote!(get("/api/returns_final_url_works"), returns_final_url);
+        let handler_tokens = quote! {
+            pub async fn handler() -> Json<User> {
+                Json(User::default())
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler() -> Json<User> {
+                Json(User::default())
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<(::std::string::String, User)> {
+                {
+                    let response = ::reqwasm::http::Request::get("/api/returns_final_url_works")
+                    .header("Accept", "application/json")
+                        .send()
+                        .await?;
+                    let url = ::wrpc::response::url(&response);
+                    response.json().await.map(|body| (url, body))
+                }
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<(::std::string::String, User)> {
+                let __wrpc_client = ::reqwest::Client::new();
+                {
+                    let response = __wrpc_client.get("/api/returns_final_url_works")
+                    .header("Accept", "application/json")
+                        .send()
+                        .await?;
+                    let url = ::wrpc::response::url(&response);
+                    response.json().await.map(|body| (url, body))
+                }
+            }
+
+            const _: fn() = || {
+                fn assert_deserialize<T: ::serde::de::DeserializeOwned>() {}
+                assert_deserialize::<User>();
+            };
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn returns_with_status_works() {
+        let attr_tokens = quote!(
+            post("/api/returns_with_status_works"),
+            returns_with_status,
+            returns_final_url
+        );
+        let handler_tokens = quote! {
+            pub async fn handler() -> Json<User> {
+                Json(User::default())
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler() -> Json<User> {
+                Json(User::default())
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<(::wrpc::StatusCode, ::std::string::String, User)> {
+                {
+                    let response = ::reqwasm::http::Request::post("/api/returns_with_status_works")
+                    .header("Accept", "application/json")
+                        .send()
+                        .await?;
+                    let status = ::wrpc::response::status(&response);
+                    let url = ::wrpc::response::url(&response);
+                    response.json().await.map(|body| (status, url, body))
+                }
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<(::wrpc::StatusCode, ::std::string::String, User)> {
+                let __wrpc_client = ::reqwest::Client::new();
+                {
+                    let response = __wrpc_client.post("/api/returns_with_status_works")
+                    .header("Accept", "application/json")
+                        .send()
+                        .await?;
+                    let status = ::wrpc::response::status(&response);
+                    let url = ::wrpc::response::url(&response);
+                    response.json().await.map(|body| (status, url, body))
+                }
+            }
+
+            const _: fn() = || {
+                fn assert_deserialize<T: ::serde::de::DeserializeOwned>() {}
+                assert_deserialize::<User>();
+            };
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn api_error_works() {
+        let attr_tokens = quote!(get("/api/api_error_works"), returns(User), error(ApiError));
+        let handler_tokens = quote! {
+            pub async fn handler() -> impl IntoResponse {
+                Json(User::default())
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler() -> impl IntoResponse {
+                Json(User::default())
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler() -> ::std::result::Result<User, ::wrpc::Error<ApiError>> {
+                ::wrpc::response::api_error(
+                    ::reqwasm::http::Request::get("/api/api_error_works")
+                    .header("Accept", "application/json")
+                        .send()
+                        .await
+                )
+                .await?
+                .json()
+                .await
+                .map_err(::wrpc::Error::Request)
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler() -> ::std::result::Result<User, ::wrpc::Error<ApiError>> {
+                let __wrpc_client = ::reqwest::Client::new();
+                ::wrpc::response::api_error(
+                    __wrpc_client.get("/api/api_error_works")
+                    .header("Accept", "application/json")
+                        .send()
+                        .await
+                )
+                .await?
+                .json()
+                .await
+                .map_err(::wrpc::Error::Request)
+            }
+
+            const _: fn() = || {
+                fn assert_deserialize<T: ::serde::de::DeserializeOwned>() {}
+                assert_deserialize::<User>();
+            };
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn json_reference_works() {
+        let attr_tokens = quote!(post("/api/json_reference_works"));
+        let handler_tokens = quote! {
+            pub async fn handler(Json(payload): Json<&'static MyType>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Json(payload): Json<&'static MyType>) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(payload: &MyType) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::post("/api/json_reference_works")
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string(payload).unwrap())
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(payload: &MyType) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.post("/api/json_reference_works")
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string(payload).unwrap())
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn generics_work() {
+        let attr_tokens = quote!(post("/api/generics_work"));
+        let handler_tokens = quote! {
+            pub async fn handler<T: Serialize>(Json(item): Json<T>) -> Json<Vec<T> >
+            where
+                T: DeserializeOwned,
+            {
+                Json(vec![item])
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler<T: Serialize>(Json(item): Json<T>) -> Json<Vec<T> >
+            where
+                T: DeserializeOwned,
+            {
+                Json(vec![item])
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler<T: Serialize>(item: &T) -> ::wrpc::Result<Vec<T> >
+            where
+                T: DeserializeOwned,
+            {
+                ::reqwasm::http::Request::post("/api/generics_work")
+                    .header("Accept", "application/json")
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string(item).unwrap())
+                    .send()
+                    .await?
+                    .json()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler<T: Serialize>(item: &T) -> ::wrpc::Result<Vec<T> >
+            where
+                T: DeserializeOwned,
+            {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.post("/api/generics_work")
+                    .header("Accept", "application/json")
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string(item).unwrap())
+                    .send()
+                    .await?
+                    .json()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn graphql_works() {
+        let attr_tokens = quote!(post("/graphql"), graphql, returns(User));
+        let handler_tokens = quote! {
+            pub async fn handler(Json(request): Json<Request>) -> Json<Response> {
+                execute(request).await
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Json(request): Json<Request>) -> Json<Response> {
+                execute(request).await
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(query: &str, variables: &impl ::serde::Serialize) -> ::wrpc::Result<User> {
+                ::reqwasm::http::Request::post("/graphql")
+                    .header("Accept", "application/json")
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string(&::wrpc::graphql::Request { query, variables }).unwrap())
+                    .send()
+                    .await?
+                    .json::<::wrpc::graphql::Response<User>>()
+                    .await
+                    .map(|response| response.data)
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(query: &str, variables: &impl ::serde::Serialize) -> ::wrpc::Result<User> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.post("/graphql")
+                    .header("Accept", "application/json")
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string(&::wrpc::graphql::Request { query, variables }).unwrap())
+                    .send()
+                    .await?
+                    .json::<::wrpc::graphql::Response<User>>()
+                    .await
+                    .map(|response| response.data)
+            }
+
+            const _: fn() = || {
+                fn assert_deserialize<T: ::serde::de::DeserializeOwned>() {}
+                assert_deserialize::<User>();
+            };
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn graphql_without_data_type_errors() {
+        let attr_tokens = quote!(post("/graphql"), graphql);
+        let handler_tokens = quote! {
+            pub async fn handler(Json(request): Json<Request>) -> String {
+                execute(request).await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens)
+                .unwrap_err()
+                .to_string(),
+            "`graphql` needs the type of the `data` field, e.g. `returns(T)`"
+        );
+    }
+
+    #[test]
+    pub fn query_encoding_qs_works() {
+        let attr_tokens = quote!(get("/api/query_encoding_qs_works"), query_encoding(qs));
+        let handler_tokens = quote! {
+            pub async fn handler(query: Query<Pagination>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(query: Query<Pagination>) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(query: &Pagination) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get(&::std::format!("/api/query_encoding_qs_works?{}", ::serde_qs::to_string(query).unwrap()))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(query: &Pagination) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get(&::std::format!("/api/query_encoding_qs_works?{}", ::serde_qs::to_string(query).unwrap()))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn query_key_works() {
+        let attr_tokens = quote!(get("/api/query_key_works"), query_key("filter"));
+        let handler_tokens = quote! {
+            pub async fn handler(query: Query<Pagination>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(query: Query<Pagination>) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(query: &Pagination) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get(&::std::format!("/api/query_key_works?{}", ::wrpc::query::nest("filter", &::serde_urlencoded::to_string(query).unwrap())))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(query: &Pagination) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get(&::std::format!("/api/query_key_works?{}", ::wrpc::query::nest("filter", &::serde_urlencoded::to_string(query).unwrap())))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn query_key_without_query_errors() {
+        let attr_tokens = quote!(
+            get("/api/query_key_without_query_errors"),
+            query_key("filter")
+        );
+        let handler_tokens = quote! {
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens)
+                .unwrap_err()
+                .to_string(),
+            "`query_key` needs a `Query` argument to nest"
+        );
+    }
+
+    #[test]
+    pub fn query_encoding_invalid_errors() {
+        let attr_tokens = quote!(
+            get("/api/query_encoding_invalid_errors"),
+            query_encoding(json)
+        );
+        let handler_tokens = quote! {
+            pub async fn handler(query: Query<Pagination>) -> String {
+                "hello world".into()
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens)
+                .unwrap_err()
+                .to_string(),
+            "Expected one of `qs` or `urlencoded`"
+        );
+    }
+
+    #[test]
+    pub fn query_array_works() {
+        let attr_tokens = quote!(get("/api/query_array_works"), query_array(repeat));
+        let handler_tokens = quote! {
+            pub async fn handler(query: Query<Pagination>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(query: Query<Pagination>) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(query: &Pagination) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get(&::std::format!(
+                    "/api/query_array_works?{}",
+                    ::wrpc::query::to_string(query, ::wrpc::query::Arrays::Repeat)
+                ))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(query: &Pagination) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get(&::std::format!(
+                    "/api/query_array_works?{}",
+                    ::wrpc::query::to_string(query, ::wrpc::query::Arrays::Repeat)
+                ))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn query_array_invalid_errors() {
+        let attr_tokens = quote!(get("/api/query_array_invalid_errors"), query_array(indexed));
+        let handler_tokens = quote! {
+            pub async fn handler(query: Query<Pagination>) -> String {
+                "hello world".into()
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens)
+                .unwrap_err()
+                .to_string(),
+            "Expected one of `repeat` or `brackets`"
+        );
+    }
+
+    #[test]
+    pub fn json_override_of_text_return_errors() {
+        let attr_tokens = quote!(
+            get("/api/json_override_of_text_return_errors"),
+            returns(User)
+        );
+        let handler_tokens = quote! {
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens)
+                .unwrap_err()
+                .to_string(),
+            "The handler returns text, but `returns(...)` decodes JSON. Return `Json<T>` from the handler or use `returns(text(T))` instead"
+        );
+    }
+
+    #[test]
+    pub fn prefix_works() {
+        let attr_tokens = quote!(get("/user/:id"), prefix(API_PREFIX));
+        let handler_tokens = quote! {
+            pub async fn handler(Path(id): Path<u32>, query: Query<Pagination>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Path(id): Path<u32>, query: Query<Pagination>) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(id: u32, query: &Pagination) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get(&::std::format!("{}/user/{}?{}", ::std::primitive::str::trim_end_matches(API_PREFIX, '/'), id, ::serde_urlencoded::to_string(query).unwrap()))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(id: u32, query: &Pagination) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get(&::std::format!("{}/user/{}?{}", ::std::primitive::str::trim_end_matches(API_PREFIX, '/'), id, ::serde_urlencoded::to_string(query).unwrap()))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn prefix_with_absolute_url_errors() {
+        let attr_tokens = quote!(get("http://localhost/user"), prefix(API_PREFIX));
+        let handler_tokens = quote! {
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens)
+                .unwrap_err()
+                .to_string(),
+            "`prefix` can't be combined with an absolute URL"
+        );
+    }
+
+    #[test]
+    pub fn default_headers_work() {
+        let attr_tokens = quote!(
+            get("/api/default_headers_work"),
+            default_headers("X-Client=wrpc", "X-Version=2")
+        );
+        let handler_tokens = quote! {
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get("/api/default_headers_work")
+                    .header("X-Client", "wrpc")
+                    .header("X-Version", "2")
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get("/api/default_headers_work")
+                    .header("X-Client", "wrpc")
+                    .header("X-Version", "2")
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn default_headers_without_value_errors() {
+        let attr_tokens = quote!(
+            get("/api/default_headers_without_value_errors"),
+            default_headers("X-Client")
+        );
+        let handler_tokens = quote! {
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens)
+                .unwrap_err()
+                .to_string(),
+            "Expected a header as `\"Name=value\"`"
+        );
+    }
+
+    #[test]
+    pub fn body_first_works() {
+        let attr_tokens = quote!(put("/api/user/:id"));
+        let handler_tokens = quote! {
+            pub async fn handler(Json(user): Json<User>, Path(id): Path<u32>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Json(user): Json<User>, Path(id): Path<u32>) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(id: u32, user: &User) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::put(&::std::format!("/api/user/{}", id))
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string(user).unwrap())
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(id: u32, user: &User) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.put(&::std::format!("/api/user/{}", id))
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string(user).unwrap())
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn stream_return_works() {
+        let attr_tokens = quote!(get("/api/stream_return_works"), returns(stream));
+        let handler_tokens = quote! {
+            pub async fn handler() -> Vec<u8> {
+                vec![0; 1024]
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler() -> Vec<u8> {
+                vec![0; 1024]
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            ::std::compile_error!("`returns(stream)` isn't supported on WASM yet");
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<impl ::wrpc::Stream<Item = ::wrpc::Result<::wrpc::ClientBytes>> + ::std::marker::Send> {
+                let __wrpc_client = ::reqwest::Client::new();
+                ::std::result::Result::Ok(__wrpc_client.get("/api/stream_return_works")
+                    .send()
+                    .await?
+                    .bytes_stream())
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn ndjson_return_works() {
+        let attr_tokens = quote!(get("/api/ndjson_return_works"), returns(ndjson(User)));
+        let handler_tokens = quote! {
+            pub async fn handler() -> String {
+                String::new()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler() -> String {
+                String::new()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            ::std::compile_error!("`returns(ndjson(T))` isn't supported on WASM yet");
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<impl ::wrpc::Stream<Item = ::std::result::Result<User, ::wrpc::Error>> + ::std::marker::Send> {
+                let __wrpc_client = ::reqwest::Client::new();
+                ::std::result::Result::Ok(::wrpc::ndjson::lines::<User>(__wrpc_client.get("/api/ndjson_return_works")
+                    .header("Accept", "application/x-ndjson")
+                    .send()
+                    .await?
+                    .bytes_stream()))
+            }
+
+            const _: fn() = || {
+                fn assert_deserialize<T: ::serde::de::DeserializeOwned>() {}
+                assert_deserialize::<User>();
+            };
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn vis_works() {
+        let attr_tokens = quote!(get("/api/vis_works"), vis(pub));
+        let handler_tokens = quote! {
+            pub(crate) async fn handler() -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub(crate) async fn handler() -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get("/api/vis_works")
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get("/api/vis_works")
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn content_type_works() {
+        let attr_tokens = quote!(post("/api/content_type_works"), content_type("text/csv"));
+        let handler_tokens = quote! {
+            pub async fn handler(payload: String) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(payload: String) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(payload: &str) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::post("/api/content_type_works")
+                    .header("Content-Type", "text/csv")
+                    .body(::std::string::ToString::to_string(payload))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(payload: &str) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.post("/api/content_type_works")
+                    .header("Content-Type", "text/csv")
+                    .body(::std::string::ToString::to_string(payload))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn content_type_on_delete_works() {
+        let attr_tokens = quote!(
+            delete("/api/content_type_on_delete_works"),
+            content_type("text/csv")
+        );
+        let handler_tokens = quote! {
+            pub async fn handler(payload: String) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(payload: String) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(payload: &str) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::delete("/api/content_type_on_delete_works")
+                    .header("Content-Type", "text/csv")
+                    .body(::std::string::ToString::to_string(payload))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(payload: &str) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.delete("/api/content_type_on_delete_works")
+                    .header("Content-Type", "text/csv")
+                    .body(::std::string::ToString::to_string(payload))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn content_type_overrides_json() {
+        let attr_tokens = quote!(
+            post("/api/content_type_overrides_json"),
+            content_type("application/vnd.api+json")
+        );
+        let handler_tokens = quote! {
+            pub async fn handler(Json(payload): Json<MyType>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Json(payload): Json<MyType>) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(payload: &MyType) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::post("/api/content_type_overrides_json")
+                    .header("Content-Type", "application/vnd.api+json")
+                    .body(::serde_json::to_string(payload).unwrap())
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(payload: &MyType) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.post("/api/content_type_overrides_json")
+                    .header("Content-Type", "application/vnd.api+json")
+                    .body(::serde_json::to_string(payload).unwrap())
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn content_type_without_body_errors() {
+        let attr_tokens = quote!(
+            get("/api/content_type_without_body_errors"),
+            content_type("text/csv")
+        );
+        let handler_tokens = quote! {
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens)
+                .unwrap_err()
+                .to_string(),
+            "`content_type` needs a method with a body, i.e. `post`, `put`, `patch` or `delete`"
+        );
+    }
+
+    #[test]
+    pub fn merge_patch_works() {
+        let attr_tokens = quote!(patch("/api/merge_patch_works"), merge_patch);
+        let handler_tokens = quote! {
+            pub async fn handler(Json(payload): Json<MyType>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Json(payload): Json<MyType>) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(payload: &MyType) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::patch("/api/merge_patch_works")
+                    .header("Content-Type", "application/merge-patch+json")
+                    .body(::serde_json::to_string(payload).unwrap())
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(payload: &MyType) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.patch("/api/merge_patch_works")
+                    .header("Content-Type", "application/merge-patch+json")
+                    .body(::serde_json::to_string(payload).unwrap())
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn merge_patch_without_patch_errors() {
+        let attr_tokens = quote!(post("/api/merge_patch_without_patch_errors"), merge_patch);
+        let handler_tokens = quote! {
+            pub async fn handler(Json(payload): Json<MyType>) -> String {
+                "hello world".into()
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens)
+                .unwrap_err()
+                .to_string(),
+            "`merge_patch` is only valid with `patch`"
+        );
+    }
+
+    #[test]
+    pub fn path_struct_works() {
+        let attr_tokens = quote!(
+            get("/api/team/:team/user/:id"),
+            path_struct(Params {
+                team: String,
+                id: u32
+            })
+        );
+        let handler_tokens = quote! {
+            pub async fn handler(Path(params): Path<Params>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Path(params): Path<Params>) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(team: &str, id: u32) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get(&::std::format!("/api/team/{}/user/{}", team, id))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(team: &str, id: u32) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get(&::std::format!("/api/team/{}/user/{}", team, id))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn path_struct_missing_segment_errors() {
+        let attr_tokens = quote!(
+            get("/api/team/:team/user/:id"),
+            path_struct(Params { id: u32 })
+        );
+        let handler_tokens = quote! {
+            pub async fn handler(Path(params): Path<Params>) -> String {
+                "hello world".into()
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens)
+                .unwrap_err()
+                .to_string(),
+            "Path segment `:team` has no matching field"
+        );
+    }
+
+    #[test]
+    pub fn ureq_client_works() {
+        let attr_tokens = quote!(
+            post("/api/ureq_client_works/:id"),
+            default_headers("X-Client=wrpc"),
+            native_client(ureq)
+        );
+        let handler_tokens = quote! {
+            pub async fn handler(Path(id): Path<u32>, Json(payload): Json<MyType>) -> Json<MyType> {
+                Json(payload)
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Path(id): Path<u32>, Json(payload): Json<MyType>) -> Json<MyType> {
+                Json(payload)
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(id: u32, payload: &MyType) -> ::wrpc::Result<MyType> {
+                ::reqwasm::http::Request::post(&::std::format!("/api/ureq_client_works/{}", id))
+                    .header("Accept", "application/json")
+                    .header("X-Client", "wrpc")
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string(payload).unwrap())
+                    .send()
+                    .await?
+                    .json()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub fn call_handler(id: u32, payload: &MyType) -> ::std::result::Result<MyType, ::wrpc::Error> {
+                let __wrpc_request = ::wrpc::ureq::request("POST", &::std::format!("/api/ureq_client_works/{}", id))
+                    .set("Accept", "application/json")
+                    .set("X-Client", "wrpc");
+                ::wrpc::ureq::send(
+                    __wrpc_request.set("Content-Type", "application/json"),
+                    ::serde_json::to_string(payload).unwrap()
+                )
+                    .and_then(::wrpc::ureq::json)
+            }
+
+            const _: fn() = || {
+                fn assert_deserialize<T: ::serde::de::DeserializeOwned>() {}
+                assert_deserialize::<MyType>();
+            };
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn ureq_client_with_retry_errors() {
+        let attr_tokens = quote!(
+            get("/api/ureq_client_with_retry_errors"),
+            retry(3, 100),
+            native_client(ureq)
+        );
+        let handler_tokens = quote! {
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens)
+                .unwrap_err()
+                .to_string(),
+            "`native_client(ureq)` doesn't support retry"
+        );
+    }
+
+    #[test]
+    pub fn client_path_segment_works() {
+        let attr_tokens = quote!(get("/api/client/:client"));
+        let handler_tokens = quote! {
+            pub async fn handler(Path(client): Path<String>) -> String {
+                client
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Path(client): Path<String>) -> String {
+                client
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(client: &str) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get(&::std::format!("/api/client/{}", client))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(client: &str) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get(&::std::format!("/api/client/{}", client))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn raw_query_works() {
+        let attr_tokens = quote!(get("/api/raw_query_works"));
+        let handler_tokens = quote! {
+            pub async fn handler(RawQuery(query): RawQuery) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(RawQuery(query): RawQuery) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(query: ::std::option::Option<&str>) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get(&::std::format!(
+                    "/api/raw_query_works{}",
+                    ::wrpc::query::optional(query.map(::std::string::ToString::to_string), false)
+                ))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(query: ::std::option::Option<&str>) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get(&::std::format!(
+                    "/api/raw_query_works{}",
+                    ::wrpc::query::optional(query.map(::std::string::ToString::to_string), false)
+                ))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn query_and_raw_query_errors() {
+        let attr_tokens = quote!(get("/api/query_and_raw_query_errors"));
+        let handler_tokens = quote! {
+            pub async fn handler(Query(query): Query<Pagination>, RawQuery(raw): RawQuery) -> String {
+                "hello world".into()
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens)
+                .unwrap_err()
+                .to_string(),
+            "Handlers can't take both `Query` and `RawQuery`"
+        );
+    }
+
+    #[test]
+    pub fn extra_query_works() {
+        let attr_tokens = quote!(get("/api/extra_query_works"), extra_query);
+        let handler_tokens = quote! {
+            pub async fn handler(query: Query<Pagination>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(query: Query<Pagination>) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(query: &Pagination, extra_query: &[(&str, &str)]) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get(&::wrpc::query::append(
+                    &::std::format!("/api/extra_query_works?{}", ::serde_urlencoded::to_string(query).unwrap()),
+                    extra_query
+                ))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(query: &Pagination, extra_query: &[(&str, &str)]) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get(&::wrpc::query::append(
+                    &::std::format!("/api/extra_query_works?{}", ::serde_urlencoded::to_string(query).unwrap()),
+                    extra_query
+                ))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn builder_works() {
+        let attr_tokens = quote!(get("/api/builder_works/:id"), builder);
+        let handler_tokens = quote! {
+            pub async fn get_user(Path(id): Path<u32>, Query(query): Query<Pagination>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn get_user(Path(id): Path<u32>, Query(query): Query<Pagination>) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_get_user(id: u32, query: &Pagination) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get(&::std::format!("/api/builder_works/{}?{}", id, ::serde_urlencoded::to_string(query).unwrap()))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_get_user(id: u32, query: &Pagination) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get(&::std::format!("/api/builder_works/{}?{}", id, ::serde_urlencoded::to_string(query).unwrap()))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            pub struct GetUserRequest {
+                id: u32,
+                query: Pagination,
+            }
+
+            impl GetUserRequest {
+                #[must_use]
+                pub fn new(id: u32, query: Pagination) -> Self {
+                    Self {
+                        id,
+                        query,
+                    }
+                }
+
+                #[must_use]
+                pub async fn send(self) -> ::wrpc::Result<String> {
+                    call_get_user(self.id, &self.query).await
+                }
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn builder_with_body_errors() {
+        let attr_tokens = quote!(post("/api/builder_with_body_errors"), builder);
+        let handler_tokens = quote! {
+            pub async fn handler(Json(user): Json<User>) -> String {
+                "hello world".into()
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens)
+                .unwrap_err()
+                .to_string(),
+            "`builder` only supports path and `Query` arguments"
+        );
+    }
+
+    #[test]
+    pub fn ws_works() {
+        let attr_tokens = quote!(ws("/api/chat/:room"));
+        let handler_tokens = quote! {
+            pub async fn chat(ws: WebSocketUpgrade, Path(room): Path<String>) -> Response {
+                ws.on_upgrade(|socket| async {})
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn chat(ws: WebSocketUpgrade, Path(room): Path<String>) -> Response {
+                ws.on_upgrade(|socket| async {})
+            }
+
+            #[must_use]
+            pub async fn connect_chat(room: &str) -> ::std::result::Result<::wrpc::ws::WebSocket, ::wrpc::ws::Error> {
+                ::wrpc::ws::connect(&::std::format!("/api/chat/{}", room)).await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    pub fn ws_with_body_errors() {
+        let attr_tokens = quote!(ws("/api/ws_with_body_errors"));
+        let handler_tokens = quote! {
+            pub async fn handler(ws: WebSocketUpgrade, Json(user): Json<User>) -> Response {
+                ws.on_upgrade(|socket| async {})
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens)
+                .unwrap_err()
+                .to_string(),
+            "`ws` handlers only support path and `Query` arguments"
+        );
+    }
+
+    #[test]
+    pub fn propagate_trace_works() {
+        let attr_tokens = quote!(
+            get("/api/propagate_trace_works"),
+            default_headers("X-Client=wrpc"),
+            propagate_trace
+        );
+        let handler_tokens = quote! {
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get("/api/propagate_trace_works")
+                    .header("X-Client", "wrpc")
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                ::wrpc::trace::headers()
+                    .into_iter()
+                    .fold(
+                        __wrpc_client.get("/api/propagate_trace_works")
+                            .header("X-Client", "wrpc"),
+                        |request, (name, value)| request.header(&name, &value)
+                    )
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[cfg(feature = "probe")]
+    #[test]
+    pub fn probe_helper_works() {
+        let attr_tokens = quote!(get("/api/probe_helper_works/:id"));
+        let handler_tokens = quote! {
+            pub async fn handler(Path(id): Path<u32>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Path(id): Path<u32>) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(id: u32) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get(&::std::format!("/api/probe_helper_works/{}", id))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(id: u32) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get(&::std::format!("/api/probe_helper_works/{}", id))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(all(test, not(target_arch = "wasm32")))]
+            pub async fn handler_probe(base: &str, id: u32) -> ::std::time::Duration {
+                let __wrpc_client = ::reqwest::Client::new();
+                let start = ::std::time::Instant::now();
+                let _ = __wrpc_client.get(::std::format!("{}{}", base, &::std::format!("/api/probe_helper_works/{}", id)))
+                    .send()
+                    .await;
+                start.elapsed()
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[cfg(feature = "inventory")]
+    #[test]
+    pub fn inventory_works() {
+        let attr_tokens = quote!(get("/api/inventory_works"));
+        let handler_tokens = quote! {
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get("/api/inventory_works")
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler() -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get("/api/inventory_works")
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            ::wrpc::inventory::submit! {
+                ::wrpc::routes::Route {
+                    method: "get",
+                    path: "/api/inventory_works",
+                    route: || ::axum::routing::get(handler),
+                }
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    pub fn mock_works() {
+        let attr_tokens = quote!(get("/api/mock_works/:id"));
+        let handler_tokens = quote! {
+            pub async fn handler(Path(id): Path<u32>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Path(id): Path<u32>) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg(not(test))]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(id: u32) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get(&::std::format!("/api/mock_works/{}", id))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[cfg(not(test))]
+            #[must_use]
+            pub async fn call_handler(id: u32) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get(&::std::format!("/api/mock_works/{}", id))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(test)]
+            #[allow(unused_variables, clippy::ptr_arg)]
+            #[must_use]
+            pub async fn call_handler(id: u32) -> ::wrpc::Result<String> {
+                ::wrpc::mock::response("handler")
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    pub fn blocking_works() {
+        let attr_tokens = quote!(post("/api/blocking_works/:id"));
+        let handler_tokens = quote! {
+            pub async fn handler(Path(id): Path<u32>, Json(payload): Json<MyType>) -> Json<MyType> {
+                Json(payload)
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Path(id): Path<u32>, Json(payload): Json<MyType>) -> Json<MyType> {
+                Json(payload)
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(id: u32, payload: &MyType) -> ::wrpc::Result<MyType> {
+                ::reqwasm::http::Request::post(&::std::format!("/api/blocking_works/{}", id))
+                    .header("Accept", "application/json")
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string(payload).unwrap())
+                    .send()
+                    .await?
+                    .json()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(id: u32, payload: &MyType) -> ::wrpc::Result<MyType> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.post(&::std::format!("/api/blocking_works/{}", id))
+                    .header("Accept", "application/json")
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string(payload).unwrap())
+                    .send()
+                    .await?
+                    .json()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub fn call_handler_blocking(id: u32, payload: &MyType) -> ::wrpc::Result<MyType> {
+                let __wrpc_client = ::reqwest::blocking::Client::new();
+                __wrpc_client.post(&::std::format!("/api/blocking_works/{}", id))
+                    .header("Accept", "application/json")
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string(payload).unwrap())
+                    .send()?
+                    .json()
+            }
+
+            const _: fn() = || {
+                fn assert_deserialize<T: ::serde::de::DeserializeOwned>() {}
+                assert_deserialize::<MyType>();
+            };
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[cfg(feature = "spawn")]
+    #[test]
+    pub fn spawn_works() {
+        let attr_tokens = quote!(post("/api/spawn_works/:id"));
+        let handler_tokens = quote! {
+            pub async fn handler(Path(id): Path<u32>, Json(payload): Json<MyType>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Path(id): Path<u32>, Json(payload): Json<MyType>) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(id: u32, payload: &MyType) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::post(&::std::format!("/api/spawn_works/{}", id))
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string(payload).unwrap())
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            pub fn spawn_call_handler(
+                id: u32,
+                payload: &'static MyType,
+                on_result: impl ::std::ops::FnOnce(::wrpc::Result<String>) + 'static
+            ) {
+                ::wrpc::wasm_bindgen_futures::spawn_local(async move {
+                    on_result(call_handler(id, payload).await);
+                });
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(id: u32, payload: &MyType) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.post(&::std::format!("/api/spawn_works/{}", id))
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string(payload).unwrap())
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[cfg(feature = "prefetch")]
+    #[test]
+    pub fn prefetch_works() {
+        let attr_tokens = quote!(get("/api/prefetch_works/:id"));
+        let handler_tokens = quote! {
+            pub async fn handler(Path(id): Path<u32>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Path(id): Path<u32>) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(id: u32) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::get(&::std::format!("/api/prefetch_works/{}", id))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[must_use]
+            pub fn prefetch_handler(id: u32) -> ::wrpc::prefetch::HtmlLinkElement {
+                ::wrpc::prefetch::link(&::std::format!("/api/prefetch_works/{}", id))
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(id: u32) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get(&::std::format!("/api/prefetch_works/{}", id))
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[cfg(feature = "debug_bodies")]
+    #[test]
+    pub fn debug_bodies_works() {
+        let attr_tokens = quote!(get("/api/debug_bodies_works"));
+        let handler_tokens = quote! {
+            pub async fn handler() -> Json<User> {
+                Json(User::default())
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler() -> Json<User> {
+                Json(User::default())
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler() -> ::std::result::Result<User, ::wrpc::Error> {
+                ::reqwasm::http::Request::get("/api/debug_bodies_works")
+                    .header("Accept", "application/json")
+                    .send()
+                    .await?
+                    .binary()
+                    .await
+                    .map_err(::wrpc::Error::Request)
+                    .and_then(|bytes| ::wrpc::response::json(&bytes))
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler() -> ::std::result::Result<User, ::wrpc::Error> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.get("/api/debug_bodies_works")
+                    .header("Accept", "application/json")
+                    .send()
+                    .await?
+                    .bytes()
+                    .await
+                    .map_err(::wrpc::Error::Request)
+                    .and_then(|bytes| ::wrpc::response::json(&bytes))
+            }
+
+            const _: fn() = || {
+                fn assert_deserialize<T: ::serde::de::DeserializeOwned>() {}
+                assert_deserialize::<User>();
+            };
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    pub fn tracing_works() {
+        let attr_tokens = quote!(get("/api/tracing_works/:id"));
+        let handler_tokens = quote! {
+            pub async fn handler(Path(id): Path<u32>) -> Json<User> {
+                Json(User::default())
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Path(id): Path<u32>) -> Json<User> {
+                Json(User::default())
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(id: u32) -> ::wrpc::Result<User> {
+                ::wrpc::instrument::call(
+                    ::wrpc::instrument::info_span!(
+                        "call_handler",
+                        method = "GET",
+                        path = "/api/tracing_works/:id",
+                        status = ::wrpc::instrument::field::Empty,
+                    ),
+                    async move {
+                        let result: ::wrpc::Result<User> = {
+                            ::reqwasm::http::Request::get(&::std::format!("/api/tracing_works/{}", id))
+                                .header("Accept", "application/json")
+                                .send()
+                                .await
+                                .map(::wrpc::instrument::status)?
+                                .json()
+                                .await
+                        };
+                        result
+                    },
+                )
+                .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(id: u32) -> ::wrpc::Result<User> {
+                ::wrpc::instrument::call(
+                    ::wrpc::instrument::info_span!(
+                        "call_handler",
+                        method = "GET",
+                        path = "/api/tracing_works/:id",
+                        status = ::wrpc::instrument::field::Empty,
+                    ),
+                    async move {
+                        let result: ::wrpc::Result<User> = {
+                            let __wrpc_client = ::reqwest::Client::new();
+                            __wrpc_client.get(&::std::format!("/api/tracing_works/{}", id))
+                                .header("Accept", "application/json")
+                                .send()
+                                .await
+                                .map(::wrpc::instrument::status)?
+                                .json()
+                                .await
+                        };
+                        result
+                    },
+                )
+                .await
+            }
+
+            const _: fn() = || {
+                fn assert_deserialize<T: ::serde::de::DeserializeOwned>() {}
+                assert_deserialize::<User>();
+            };
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[cfg(all(feature = "openapi", not(feature = "inventory")))]
+    #[test]
+    pub fn openapi_works() {
+        let attr_tokens = quote!(post("/api/openapi_works/:id"));
+        let handler_tokens = quote! {
+            pub async fn handler(Path(id): Path<u32>, Json(payload): Json<MyType>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Path(id): Path<u32>, Json(payload): Json<MyType>) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(id: u32, payload: &MyType) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::post(&::std::format!("/api/openapi_works/{}", id))
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string(payload).unwrap())
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(id: u32, payload: &MyType) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.post(&::std::format!("/api/openapi_works/{}", id))
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string(payload).unwrap())
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            ::wrpc::inventory::submit! {
+                ::wrpc::openapi::Operation {
+                    name: "handler",
+                    method: "post",
+                    path: "/api/openapi_works/:id",
+                    parameters: &[
+                        ::wrpc::openapi::Parameter {
+                            name: "id",
+                            location: ::wrpc::openapi::Location::Path,
+                            ty: "u32",
+                            required: true,
+                        },
+                        ::wrpc::openapi::Parameter {
+                            name: "payload",
+                            location: ::wrpc::openapi::Location::Body("application/json"),
+                            ty: "MyType",
+                            required: true,
+                        }
+                    ],
+                }
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[cfg(all(
+        feature = "ts-export",
+        not(feature = "inventory"),
+        not(feature = "openapi")
+    ))]
+    #[test]
+    pub fn ts_export_works() {
+        let attr_tokens = quote!(post("/api/ts_export_works/:id"));
+        let handler_tokens = quote! {
+            pub async fn handler(Path(id): Path<u32>, Json(payload): Json<MyType>) -> String {
+                "hello world".into()
+            }
+        };
+
+        let expected = quote! {
+            #[cfg(any(not(target_arch = "wasm32"), not(client)))]
+            pub async fn handler(Path(id): Path<u32>, Json(payload): Json<MyType>) -> String {
+                "hello world".into()
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[cfg_attr(docsrs, doc(hidden))]
+            #[must_use]
+            pub async fn call_handler(id: u32, payload: &MyType) -> ::wrpc::Result<String> {
+                ::reqwasm::http::Request::post(&::std::format!("/api/ts_export_works/{}", id))
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string(payload).unwrap())
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[must_use]
+            pub async fn call_handler(id: u32, payload: &MyType) -> ::wrpc::Result<String> {
+                let __wrpc_client = ::reqwest::Client::new();
+                __wrpc_client.post(&::std::format!("/api/ts_export_works/{}", id))
+                    .header("Content-Type", "application/json")
+                    .body(::serde_json::to_string(payload).unwrap())
+                    .send()
+                    .await?
+                    .text()
+                    .await
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            ::wrpc::inventory::submit! {
+                ::wrpc::ts::Function {
+                    name: "handler",
+                    method: "post",
+                    path: "/api/ts_export_works/:id",
+                    params: &[
+                        ::wrpc::ts::Param { name: "id", ty: "number", source: ::wrpc::ts::Source::Path },
+                        ::wrpc::ts::Param { name: "payload", ty: "MyType", source: ::wrpc::ts::Source::Json }
+                    ],
+                    headers: &[("Content-Type", "application/json")],
+                    credentials: ::std::option::Option::None,
+                    returns: "string",
+                    response: ::wrpc::ts::Response::Text,
+                    not_modified: false,
+                    types: &[
+                        ::wrpc::ts::Type {
+                            name: "MyType",
+                            declarations: ::wrpc::ts::declarations::<MyType>,
+                        }
+                    ],
+                }
+            }
+        };
+
+        assert_eq!(
+            rpc_impl(attr_tokens, handler_tokens).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+}
+
+pub mod cfg_matrix {
+    use quote::quote;
+    use syn::{parse_quote, punctuated::Punctuated, Expr, Item, ItemFn, Lit, Meta, Token};
+
+    use crate::rpc_impl;
+
+    /// Evaluates a `cfg` predicate for the given target/`client` combination.
+    /// `test` is always considered unset.
+    fn eval(meta: &Meta, wasm32: bool, client: bool) -> bool {
+        match meta {
+            Meta::Path(path) if path.is_ident("client") => client,
+            Meta::Path(_) => false,
+            Meta::NameValue(name_value) => match &name_value.value {
+                Expr::Lit(expr) => match &expr.lit {
+                    Lit::Str(value) if name_value.path.is_ident("target_arch") => {
+                        (value.value() == "wasm32") == wasm32
+                    }
+                    _ => false,
+                },
+                _ => false,
+            },
+            Meta::List(list) => {
+                let inner = list
+                    .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+                    .unwrap();
+                let mut inner = inner.iter().map(|meta| eval(meta, wasm32, client));
+                if list.path.is_ident("any") {
+                    inner.any(|value| value)
+                } else if list.path.is_ident("all") {
+                    inner.all(|value| value)
+                } else if list.path.is_ident("not") {
+                    !inner.next().unwrap()
+                } else {
+                    panic!("Unexpected cfg predicate")
+                }
+            }
+        }
+    }
+
+    /// Returns all functions that survive `cfg` evaluation.
+    fn active_items(file: &syn::File, wasm32: bool, client: bool) -> Vec<&ItemFn> {
+        file.items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Fn(item) => Some(item),
+                _ => None,
+            })
+            .filter(|item| {
+                item.attrs
+                    .iter()
+                    .filter(|attr| attr.path().is_ident("cfg"))
+                    .all(|attr| {
+                        let meta = attr.parse_args::<Meta>().unwrap();
+                        eval(&meta, wasm32, client)
+                    })
+            })
+            .collect()
+    }
+
+    /// Returns the names of all functions that survive `cfg` evaluation.
+    fn active_fns(file: &syn::File, wasm32: bool, client: bool) -> Vec<String> {
+        active_items(file, wasm32, client)
+            .into_iter()
+            .map(|item| item.sig.ident.to_string())
+            .collect()
+    }
+
+    #[test]
+    pub fn cfg_matrix_works() {
+        let attr_tokens = quote!(get("/api/cfg_matrix_works"));
+        let handler_tokens = quote! {
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+        };
+        let tokens = rpc_impl(attr_tokens, handler_tokens).unwrap();
+        let file: syn::File = syn::parse2(tokens).unwrap();
+
+        let expected: &[(bool, bool, &[&str])] = &[
+            (false, false, &["handler", "call_handler"]),
+            (false, true, &["handler", "call_handler"]),
+            (true, false, &["handler", "call_handler"]),
+            (true, true, &["call_handler"]),
+        ];
+        // `blocking` adds a second client function natively
+        let blocking: &[&str] = if cfg!(feature = "blocking") {
+            &["call_handler_blocking"]
+        } else {
+            &[]
+        };
+        for &(wasm32, client, fns) in expected {
+            let mut fns = fns.to_vec();
+            // `client_only` drops the handler in every configuration
+            if cfg!(feature = "client_only") {
+                fns.retain(|&name| name != "handler");
+            }
+            if !wasm32 {
+                fns.extend(blocking);
+            }
+            assert_eq!(
+                active_fns(&file, wasm32, client),
+                fns,
+                "wasm32 = {wasm32}, client = {client}"
+            );
+        }
+    }
+
+    #[test]
+    pub fn docsrs_keeps_native_client_visible() {
+        let attr_tokens = quote!(get("/api/docsrs_keeps_native_client_visible"));
+        let handler_tokens = quote! {
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+        };
+        let tokens = rpc_impl(attr_tokens, handler_tokens).unwrap();
+        let file: syn::File = syn::parse2(tokens).unwrap();
+
+        // docs.rs builds for a native target with `--cfg docsrs`
+        let hidden: syn::Attribute = parse_quote!(#[cfg_attr(docsrs, doc(hidden))]);
+        let clients: Vec<_> = active_items(&file, false, false)
+            .into_iter()
+            .filter(|item| item.sig.ident == "call_handler")
+            .collect();
+        assert_eq!(clients.len(), 1);
+        assert!(!clients[0].attrs.contains(&hidden));
+        // The WASM client is the one that gets hidden
+        let wasm_clients: Vec<_> = active_items(&file, true, false)
+            .into_iter()
+            .filter(|item| item.sig.ident == "call_handler")
+            .collect();
+        assert_eq!(wasm_clients.len(), 1);
+        assert!(wasm_clients[0].attrs.contains(&hidden));
+    }
+
+    #[test]
+    pub fn cfg_gated_handler_works() {
+        let attr_tokens = quote!(get("/api/cfg_gated_handler_works"));
+        let handler_tokens = quote! {
+            #[cfg(feature = "admin")]
+            pub async fn handler() -> String {
+                "hello world".into()
+            }
+        };
+        let tokens = rpc_impl(attr_tokens, handler_tokens).unwrap();
+        let file: syn::File = syn::parse2(tokens).unwrap();
+
+        // `feature = "admin"` is never enabled here, so nothing should be left
+        for (wasm32, client) in [(false, false), (false, true), (true, false), (true, true)] {
+            assert!(
+                active_fns(&file, wasm32, client).is_empty(),
+                "wasm32 = {wasm32}, client = {client}"
+            );
+        }
+        let admin: syn::Attribute = parse_quote!(#[cfg(feature = "admin")]);
+        for item in &file.items {
+            if let Item::Fn(item) = item {
+                assert_eq!(item.attrs.iter().filter(|&attr| *attr == admin).count(), 1);
+            }
+        }
+    }
+
+    #[test]
+    pub fn client_only_option_omits_handler() {
+        let attr_tokens = quote!(
+            get("/api/client_only_option_omits_handler/:id"),
+            client_only
+        );
+        let handler_tokens = quote! {
+            pub async fn handler(Path(id): Path<u32>, State(state): State<AppState>) -> String {
+                "hello world".into()
+            }
+        };
+        let tokens = rpc_impl(attr_tokens, handler_tokens).unwrap();
+        let file: syn::File = syn::parse2(tokens).unwrap();
+
+        for (wasm32, client) in [(false, false), (false, true), (true, false), (true, true)] {
+            assert_eq!(
+                active_fns(&file, wasm32, client)
+                    .iter()
+                    .filter(|name| *name == "call_handler")
+                    .count(),
+                1,
+                "wasm32 = {wasm32}, client = {client}"
+            );
+        }
+        assert!(!file.items.iter().any(|item| match item {
+            Item::Fn(item) => item.sig.ident == "handler",
+            _ => false,
+        }));
+    }
+
+    #[cfg(feature = "client_only")]
+    #[test]
+    pub fn client_only_omits_handler() {
+        let attr_tokens = quote!(get("/api/client_only_omits_handler/:id"));
+        let handler_tokens = quote! {
+            pub async fn handler(Path(id): Path<u32>, State(state): State<AppState>) -> String {
+                "hello world".into()
+            }
+        };
+        let tokens = rpc_impl(attr_tokens, handler_tokens).unwrap();
+        let file: syn::File = syn::parse2(tokens).unwrap();
+
+        for (wasm32, client) in [(false, false), (false, true), (true, false), (true, true)] {
+            let fns = active_fns(&file, wasm32, client);
+            assert!(
+                !fns.iter().any(|name| name == "handler"),
+                "wasm32 = {wasm32}, client = {client}"
+            );
+            assert!(fns.iter().any(|name| name == "call_handler"));
+        }
+        // The handler isn't even behind a `cfg`, it's gone entirely
+        assert!(!file.items.iter().any(|item| match item {
+            Item::Fn(item) => item.sig.ident == "handler",
+            _ => false,
+        }));
+    }
 }
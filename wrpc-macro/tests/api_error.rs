@@ -0,0 +1,68 @@
+//! Round trip test for `error(ErrType)`, against a handler failing with a
+//! structured error body for unknown users.
+
+mod common;
+
+use axum::{extract::Path, http::StatusCode, response::IntoResponse, routing::get, Json, Router};
+use common::serve;
+use serde::{Deserialize, Serialize};
+use wrpc_macro::rpc;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct User {
+    id: u32,
+    name: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct ApiError {
+    code: u32,
+    message: String,
+}
+
+// Has to match the port in the `#[rpc]` URLs below
+const PORT: u16 = 38310;
+
+#[rpc(
+    get("http://127.0.0.1:38310/api/user/:id"),
+    returns(User),
+    error(ApiError)
+)]
+pub async fn get_user(Path(id): Path<u32>) -> impl IntoResponse {
+    if id == 1 {
+        Ok(Json(User {
+            id,
+            name: "hello".to_string(),
+        }))
+    } else {
+        let error = ApiError {
+            code: 404,
+            message: format!("no user {id}"),
+        };
+        Err((StatusCode::NOT_FOUND, Json(error)))
+    }
+}
+
+#[tokio::test]
+async fn api_error_works() {
+    let router = Router::new().route("/api/user/:id", get(get_user));
+    serve(PORT, router);
+
+    assert_eq!(
+        call_get_user(1).await.unwrap(),
+        User {
+            id: 1,
+            name: "hello".to_string()
+        }
+    );
+    match call_get_user(2).await {
+        Err(wrpc::Error::Api(error)) => assert_eq!(
+            error,
+            ApiError {
+                code: 404,
+                message: "no user 2".to_string()
+            }
+        ),
+        result => panic!("expected an API error, got {result:?}"),
+    }
+}
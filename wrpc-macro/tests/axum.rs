@@ -1,4 +1,16 @@
-use axum::{extract::Path, routing::get, Json, Router};
+#[cfg(not(target_arch = "wasm32"))]
+mod common;
+
+use std::{collections::HashMap, sync::Mutex};
+
+use axum::{
+    extract::{Path, Query},
+    headers::{authorization::Bearer, Authorization},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    routing::{delete, get, post},
+    Json, Router, TypedHeader,
+};
 use serde::{Deserialize, Serialize};
 use wrpc_macro::rpc;
 
@@ -18,8 +30,201 @@ pub async fn handler(Path((team, id)): Path<(String, u32)>) -> Json<User> {
     })
 }
 
-#[allow(unused)]
+#[rpc(get("/api/whoami"))]
+pub async fn whoami(TypedHeader(auth): TypedHeader<Authorization<Bearer>>) -> String {
+    auth.token().to_string()
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Pagination {
+    page: u32,
+}
+
+#[rpc(post("/api/user/:id/rename"), named_lifetimes)]
+pub async fn rename(
+    Path(id): Path<u32>,
+    Query(pagination): Query<Pagination>,
+    headers: HeaderMap,
+    Json(name): Json<String>,
+) -> Json<User> {
+    Json(User {
+        id,
+        team: format!("{} {}", pagination.page, headers.len()),
+        name,
+    })
+}
+
+#[rpc(get("/api/user/:id/binary"), returns_bincode(User))]
+pub async fn binary_user(Path(id): Path<u32>) -> Vec<u8> {
+    bincode::serialize(&User {
+        id,
+        team: "binary".to_string(),
+        name: "hello".to_string(),
+    })
+    .unwrap()
+}
+
+#[rpc(get("/api/user/:id/versioned"), returns(User), expect_version("2"))]
+pub async fn versioned_user(Path(id): Path<u32>) -> impl IntoResponse {
+    let user = User {
+        id,
+        team: "versioned".to_string(),
+        name: "hello".to_string(),
+    };
+    ([("X-Api-Version", "2")], Json(user))
+}
+
+#[rpc(get("/api/user/:id/with_headers"), returns_headers)]
+pub async fn user_with_headers(Path(id): Path<u32>) -> (HeaderMap, Json<User>) {
+    let user = User {
+        id,
+        team: "headers".to_string(),
+        name: "hello".to_string(),
+    };
+    (HeaderMap::new(), Json(user))
+}
+
+#[rpc(get("/api/object/:id"))]
+pub async fn object(#[path_fmt("{:016x}")] Path(id): Path<u64>) -> String {
+    format!("{id:016x}")
+}
+
+#[rpc(get("/api/user/:id/raw"), raw_bytes_return)]
+pub async fn raw_user(Path(id): Path<u32>) -> Json<User> {
+    Json(User {
+        id,
+        team: "raw".to_string(),
+        name: "hello".to_string(),
+    })
+}
+
+#[rpc(get("/api/user/:id/count"), returns(text(u32)))]
+pub async fn user_count(Path(id): Path<u32>) -> String {
+    (id * 10).to_string()
+}
+
+#[rpc(get("/api/client/:client"))]
+pub async fn client_name(Path(client): Path<String>) -> String {
+    client
+}
+
+#[rpc(get("/api/team/:team/member/:id"))]
+pub async fn team_member(Path(params): Path<HashMap<String, String>>) -> String {
+    format!("{} {}", params["team"], params["id"])
+}
+
+// Has to match the port in `SERVER`
+#[cfg(not(target_arch = "wasm32"))]
+const PORT: u16 = 38328;
+const SERVER: &str = "http://127.0.0.1:38328";
+
+static DELETED: Mutex<Vec<u32>> = Mutex::new(Vec::new());
+
+#[rpc(delete("/api/items"), prefix(SERVER))]
+pub async fn bulk_delete(Json(ids): Json<Vec<u32>>) -> StatusCode {
+    DELETED.lock().unwrap().extend(ids);
+    StatusCode::NO_CONTENT
+}
+
+#[rpc(delete("/api/user/:id"), prefix(SERVER))]
+pub async fn delete_user(Path(id): Path<u32>) -> Json<User> {
+    DELETED.lock().unwrap().push(id);
+    Json(User {
+        id,
+        team: "deleted".to_string(),
+        name: "hello".to_string(),
+    })
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 pub fn router() -> Router {
-    Router::new().route("/api/handler/:team/:id", get(handler))
+    Router::new()
+        .route("/api/handler/:team/:id", get(handler))
+        .route("/api/whoami", get(whoami))
+        .route("/api/user/:id/rename", post(rename))
+        .route("/api/user/:id/binary", get(binary_user))
+        .route("/api/user/:id/versioned", get(versioned_user))
+        .route("/api/user/:id/with_headers", get(user_with_headers))
+        .route("/api/object/:id", get(object))
+        .route("/api/user/:id/raw", get(raw_user))
+        .route("/api/user/:id/count", get(user_count))
+        .route("/api/client/:client", get(client_name))
+        .route("/api/team/:team/member/:id", get(team_member))
+        .route("/api/items", delete(bulk_delete))
+        .route("/api/user/:id", delete(delete_user))
+}
+
+/// Client futures have to be `Send` to be spawned on multi-threaded runtimes.
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn client_futures_are_send() {
+    fn assert_send(_: impl Send) {}
+
+    let pagination = Pagination { page: 1 };
+    let name = "hello".to_string();
+    let params = HashMap::from([("team".to_string(), "a".to_string())]);
+    assert_send(call_handler("team", 1));
+    assert_send(call_rename(1, &pagination, &name, &[("X-Test", "1")]));
+    assert_send(call_binary_user(1));
+    assert_send(call_versioned_user(1));
+    assert_send(call_user_with_headers(1));
+    assert_send(call_object(1));
+    assert_send(call_raw_user(1));
+    assert_send(call_user_count(1));
+    assert_send(call_delete_user(1));
+    assert_send(call_team_member(&params));
+}
+
+/// `delete` handlers taking a `Json` body and returning a bare status code, and
+/// taking a path segment and returning the deleted resource.
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::test]
+async fn delete_works() {
+    common::serve(PORT, router());
+
+    let _: fn(&'static [u32]) -> _ = call_bulk_delete;
+    let result: wrpc::Result<()> = call_bulk_delete(&[1, 2, 3]).await;
+    result.unwrap();
+    assert_eq!(*DELETED.lock().unwrap(), [1, 2, 3]);
+
+    let user: wrpc::Result<User> = call_delete_user(4).await;
+    let user = user.unwrap();
+    assert_eq!((user.id, user.team.as_str()), (4, "deleted"));
+    assert_eq!(*DELETED.lock().unwrap(), [1, 2, 3, 4]);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::test]
+#[should_panic(expected = "`params` has no entry for the path segment `:id`")]
+async fn missing_path_segment_panics() {
+    let params = HashMap::from([("team".to_string(), "wrpc".to_string())]);
+    let _ = call_team_member(&params).await;
+}
+
+#[cfg(feature = "inventory")]
+#[test]
+fn inventory_collects_routes() {
+    let mut routes = wrpc::routes::iter()
+        .map(|route| (route.method, route.path))
+        .collect::<Vec<_>>();
+    routes.sort();
+    assert_eq!(
+        routes,
+        [
+            ("delete", "/api/items"),
+            ("delete", "/api/user/:id"),
+            ("get", "/api/client/:client"),
+            ("get", "/api/handler/:team/:id"),
+            ("get", "/api/object/:id"),
+            ("get", "/api/team/:team/member/:id"),
+            ("get", "/api/user/:id/binary"),
+            ("get", "/api/user/:id/count"),
+            ("get", "/api/user/:id/raw"),
+            ("get", "/api/user/:id/versioned"),
+            ("get", "/api/user/:id/with_headers"),
+            ("get", "/api/whoami"),
+            ("post", "/api/user/:id/rename"),
+        ]
+    );
+    let _ = wrpc::routes::router();
 }
@@ -0,0 +1,61 @@
+//! Round trip test for the `reqwest::blocking` client functions of the
+//! `blocking` feature.
+#![cfg(feature = "blocking")]
+
+mod common;
+
+use axum::{
+    extract::{Path, Query},
+    routing::post,
+    Json, Router,
+};
+use common::serve;
+use serde::{Deserialize, Serialize};
+use wrpc_macro::rpc;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct User {
+    id: u32,
+    name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Pagination {
+    page: u32,
+}
+
+// Has to match the port in the `#[rpc]` URLs below
+const PORT: u16 = 38317;
+
+#[rpc(post("http://127.0.0.1:38317/api/user/:id"))]
+pub async fn rename(
+    Path(id): Path<u32>,
+    Query(query): Query<Pagination>,
+    Json(name): Json<String>,
+) -> Json<User> {
+    Json(User {
+        id,
+        name: format!("{name} {}", query.page),
+    })
+}
+
+#[tokio::test]
+async fn blocking_works() {
+    let router = Router::new().route("/api/user/:id", post(rename));
+    serve(PORT, router);
+
+    // The blocking client runs its own runtime, which can't live on an async one
+    let user = tokio::task::spawn_blocking(|| {
+        call_rename_blocking(1, &Pagination { page: 2 }, &"hello".to_string())
+    })
+    .await
+    .unwrap()
+    .unwrap();
+    assert_eq!(
+        user,
+        User {
+            id: 1,
+            name: "hello 2".to_string()
+        }
+    );
+}
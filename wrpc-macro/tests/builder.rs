@@ -0,0 +1,57 @@
+//! Round trip test for `builder`, taking the path arguments in `new` and
+//! setting the optional query afterwards.
+
+mod common;
+
+use axum::{
+    extract::{Path, Query},
+    routing::get,
+    Router,
+};
+use common::serve;
+use serde::{Deserialize, Serialize};
+use wrpc_macro::rpc;
+
+#[derive(Serialize, Deserialize)]
+pub struct Pagination {
+    page: u32,
+    per_page: u32,
+}
+
+// Has to match the port in the `#[rpc]` URLs below
+const PORT: u16 = 38323;
+
+#[rpc(get("http://127.0.0.1:38323/api/org/:org/user/:id"), builder)]
+pub async fn get_user(
+    Path((org, id)): Path<(String, u32)>,
+    pagination: Option<Query<Pagination>>,
+) -> String {
+    match pagination {
+        Some(Query(pagination)) => {
+            format!("{org} {id} {} {}", pagination.page, pagination.per_page)
+        }
+        None => format!("{org} {id}"),
+    }
+}
+
+#[tokio::test]
+async fn builder_works() {
+    let router = Router::new().route("/api/org/:org/user/:id", get(get_user));
+    serve(PORT, router);
+
+    let response = GetUserRequest::new("wrpc".to_string(), 1)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response, "wrpc 1");
+
+    let response = GetUserRequest::new("wrpc".to_string(), 1)
+        .pagination(Pagination {
+            page: 2,
+            per_page: 10,
+        })
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response, "wrpc 1 2 10");
+}
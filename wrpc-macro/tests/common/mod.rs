@@ -0,0 +1,17 @@
+//! Helpers shared by the round trip tests.
+
+use std::net::{SocketAddr, TcpListener};
+
+use axum::Router;
+
+/// Serves `router` on a fixed local port in the background. The client
+/// functions need absolute URLs on native targets, so `port` has to match the
+/// one in the handlers' `#[rpc]` URLs.
+pub fn serve(port: u16, router: Router) {
+    let listener = TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], port))).unwrap();
+    tokio::spawn(
+        axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(router.into_make_service()),
+    );
+}
@@ -0,0 +1,7 @@
+//! Compile errors of handlers the macro rejects, with their spans.
+
+#[test]
+fn compile_fail() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
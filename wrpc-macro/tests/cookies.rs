@@ -0,0 +1,57 @@
+//! Round trip test for `cookies`, against a handler echoing the `Cookie`
+//! header it received and one setting a cookie.
+
+mod common;
+
+use axum::{
+    http::{header, HeaderMap, HeaderValue},
+    routing::{get, post},
+    Router,
+};
+use common::serve;
+use wrpc_macro::rpc;
+
+// Has to match the port in the `#[rpc]` URLs below
+const PORT: u16 = 38307;
+
+#[rpc(
+    get("http://127.0.0.1:38307/api/session"),
+    cookies,
+    allow_unknown_extractors
+)]
+pub async fn session(headers: HeaderMap) -> String {
+    headers
+        .get(header::COOKIE)
+        .and_then(|cookie| cookie.to_str().ok())
+        .unwrap_or("none")
+        .to_string()
+}
+
+#[rpc(post("http://127.0.0.1:38307/api/login"), cookies)]
+pub async fn login() -> (HeaderMap, String) {
+    let mut headers = HeaderMap::new();
+    headers.insert(header::SET_COOKIE, HeaderValue::from_static("token=xyz"));
+    (headers, "ok".to_string())
+}
+
+#[tokio::test]
+async fn cookies_works() {
+    let router = Router::new()
+        .route("/api/session", get(session))
+        .route("/api/login", post(login));
+    serve(PORT, router);
+
+    assert_eq!(
+        call_session(&[], Some("session=abc")).await.unwrap(),
+        "session=abc"
+    );
+    assert_eq!(call_session(&[], None).await.unwrap(), "none");
+
+    // The cookie set by one handler is sent to the other, unless overridden
+    assert_eq!(call_login(None).await.unwrap(), "ok");
+    assert_eq!(call_session(&[], None).await.unwrap(), "token=xyz");
+    assert_eq!(
+        call_session(&[], Some("session=abc")).await.unwrap(),
+        "session=abc"
+    );
+}
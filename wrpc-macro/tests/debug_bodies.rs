@@ -0,0 +1,43 @@
+//! Round trip test for the `debug_bodies` feature, keeping the start of a
+//! malformed JSON body in the decode error.
+#![cfg(feature = "debug_bodies")]
+
+mod common;
+
+use axum::{http::header, response::IntoResponse, routing::get, Router};
+use common::serve;
+use serde::{Deserialize, Serialize};
+use wrpc_macro::rpc;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct User {
+    id: u32,
+    name: String,
+}
+
+// Has to match the port in the `#[rpc]` URLs below
+const PORT: u16 = 38330;
+
+#[rpc(get("http://127.0.0.1:38330/api/user"), returns(User))]
+pub async fn get_user() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "application/json")],
+        r#"{"id": 1, "name": nul"#,
+    )
+}
+
+#[tokio::test]
+async fn debug_bodies_keeps_body() {
+    let router = Router::new().route("/api/user", get(get_user));
+    serve(PORT, router);
+
+    let err = call_get_user().await.unwrap_err();
+    let wrpc::Error::Decode(source) = &err else {
+        panic!("expected a decode error, got {err}");
+    };
+    let source = source.downcast_ref::<wrpc::BodyError>().unwrap();
+    assert_eq!(source.body, r#"{"id": 1, "name": nul"#);
+    assert!(err
+        .to_string()
+        .ends_with(r#"in body `{"id": 1, "name": nul`"#));
+}
@@ -0,0 +1,67 @@
+//! Round trip test for `etag`, against a handler answering `304 Not Modified`
+//! when the client's copy is current.
+
+mod common;
+
+use axum::{
+    extract::FromRequestParts,
+    http::{header, request::Parts, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use common::serve;
+use serde::{Deserialize, Serialize};
+use wrpc_macro::rpc;
+
+const ETAG: &str = "\"v2\"";
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Config {
+    version: u32,
+}
+
+/// The `If-None-Match` header of the request, if any.
+pub struct IfNoneMatch(Option<String>);
+
+#[axum::async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for IfNoneMatch {
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, StatusCode> {
+        let etag = parts
+            .headers
+            .get(header::IF_NONE_MATCH)
+            .and_then(|value| value.to_str().ok())
+            .map(ToString::to_string);
+        Ok(IfNoneMatch(etag))
+    }
+}
+
+// Has to match the port in the `#[rpc]` URLs below
+const PORT: u16 = 38322;
+
+#[rpc(
+    get("http://127.0.0.1:38322/api/config"),
+    returns(Config),
+    etag,
+    allow_unknown_extractors
+)]
+pub async fn config(IfNoneMatch(etag): IfNoneMatch) -> Response {
+    if etag.as_deref() == Some(ETAG) {
+        return StatusCode::NOT_MODIFIED.into_response();
+    }
+    ([(header::ETAG, ETAG)], Json(Config { version: 2 })).into_response()
+}
+
+#[tokio::test]
+async fn etag_works() {
+    let router = Router::new().route("/api/config", get(config));
+    serve(PORT, router);
+
+    let current = Some(Config { version: 2 });
+    assert_eq!(call_config(None).await.unwrap(), current);
+    assert_eq!(call_config(Some("\"v1\"")).await.unwrap(), current);
+    // The client's copy is current, so there's nothing to decode
+    assert_eq!(call_config(Some(ETAG)).await.unwrap(), None);
+}
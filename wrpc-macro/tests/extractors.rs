@@ -0,0 +1,38 @@
+//! Handlers importing their extractors under other names, registered with
+//! `extractors(...)`.
+
+use axum::extract::{Path as AxPath, Query as AxQuery};
+use axum::Json as AxJson;
+use serde::{Deserialize, Serialize};
+use wrpc_macro::rpc;
+
+#[derive(Serialize, Deserialize)]
+pub struct Filter {
+    name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct User {
+    id: u32,
+    name: String,
+}
+
+#[rpc(
+    post("/api/team/:team/users"),
+    extractors(json = AxJson, query = AxQuery, path = AxPath)
+)]
+pub async fn create_user(
+    AxPath(team): AxPath<u32>,
+    AxQuery(filter): AxQuery<Filter>,
+    AxJson(user): AxJson<User>,
+) -> AxJson<User> {
+    AxJson(User {
+        id: team,
+        name: format!("{} {}", filter.name, user.name),
+    })
+}
+
+#[test]
+fn aliased_extractors_become_arguments() {
+    let _: fn(u32, &'static Filter, &'static User) -> _ = call_create_user;
+}
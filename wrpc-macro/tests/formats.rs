@@ -0,0 +1,226 @@
+//! Round trip tests for the non-JSON body formats and the `Accept` header
+//! following them. The client functions need absolute URLs on native targets,
+//! so each test serves its router on a fixed local port.
+
+mod common;
+
+use axum::{
+    async_trait,
+    body::{Bytes, HttpBody},
+    extract::{FromRequest, FromRequestParts},
+    http::{header, request::Parts, Request, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    BoxError, Json, Router,
+};
+use common::serve;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use wrpc_macro::rpc;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct User {
+    id: u32,
+    name: String,
+}
+
+/// Minimal CBOR extractor/response, mirroring `axum::Json`.
+pub struct Cbor<T>(pub T);
+
+#[async_trait]
+impl<T, S, B> FromRequest<S, B> for Cbor<T>
+where
+    T: DeserializeOwned,
+    B: HttpBody + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+    S: Send + Sync,
+{
+    type Rejection = StatusCode;
+
+    async fn from_request(req: Request<B>, state: &S) -> Result<Self, Self::Rejection> {
+        let bytes = Bytes::from_request(req, state)
+            .await
+            .map_err(|_| StatusCode::BAD_REQUEST)?;
+        ciborium::de::from_reader(&bytes[..])
+            .map(Cbor)
+            .map_err(|_| StatusCode::UNPROCESSABLE_ENTITY)
+    }
+}
+
+impl<T: Serialize> IntoResponse for Cbor<T> {
+    fn into_response(self) -> Response {
+        let mut bytes = Vec::new();
+        ciborium::ser::into_writer(&self.0, &mut bytes).unwrap();
+        ([(header::CONTENT_TYPE, "application/cbor")], bytes).into_response()
+    }
+}
+
+/// Minimal MessagePack extractor/response, mirroring `axum::Json`.
+pub struct MsgPack<T>(pub T);
+
+#[async_trait]
+impl<T, S, B> FromRequest<S, B> for MsgPack<T>
+where
+    T: DeserializeOwned,
+    B: HttpBody + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+    S: Send + Sync,
+{
+    type Rejection = StatusCode;
+
+    async fn from_request(req: Request<B>, state: &S) -> Result<Self, Self::Rejection> {
+        let bytes = Bytes::from_request(req, state)
+            .await
+            .map_err(|_| StatusCode::BAD_REQUEST)?;
+        rmp_serde::from_slice(&bytes)
+            .map(MsgPack)
+            .map_err(|_| StatusCode::UNPROCESSABLE_ENTITY)
+    }
+}
+
+impl<T: Serialize> IntoResponse for MsgPack<T> {
+    fn into_response(self) -> Response {
+        let bytes = rmp_serde::to_vec(&self.0).unwrap();
+        ([(header::CONTENT_TYPE, "application/msgpack")], bytes).into_response()
+    }
+}
+
+// Has to match the port in the `#[rpc]` URL below
+const CBOR_PORT: u16 = 38301;
+
+#[rpc(post("http://127.0.0.1:38301/api/cbor"), format(cbor))]
+pub async fn cbor_echo(Cbor(user): Cbor<User>) -> Cbor<User> {
+    Cbor(User {
+        id: user.id + 1,
+        name: user.name,
+    })
+}
+
+#[tokio::test]
+async fn cbor_round_trip_works() {
+    serve(CBOR_PORT, Router::new().route("/api/cbor", post(cbor_echo)));
+
+    let user = User {
+        id: 1,
+        name: "hello".to_string(),
+    };
+    let response = call_cbor_echo(&user).await.unwrap();
+    assert_eq!(
+        response,
+        User {
+            id: 2,
+            name: "hello".to_string()
+        }
+    );
+}
+
+// Has to match the port in the `#[rpc]` URL below
+const MSGPACK_PORT: u16 = 38302;
+
+#[rpc(post("http://127.0.0.1:38302/api/msgpack"), format(msgpack))]
+pub async fn msgpack_echo(MsgPack(user): MsgPack<User>) -> MsgPack<User> {
+    MsgPack(User {
+        id: user.id + 1,
+        name: user.name,
+    })
+}
+
+#[tokio::test]
+async fn msgpack_round_trip_works() {
+    serve(
+        MSGPACK_PORT,
+        Router::new().route("/api/msgpack", post(msgpack_echo)),
+    );
+
+    let user = User {
+        id: 1,
+        name: "hello".to_string(),
+    };
+    let response = call_msgpack_echo(&user).await.unwrap();
+    assert_eq!(
+        response,
+        User {
+            id: 2,
+            name: "hello".to_string()
+        }
+    );
+}
+
+/// The request's `Accept` header, for handlers negotiating their response
+/// format.
+pub struct Accept(pub String);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Accept {
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        let accept = parts
+            .headers
+            .get(header::ACCEPT)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        Ok(Accept(accept.to_string()))
+    }
+}
+
+fn negotiate(accept: &str) -> Response {
+    let user = User {
+        id: 1,
+        name: accept.to_string(),
+    };
+    match accept {
+        "application/msgpack" => MsgPack(user).into_response(),
+        "application/cbor" => Cbor(user).into_response(),
+        _ => Json(user).into_response(),
+    }
+}
+
+// Has to match the port in the `#[rpc]` URL below
+const NEGOTIATE_PORT: u16 = 38326;
+
+// Both clients call the same negotiating route
+#[rpc(
+    get("http://127.0.0.1:38326/api/negotiate"),
+    returns(User),
+    allow_unknown_extractors
+)]
+pub async fn negotiate_json(Accept(accept): Accept) -> Response {
+    negotiate(&accept)
+}
+
+#[rpc(
+    get("http://127.0.0.1:38326/api/negotiate"),
+    returns(User),
+    format(msgpack),
+    allow_unknown_extractors
+)]
+pub async fn negotiate_msgpack(Accept(accept): Accept) -> Response {
+    negotiate(&accept)
+}
+
+#[rpc(
+    get("http://127.0.0.1:38326/api/negotiate"),
+    returns(User),
+    format(cbor),
+    allow_unknown_extractors
+)]
+pub async fn negotiate_cbor(Accept(accept): Accept) -> Response {
+    negotiate(&accept)
+}
+
+#[tokio::test]
+async fn accept_follows_format() {
+    serve(
+        NEGOTIATE_PORT,
+        Router::new().route("/api/negotiate", get(negotiate_json)),
+    );
+
+    let json = call_negotiate_json().await.unwrap();
+    assert_eq!(json.name, "application/json");
+    let msgpack = call_negotiate_msgpack().await.unwrap();
+    assert_eq!(msgpack.name, "application/msgpack");
+    let cbor = call_negotiate_cbor().await.unwrap();
+    assert_eq!(cbor.name, "application/cbor");
+}
@@ -0,0 +1,44 @@
+//! Round trip test for `graphql`, against a handler echoing the request
+//! envelope back as the `data` field.
+
+mod common;
+
+use axum::{routing::post, Json, Router};
+use common::serve;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use wrpc_macro::rpc;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Variables {
+    id: u32,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct Echo {
+    query: String,
+    variables: Variables,
+}
+
+// Has to match the port in the `#[rpc]` URLs below
+const PORT: u16 = 38311;
+
+#[rpc(post("http://127.0.0.1:38311/graphql"), graphql, returns(Echo))]
+pub async fn graphql(Json(request): Json<Value>) -> Json<Value> {
+    Json(json!({ "data": request }))
+}
+
+#[tokio::test]
+async fn graphql_works() {
+    let router = Router::new().route("/graphql", post(graphql));
+    serve(PORT, router);
+
+    let query = "query User($id: Int!) { user(id: $id) { name } }";
+    assert_eq!(
+        call_graphql(query, &Variables { id: 1 }).await.unwrap(),
+        Echo {
+            query: query.to_string(),
+            variables: Variables { id: 1 },
+        }
+    );
+}
@@ -0,0 +1,30 @@
+//! Round trip test for `header_map`, against a handler echoing a header it
+//! received.
+
+mod common;
+
+use axum::{http::HeaderMap, routing::get, Router};
+use common::serve;
+use wrpc_macro::rpc;
+
+// Has to match the port in the `#[rpc]` URLs below
+const PORT: u16 = 38308;
+
+#[rpc(get("http://127.0.0.1:38308/api/trace"), header_map)]
+pub async fn trace(headers: HeaderMap) -> String {
+    headers
+        .get("X-Trace-Id")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("none")
+        .to_string()
+}
+
+#[tokio::test]
+async fn header_map_works() {
+    let router = Router::new().route("/api/trace", get(trace));
+    serve(PORT, router);
+
+    let mut headers = wrpc::ClientHeaders::new();
+    headers.insert("X-Trace-Id", "abc".parse().unwrap());
+    assert_eq!(call_trace(&[], &headers).await.unwrap(), "abc");
+}
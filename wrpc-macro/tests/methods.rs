@@ -0,0 +1,24 @@
+//! Round trip test for methods without a shorthand on the HTTP clients, which
+//! are sent through their generic request constructor.
+
+mod common;
+
+use axum::{extract::Path, routing::trace, Router};
+use common::serve;
+use wrpc_macro::rpc;
+
+// Has to match the port in the `#[rpc]` URLs below
+const PORT: u16 = 38333;
+
+#[rpc(trace("http://127.0.0.1:38333/api/echo/:id"))]
+pub async fn echo(Path(id): Path<u32>) -> String {
+    format!("trace {id}")
+}
+
+#[tokio::test]
+async fn trace_works() {
+    let router = Router::new().route("/api/echo/:id", trace(echo));
+    serve(PORT, router);
+
+    assert_eq!(call_echo(1).await.unwrap(), "trace 1");
+}
@@ -0,0 +1,68 @@
+//! Tests for the `mock` feature. Client functions return the canned responses
+//! registered with `wrpc::mock` instead of sending requests, so no server is
+//! running here.
+
+use axum::{extract::Path, response::IntoResponse, Json};
+use serde::{Deserialize, Serialize};
+use wrpc_macro::rpc;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct User {
+    id: u32,
+    name: String,
+}
+
+#[rpc(get("/api/user/:id"))]
+pub async fn get_user(Path(id): Path<u32>) -> Json<User> {
+    Json(User {
+        id,
+        name: "hello".to_string(),
+    })
+}
+
+#[rpc(get("/api/user/:id/versioned"), returns(User), expect_version("2"))]
+pub async fn versioned_user(Path(id): Path<u32>) -> impl IntoResponse {
+    let user = User {
+        id,
+        name: "hello".to_string(),
+    };
+    ([("X-Api-Version", "2")], Json(user))
+}
+
+#[cfg(feature = "mock")]
+#[tokio::test]
+async fn mock_response_works() {
+    let user = User {
+        id: 1,
+        name: "mocked".to_string(),
+    };
+    wrpc::mock::set_response("get_user", user);
+    assert_eq!(
+        call_get_user(1).await.unwrap(),
+        User {
+            id: 1,
+            name: "mocked".to_string()
+        }
+    );
+}
+
+#[cfg(feature = "mock")]
+#[tokio::test]
+async fn mock_error_works() {
+    let error: wrpc::Error = wrpc::Error::Version {
+        expected: "2".to_string(),
+        found: None,
+    };
+    wrpc::mock::set_error("versioned_user", error);
+    assert!(matches!(
+        call_versioned_user(1).await,
+        Err(wrpc::Error::Version { found: None, .. })
+    ));
+}
+
+#[cfg(feature = "mock")]
+#[tokio::test]
+#[should_panic(expected = "No mock response registered for `get_user`")]
+async fn missing_mock_response_panics() {
+    let _ = call_get_user(1).await;
+}
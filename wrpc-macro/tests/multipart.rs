@@ -0,0 +1,34 @@
+//! Round trip test for `Multipart` handlers. Like the format tests, the
+//! router is served on a fixed local port for the native client.
+
+mod common;
+
+use axum::{extract::Multipart, routing::post, Router};
+use common::serve;
+use wrpc_macro::rpc;
+
+// Has to match the port in the `#[rpc]` URLs below
+const PORT: u16 = 38303;
+
+#[rpc(post("http://127.0.0.1:38303/api/upload"))]
+pub async fn upload(mut multipart: Multipart) -> String {
+    let mut fields = Vec::new();
+    while let Some(field) = multipart.next_field().await.unwrap() {
+        let name = field.name().unwrap().to_string();
+        let contents = field.bytes().await.unwrap();
+        fields.push(format!("{name}={}", contents.len()));
+    }
+    fields.join(",")
+}
+
+#[tokio::test]
+async fn multipart_round_trip_works() {
+    let router = Router::new().route("/api/upload", post(upload));
+    serve(PORT, router);
+
+    let parts = vec![
+        ("avatar".to_string(), vec![0; 16]),
+        ("bio".to_string(), b"hello".to_vec()),
+    ];
+    assert_eq!(call_upload(parts).await.unwrap(), "avatar=16,bio=5");
+}
@@ -0,0 +1,55 @@
+//! Round trip test for `returns(ndjson(T))`, decoding lines that are split
+//! across chunks.
+
+mod common;
+
+use std::{convert::Infallible, future::poll_fn, pin::pin};
+
+use axum::{body::StreamBody, response::IntoResponse, routing::get, Router};
+use common::serve;
+use serde::{Deserialize, Serialize};
+use wrpc::Stream;
+use wrpc_macro::rpc;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct User {
+    id: u32,
+    name: String,
+}
+
+// Has to match the port in the `#[rpc]` URLs below
+const PORT: u16 = 38332;
+
+#[rpc(get("http://127.0.0.1:38332/api/users"), returns(ndjson(User)))]
+pub async fn list_users() -> impl IntoResponse {
+    let chunks = [
+        "{\"id\": 1, \"name\": \"a\"}\n{\"id\": 2,",
+        " \"name\": \"b\"}\n\nnot json\n",
+        "{\"id\": 3, \"name\": \"c\"}",
+    ];
+    StreamBody::new(futures_util::stream::iter(chunks.map(Ok::<_, Infallible>)))
+}
+
+#[tokio::test]
+async fn ndjson_works() {
+    let router = Router::new().route("/api/users", get(list_users));
+    serve(PORT, router);
+
+    let mut stream = pin!(call_list_users().await.unwrap());
+    let mut users = Vec::new();
+    let mut errors = 0;
+    while let Some(user) = poll_fn(|cx| stream.as_mut().poll_next(cx)).await {
+        match user {
+            Ok(user) => users.push(user),
+            Err(wrpc::Error::Decode(_)) => errors += 1,
+            Err(err) => panic!("unexpected error {err}"),
+        }
+    }
+    let user = |id, name: &str| User {
+        id,
+        name: name.to_string(),
+    };
+    assert_eq!(users, [user(1, "a"), user(2, "b"), user(3, "c")]);
+    // The malformed line doesn't end the stream
+    assert_eq!(errors, 1);
+}
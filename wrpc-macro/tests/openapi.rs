@@ -0,0 +1,92 @@
+use axum::{
+    extract::{Path, Query},
+    headers::{authorization::Bearer, Authorization},
+    Json, TypedHeader,
+};
+use serde::{Deserialize, Serialize};
+use wrpc_macro::rpc;
+
+#[derive(Serialize, Deserialize)]
+pub struct User {
+    id: u32,
+    name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Pagination {
+    page: u32,
+}
+
+#[rpc(get("/api/users/:id"))]
+pub async fn get_user(Path(id): Path<u32>, Query(pagination): Query<Pagination>) -> Json<User> {
+    Json(User {
+        id,
+        name: pagination.page.to_string(),
+    })
+}
+
+#[rpc(post("/api/users"))]
+pub async fn create_user(Json(user): Json<User>) -> Json<User> {
+    Json(user)
+}
+
+#[rpc(get("/api/me"))]
+pub async fn get_me(TypedHeader(auth): TypedHeader<Authorization<Bearer>>) -> String {
+    auth.token().to_string()
+}
+
+#[cfg(feature = "openapi")]
+#[test]
+fn openapi_collects_operations() {
+    assert_eq!(
+        wrpc::openapi::collect(),
+        serde_json::json!({
+            "/api/users/{id}": {
+                "get": {
+                    "operationId": "get_user",
+                    "parameters": [
+                        {
+                            "name": "id",
+                            "in": "path",
+                            "required": true,
+                            "schema": { "x-rust-type": "u32" },
+                        },
+                        {
+                            "name": "pagination",
+                            "in": "query",
+                            "required": true,
+                            "schema": { "x-rust-type": "Pagination" },
+                        },
+                    ],
+                },
+            },
+            "/api/me": {
+                "get": {
+                    "operationId": "get_me",
+                    "parameters": [
+                        {
+                            "name": "authorization",
+                            "in": "header",
+                            "required": true,
+                            "schema": { "x-rust-type": "Authorization < Bearer >" },
+                        },
+                    ],
+                },
+            },
+            "/api/users": {
+                "post": {
+                    "operationId": "create_user",
+                    "parameters": [],
+                    "requestBody": {
+                        "required": true,
+                        "content": {
+                            "application/json": {
+                                "schema": { "x-rust-type": "User" },
+                            },
+                        },
+                    },
+                },
+            },
+        })
+    );
+}
@@ -0,0 +1,122 @@
+//! Round trip test for optional query and body arguments, covering every
+//! combination of present and absent values, and optional bodies taken by
+//! value with `body(owned)`.
+
+mod common;
+
+use axum::{
+    extract::Query,
+    routing::{get, patch, put},
+    Json, Router,
+};
+use common::serve;
+use serde::{Deserialize, Serialize};
+use wrpc_macro::rpc;
+
+#[derive(Serialize, Deserialize)]
+pub struct Filter {
+    team: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Rename {
+    name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Search {
+    name: Option<String>,
+}
+
+// Has to match the port in the `#[rpc]` URLs below
+const PORT: u16 = 38304;
+
+#[rpc(patch("http://127.0.0.1:38304/api/users"))]
+pub async fn update_users(filter: Option<Query<Filter>>, rename: Option<Json<Rename>>) -> String {
+    format!(
+        "{:?} {:?}",
+        filter.map(|Query(filter)| filter.team),
+        rename.map(|Json(rename)| rename.name),
+    )
+}
+
+#[rpc(get("http://127.0.0.1:38304/api/users/search"), path_helper)]
+pub async fn search_users(search: Option<Query<Search>>) -> String {
+    format!("{:?}", search.map(|Query(search)| search.name))
+}
+
+// `retry` builds the request again, so the owned body must not be moved into it
+#[rpc(
+    put("http://127.0.0.1:38304/api/users/rename"),
+    body(owned),
+    retry(1, 10)
+)]
+pub async fn rename_users(rename: Option<Json<Rename>>) -> String {
+    format!("{:?}", rename.map(|Json(rename)| rename.name))
+}
+
+#[tokio::test]
+async fn optional_query_and_json_work() {
+    let router = Router::new()
+        .route("/api/users", patch(update_users))
+        .route("/api/users/search", get(search_users))
+        .route("/api/users/rename", put(rename_users));
+    serve(PORT, router);
+
+    let filter = Filter {
+        team: "core".to_string(),
+    };
+    let rename = Rename {
+        name: "hello".to_string(),
+    };
+
+    assert_eq!(call_update_users(None, None).await.unwrap(), "None None");
+    assert_eq!(
+        call_update_users(Some(&filter), None).await.unwrap(),
+        r#"Some("core") None"#
+    );
+    assert_eq!(
+        call_update_users(None, Some(&rename)).await.unwrap(),
+        r#"None Some("hello")"#
+    );
+    assert_eq!(
+        call_update_users(Some(&filter), Some(&rename))
+            .await
+            .unwrap(),
+        r#"Some("core") Some("hello")"#
+    );
+
+    // Neither `None` nor a query without pairs leaves a trailing `?`
+    let empty = Search { name: None };
+    let named = Search {
+        name: Some("core".to_string()),
+    };
+    assert_eq!(
+        search_users_path(None),
+        "http://127.0.0.1:38304/api/users/search"
+    );
+    assert_eq!(
+        search_users_path(Some(&empty)),
+        "http://127.0.0.1:38304/api/users/search"
+    );
+    assert_eq!(
+        search_users_path(Some(&named)),
+        "http://127.0.0.1:38304/api/users/search?name=core"
+    );
+    // axum deserializes the missing query into `Search` all the same
+    assert_eq!(call_search_users(None).await.unwrap(), "Some(None)");
+    assert_eq!(
+        call_search_users(Some(&named)).await.unwrap(),
+        r#"Some(Some("core"))"#
+    );
+
+    assert_eq!(call_rename_users(None).await.unwrap(), "None");
+    assert_eq!(
+        call_rename_users(Some(Rename {
+            name: "world".to_string(),
+        }))
+        .await
+        .unwrap(),
+        r#"Some("world")"#
+    );
+}
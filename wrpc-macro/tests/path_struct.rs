@@ -0,0 +1,34 @@
+//! Round trip test for `path_struct`, against a handler extracting its path
+//! into a struct.
+
+mod common;
+
+use axum::{extract::Path, routing::get, Router};
+use common::serve;
+use serde::Deserialize;
+use wrpc_macro::rpc;
+
+#[derive(Deserialize)]
+pub struct Params {
+    team: String,
+    id: u32,
+}
+
+// Has to match the port in the `#[rpc]` URLs below
+const PORT: u16 = 38316;
+
+#[rpc(
+    get("http://127.0.0.1:38316/api/team/:team/user/:id"),
+    path_struct(Params { team: String, id: u32 })
+)]
+pub async fn member(Path(params): Path<Params>) -> String {
+    format!("{} of {}", params.id, params.team)
+}
+
+#[tokio::test]
+async fn path_struct_works() {
+    let router = Router::new().route("/api/team/:team/user/:id", get(member));
+    serve(PORT, router);
+
+    assert_eq!(call_member("blue", 1).await.unwrap(), "1 of blue");
+}
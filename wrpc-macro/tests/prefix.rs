@@ -0,0 +1,59 @@
+//! Round trip test for `prefix`, with prefixes with and without a trailing
+//! `/` joined to paths with and without a leading one, and the URLs built for
+//! `host`.
+
+mod common;
+
+use axum::{
+    extract::{Path, Query},
+    routing::get,
+    Router,
+};
+use common::serve;
+use serde::{Deserialize, Serialize};
+use wrpc_macro::rpc;
+
+// Has to match the port in the URLs below
+const PORT: u16 = 38313;
+
+const API: &str = "http://127.0.0.1:38313/api/v1";
+const API_SLASH: &str = "http://127.0.0.1:38313/api/v1/";
+
+#[derive(Serialize, Deserialize)]
+pub struct Pagination {
+    page: u32,
+}
+
+#[rpc(get("/user/:id"), prefix(API))]
+pub async fn user(Path(id): Path<u32>, Query(query): Query<Pagination>) -> String {
+    format!("user {id} page {}", query.page)
+}
+
+#[rpc(get("user/:id"), prefix(API_SLASH))]
+pub async fn user_slash(Path(id): Path<u32>, Query(query): Query<Pagination>) -> String {
+    format!("user {id} page {}", query.page)
+}
+
+#[rpc(get("/user/:id"), host, path_helper)]
+pub async fn user_on_host(Path(id): Path<u32>, Query(query): Query<Pagination>) -> String {
+    format!("user {id} page {}", query.page)
+}
+
+#[test]
+fn host_prepends_caller_host() {
+    let query = Pagination { page: 2 };
+    assert_eq!(
+        user_on_host_path("example.com", 1, &query),
+        "https://example.com/user/1?page=2"
+    );
+}
+
+#[tokio::test]
+async fn prefix_works() {
+    let router = Router::new().nest("/api/v1", Router::new().route("/user/:id", get(user)));
+    serve(PORT, router);
+
+    let query = Pagination { page: 2 };
+    assert_eq!(call_user(1, &query).await.unwrap(), "user 1 page 2");
+    assert_eq!(call_user_slash(1, &query).await.unwrap(), "user 1 page 2");
+}
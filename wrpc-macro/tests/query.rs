@@ -0,0 +1,136 @@
+//! Round trip tests for `query_static`, raw queries, `extra_query` and queries
+//! alongside a JSON body, against handlers echoing the query they received.
+
+mod common;
+
+use std::collections::{BTreeMap, HashMap};
+
+use axum::{
+    extract::{Query, RawQuery},
+    http::Uri,
+    routing::{get, post},
+    Json, Router,
+};
+use common::serve;
+use serde::{Deserialize, Serialize};
+use wrpc_macro::rpc;
+
+#[derive(Serialize, Deserialize)]
+pub struct Page {
+    page: u32,
+}
+
+fn echo(query: HashMap<String, String>) -> String {
+    let pairs = query.into_iter().collect::<BTreeMap<_, _>>();
+    format!("{pairs:?}")
+}
+
+// Has to match the port in the `#[rpc]` URLs below
+const PORT: u16 = 38306;
+
+#[rpc(get("http://127.0.0.1:38306/api/dynamic"))]
+pub async fn dynamic(Query(query): Query<HashMap<String, String>>) -> String {
+    echo(query)
+}
+
+#[rpc(
+    get("http://127.0.0.1:38306/api/static"),
+    query_static("format" => "json", "q" => "a b&c")
+)]
+pub async fn fixed(Query(query): Query<HashMap<String, String>>) -> String {
+    echo(query)
+}
+
+#[rpc(get("http://127.0.0.1:38306/api/combined"), query_static("v" => 2))]
+pub async fn combined(Query(query): Query<HashMap<String, String>>) -> String {
+    echo(query)
+}
+
+#[rpc(post("http://127.0.0.1:38306/api/search"))]
+pub async fn search(
+    Query(query): Query<HashMap<String, String>>,
+    Json(body): Json<Vec<String>>,
+) -> String {
+    format!("{} {body:?}", echo(query))
+}
+
+#[rpc(get("http://127.0.0.1:38306/api/raw"), path_helper)]
+pub async fn raw(RawQuery(query): RawQuery) -> String {
+    format!("{query:?}")
+}
+
+#[rpc(
+    get("http://127.0.0.1:38306/api/extra"),
+    extra_query,
+    path_helper,
+    allow_unknown_extractors
+)]
+pub async fn extra(Query(page): Query<Page>, uri: Uri) -> String {
+    format!("{} {:?}", page.page, uri.query())
+}
+
+#[rpc(get("http://127.0.0.1:38306/api/raw_extra"), extra_query)]
+pub async fn raw_extra(RawQuery(query): RawQuery) -> String {
+    format!("{query:?}")
+}
+
+#[tokio::test]
+async fn query_static_works() {
+    let router = Router::new()
+        .route("/api/dynamic", get(dynamic))
+        .route("/api/static", get(fixed))
+        .route("/api/combined", get(combined))
+        .route("/api/search", post(search))
+        .route("/api/raw", get(raw))
+        .route("/api/extra", get(extra))
+        .route("/api/raw_extra", get(raw_extra));
+    serve(PORT, router);
+
+    let query = HashMap::from([("page".to_string(), "1".to_string())]);
+    assert_eq!(call_dynamic(&query).await.unwrap(), r#"{"page": "1"}"#);
+    assert_eq!(
+        call_fixed(&HashMap::new()).await.unwrap(),
+        r#"{"format": "json", "q": "a b&c"}"#
+    );
+    assert_eq!(
+        call_combined(&query).await.unwrap(),
+        r#"{"page": "1", "v": "2"}"#
+    );
+    // The query goes into the URL and the body stays JSON
+    assert_eq!(
+        call_search(&query, &["a".to_string(), "b".to_string()])
+            .await
+            .unwrap(),
+        r#"{"page": "1"} ["a", "b"]"#
+    );
+    // Raw queries are passed through verbatim, and `None` adds no `?`
+    assert_eq!(
+        raw_path(Some("a=1&a=2&b")),
+        "http://127.0.0.1:38306/api/raw?a=1&a=2&b"
+    );
+    assert_eq!(raw_path(None), "http://127.0.0.1:38306/api/raw");
+    assert_eq!(
+        call_raw(Some("a=1&a=2&b")).await.unwrap(),
+        r#"Some("a=1&a=2&b")"#
+    );
+    assert_eq!(call_raw(None).await.unwrap(), "None");
+
+    // Extra pairs follow the typed query's, or start the query without one
+    let extra_query = [("ref", "a b"), ("beta", "1")];
+    assert_eq!(
+        extra_path(&Page { page: 2 }, &extra_query),
+        "http://127.0.0.1:38306/api/extra?page=2&ref=a%20b&beta=1"
+    );
+    assert_eq!(
+        call_extra(&Page { page: 2 }, &extra_query).await.unwrap(),
+        r#"2 Some("page=2&ref=a%20b&beta=1")"#
+    );
+    assert_eq!(
+        call_raw_extra(None, &extra_query).await.unwrap(),
+        r#"Some("ref=a%20b&beta=1")"#
+    );
+    assert_eq!(
+        call_raw_extra(Some("a=1"), &[]).await.unwrap(),
+        r#"Some("a=1")"#
+    );
+}
@@ -0,0 +1,80 @@
+//! Round trip tests for `query_array`, against `Query` extractors parsing the
+//! repeated keys of a `Vec` field like axum-extra's and `serde_qs`'s do.
+
+mod common;
+
+use axum::{routing::get, Router};
+use common::serve;
+use serde::{Deserialize, Serialize};
+use wrpc_macro::rpc;
+
+/// Defines a `Query<T>` extractor deserializing the query string with
+/// `$deserialize`.
+macro_rules! query_extractor {
+    ($module:ident, $deserialize:expr) => {
+        mod $module {
+            use axum::{extract::FromRequestParts, http::request::Parts, http::StatusCode};
+            use serde::de::DeserializeOwned;
+
+            pub struct Query<T>(pub T);
+
+            #[axum::async_trait]
+            impl<T: DeserializeOwned, S: Send + Sync> FromRequestParts<S> for Query<T> {
+                type Rejection = StatusCode;
+
+                async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, StatusCode> {
+                    let query = parts.uri.query().unwrap_or_default();
+                    ($deserialize)(query)
+                        .map(Query)
+                        .map_err(|_| StatusCode::BAD_REQUEST)
+                }
+            }
+        }
+    };
+}
+
+query_extractor!(html_form, serde_html_form::from_str);
+query_extractor!(qs, |query| serde_qs::Config::new(5, false)
+    .deserialize_str(query));
+
+#[derive(Serialize, Deserialize)]
+pub struct Filter {
+    name: String,
+    ids: Vec<u32>,
+}
+
+// Has to match the port in the `#[rpc]` URLs below
+const PORT: u16 = 38320;
+
+#[rpc(get("http://127.0.0.1:38320/api/repeat"), query_array(repeat))]
+pub async fn repeat(html_form::Query(filter): html_form::Query<Filter>) -> String {
+    format!("{} {:?}", filter.name, filter.ids)
+}
+
+#[rpc(get("http://127.0.0.1:38320/api/brackets"), query_array(brackets))]
+pub async fn brackets(qs::Query(filter): qs::Query<Filter>) -> String {
+    format!("{} {:?}", filter.name, filter.ids)
+}
+
+#[tokio::test]
+async fn query_array_works() {
+    let router = Router::new()
+        .route("/api/repeat", get(repeat))
+        .route("/api/brackets", get(brackets));
+    serve(PORT, router);
+
+    let filter = Filter {
+        name: "a b".to_string(),
+        ids: vec![1, 2],
+    };
+    assert_eq!(
+        wrpc::query::to_string(&filter, wrpc::query::Arrays::Repeat),
+        "ids=1&ids=2&name=a%20b"
+    );
+    assert_eq!(
+        wrpc::query::to_string(&filter, wrpc::query::Arrays::Brackets),
+        "ids[]=1&ids[]=2&name=a%20b"
+    );
+    assert_eq!(call_repeat(&filter).await.unwrap(), "a b [1, 2]");
+    assert_eq!(call_brackets(&filter).await.unwrap(), "a b [1, 2]");
+}
@@ -0,0 +1,123 @@
+//! Round trip tests for `query_encoding`, against axum's `Query` extractor.
+//! Flat structs work either way, nested ones with neither. `query_key(...)`
+//! is only checked against the path, axum can't extract nested queries.
+
+mod common;
+
+use axum::{extract::Query, routing::get, Router};
+use common::serve;
+use serde::{Deserialize, Serialize};
+use wrpc_macro::rpc;
+
+#[derive(Serialize, Deserialize)]
+pub struct Flat {
+    name: String,
+    page: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Page {
+    number: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Nested {
+    name: String,
+    page: Page,
+}
+
+// Has to match the port in the `#[rpc]` URLs below
+const PORT: u16 = 38312;
+
+#[rpc(get("http://127.0.0.1:38312/api/urlencoded"))]
+pub async fn urlencoded(Query(query): Query<Flat>) -> String {
+    format!("{} {}", query.name, query.page)
+}
+
+#[rpc(get("http://127.0.0.1:38312/api/qs"), query_encoding(qs))]
+pub async fn qs(Query(query): Query<Flat>) -> String {
+    format!("{} {}", query.name, query.page)
+}
+
+#[rpc(get("http://127.0.0.1:38312/api/nested_qs"), query_encoding(qs))]
+pub async fn nested_qs(Query(query): Query<Nested>) -> String {
+    format!("{} {}", query.name, query.page.number)
+}
+
+#[rpc(get("http://127.0.0.1:38312/api/nested_urlencoded"))]
+pub async fn nested_urlencoded(Query(query): Query<Nested>) -> String {
+    format!("{} {}", query.name, query.page.number)
+}
+
+#[rpc(get("/api/filtered"), query_key("filter"), path_helper)]
+pub async fn filtered(Query(query): Query<Flat>) -> String {
+    format!("{} {}", query.name, query.page)
+}
+
+#[rpc(
+    get("/api/nested_filtered"),
+    query_encoding(qs),
+    query_key("filter"),
+    path_helper
+)]
+pub async fn nested_filtered(Query(query): Query<Nested>) -> String {
+    format!("{} {}", query.name, query.page.number)
+}
+
+#[tokio::test]
+async fn query_encoding_works() {
+    let router = Router::new()
+        .route("/api/urlencoded", get(urlencoded))
+        .route("/api/qs", get(qs))
+        .route("/api/nested_qs", get(nested_qs));
+    serve(PORT, router);
+
+    let flat = Flat {
+        name: "a b".to_string(),
+        page: 2,
+    };
+    assert_eq!(call_urlencoded(&flat).await.unwrap(), "a b 2");
+    assert_eq!(call_qs(&flat).await.unwrap(), "a b 2");
+
+    // `serde_qs` sends `page[number]=2`, which axum can't deserialize
+    let nested = Nested {
+        name: "a b".to_string(),
+        page: Page { number: 2 },
+    };
+    let rejection = call_nested_qs(&nested).await.unwrap();
+    assert!(
+        rejection.starts_with("Failed to deserialize query string"),
+        "{rejection}"
+    );
+}
+
+#[tokio::test]
+#[should_panic(expected = "unsupported value")]
+async fn nested_urlencoded_panics() {
+    // `serde_urlencoded` refuses to serialize the nested struct at all
+    let nested = Nested {
+        name: "a b".to_string(),
+        page: Page { number: 2 },
+    };
+    let _ = call_nested_urlencoded(&nested).await;
+}
+
+#[test]
+fn query_key_nests_pairs() {
+    let flat = Flat {
+        name: "a b".to_string(),
+        page: 2,
+    };
+    assert_eq!(
+        filtered_path(&flat),
+        "/api/filtered?filter[name]=a+b&filter[page]=2"
+    );
+    let nested = Nested {
+        name: "a b".to_string(),
+        page: Page { number: 2 },
+    };
+    assert_eq!(
+        nested_filtered_path(&nested),
+        "/api/nested_filtered?filter[name]=a+b&filter[page][number]=2"
+    );
+}
@@ -0,0 +1,59 @@
+//! Round trip test for `raw`, reading status, headers and body of the response
+//! by hand.
+
+mod common;
+
+use axum::{extract::Path, http::StatusCode, routing::get, Json, Router};
+use common::serve;
+use serde::{Deserialize, Serialize};
+use wrpc_macro::rpc;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct User {
+    id: u32,
+    name: String,
+}
+
+// Has to match the port in the `#[rpc]` URLs below
+const PORT: u16 = 38327;
+
+#[rpc(get("http://127.0.0.1:38327/api/user/:id"), returns(User), raw)]
+pub async fn get_user(
+    Path(id): Path<u32>,
+) -> (StatusCode, [(&'static str, &'static str); 1], Json<User>) {
+    (
+        StatusCode::ACCEPTED,
+        [("X-Request-Id", "abc")],
+        Json(User {
+            id,
+            name: "hello".to_string(),
+        }),
+    )
+}
+
+#[tokio::test]
+async fn raw_works() {
+    let router = Router::new().route("/api/user/:id", get(get_user));
+    serve(PORT, router);
+
+    let response = call_get_user_raw(1).await.unwrap();
+    assert_eq!(response.status(), wrpc::StatusCode(202));
+    assert_eq!(response.header("X-Request-Id").as_deref(), Some("abc"));
+    assert_eq!(response.header("X-Missing"), None);
+    let user: User = response.json().await.unwrap();
+    assert_eq!(
+        user,
+        User {
+            id: 1,
+            name: "hello".to_string(),
+        }
+    );
+
+    // The regular client function still decodes the body
+    assert_eq!(call_get_user(2).await.unwrap().id, 2);
+
+    let text = call_get_user_raw(2).await.unwrap().text().await.unwrap();
+    assert_eq!(text, r#"{"id":2,"name":"hello"}"#);
+    let bytes = call_get_user_raw(3).await.unwrap().bytes().await.unwrap();
+    assert_eq!(&bytes[..], br#"{"id":3,"name":"hello"}"#);
+}
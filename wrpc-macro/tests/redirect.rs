@@ -0,0 +1,79 @@
+//! Round trip tests for redirects, against handlers redirecting to another
+//! route. They're followed by default, `returns_final_url` reports where to.
+
+mod common;
+
+use axum::{
+    http::{header::LOCATION, StatusCode},
+    response::{IntoResponse, Redirect},
+    routing::get,
+    Json, Router,
+};
+use common::serve;
+use serde::{Deserialize, Serialize};
+use wrpc_macro::rpc;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct User {
+    id: u32,
+    name: String,
+}
+
+// Has to match the port in the `#[rpc]` URL below
+const FINAL_URL_PORT: u16 = 38309;
+
+#[rpc(
+    get("http://127.0.0.1:38309/api/old"),
+    returns(String),
+    returns_final_url
+)]
+pub async fn old() -> Redirect {
+    Redirect::permanent("/api/new")
+}
+
+async fn new() -> Json<String> {
+    Json("moved".to_string())
+}
+
+// Has to match the port in the `#[rpc]` URL below
+const JSON_PORT: u16 = 38315;
+
+#[rpc(get("http://127.0.0.1:38315/api/me"), returns(User))]
+pub async fn me() -> impl IntoResponse {
+    (StatusCode::FOUND, [(LOCATION, "/api/user/1")])
+}
+
+async fn user() -> Json<User> {
+    Json(User {
+        id: 1,
+        name: "hello".to_string(),
+    })
+}
+
+#[tokio::test]
+async fn returns_final_url_works() {
+    let router = Router::new()
+        .route("/api/old", get(old))
+        .route("/api/new", get(new));
+    serve(FINAL_URL_PORT, router);
+
+    let (url, body) = call_old().await.unwrap();
+    assert_eq!(url, "http://127.0.0.1:38309/api/new");
+    assert_eq!(body, "moved");
+}
+
+#[tokio::test]
+async fn redirected_json_works() {
+    let router = Router::new()
+        .route("/api/me", get(me))
+        .route("/api/user/1", get(user));
+    serve(JSON_PORT, router);
+
+    assert_eq!(
+        call_me().await.unwrap(),
+        User {
+            id: 1,
+            name: "hello".to_string()
+        }
+    );
+}
@@ -0,0 +1,85 @@
+//! Test for `request_helper`, replaying a described request against a handler
+//! echoing what it received.
+
+mod common;
+
+use axum::{
+    extract::{Path, Query},
+    http::HeaderMap,
+    routing::post,
+    Json, Router,
+};
+use common::serve;
+use serde::{Deserialize, Serialize};
+use wrpc_macro::rpc;
+
+#[derive(Serialize, Deserialize)]
+pub struct Filter {
+    limit: u32,
+}
+
+// Has to match the port in the `#[rpc]` URLs below
+const PORT: u16 = 38318;
+
+#[rpc(
+    post("http://127.0.0.1:38318/api/user/:id/note"),
+    default_headers("X-Client=wrpc"),
+    request_helper
+)]
+pub async fn note(
+    Path(id): Path<u32>,
+    Query(filter): Query<Filter>,
+    headers: HeaderMap,
+    Json(text): Json<String>,
+) -> String {
+    let trace = headers
+        .get("X-Trace-Id")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("none");
+    format!("{id} {} {trace} {text}", filter.limit)
+}
+
+#[tokio::test]
+async fn request_helper_works() {
+    let router = Router::new().route("/api/user/:id/note", post(note));
+    serve(PORT, router);
+
+    let filter = Filter { limit: 10 };
+    let text = "hello".to_string();
+    let headers = [("X-Trace-Id", "abc")];
+    let description = call_note_request(1, &filter, &text, &headers);
+    assert_eq!(description.method, "POST");
+    assert_eq!(
+        description.url,
+        "http://127.0.0.1:38318/api/user/1/note?limit=10"
+    );
+    assert_eq!(
+        description.headers,
+        [
+            ("X-Trace-Id".to_string(), "abc".to_string()),
+            ("X-Client".to_string(), "wrpc".to_string()),
+            ("Content-Type".to_string(), "application/json".to_string()),
+        ]
+    );
+    assert_eq!(description.body.as_deref(), Some(&b"\"hello\""[..]));
+
+    // Replaying the description gets the same response as the client function
+    let client = reqwest::Client::new();
+    let request = description.headers.iter().fold(
+        client.request(description.method.parse().unwrap(), &description.url),
+        |request, (name, value)| request.header(name, value),
+    );
+    let replayed = request
+        .body(description.body.unwrap())
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert_eq!(replayed, "1 10 abc hello");
+    assert_eq!(
+        call_note(1, &filter, &text, &headers).await.unwrap(),
+        replayed
+    );
+}
@@ -0,0 +1,56 @@
+//! Round trip test for `returns(headers(...))`, decoding typed headers from
+//! the response.
+
+mod common;
+
+use std::time::{Duration, SystemTime};
+
+use axum::{extract::Path, routing::get, Router, TypedHeader};
+use common::serve;
+use headers::{ETag, LastModified};
+use wrpc_macro::rpc;
+
+fn modified() -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_secs(1_600_000_000)
+}
+
+// Has to match the port in the `#[rpc]` URLs below
+const PORT: u16 = 38329;
+
+#[rpc(
+    get("http://127.0.0.1:38329/api/document/:id"),
+    returns(headers(ETag, LastModified))
+)]
+pub async fn get_document(
+    Path(id): Path<u32>,
+) -> (TypedHeader<ETag>, TypedHeader<LastModified>, &'static str) {
+    (
+        TypedHeader(format!("\"doc-{id}\"").parse().unwrap()),
+        TypedHeader(LastModified::from(modified())),
+        "body",
+    )
+}
+
+#[rpc(
+    get("http://127.0.0.1:38329/api/untagged"),
+    returns(headers(ETag, LastModified))
+)]
+pub async fn get_untagged() -> &'static str {
+    "body"
+}
+
+#[tokio::test]
+async fn response_headers_works() {
+    let router = Router::new()
+        .route("/api/document/:id", get(get_document))
+        .route("/api/untagged", get(get_untagged));
+    serve(PORT, router);
+
+    let headers: GetDocumentHeaders = call_get_document(1).await.unwrap();
+    assert_eq!(headers.etag, Some("\"doc-1\"".parse().unwrap()));
+    assert_eq!(headers.last_modified, Some(LastModified::from(modified())));
+
+    let headers = call_get_untagged().await.unwrap();
+    assert_eq!(headers.etag, None);
+    assert_eq!(headers.last_modified, None);
+}
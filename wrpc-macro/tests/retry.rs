@@ -0,0 +1,31 @@
+//! Round trip test for `retry`, against a handler that fails the first two
+//! attempts with a server error.
+
+mod common;
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use axum::{http::StatusCode, routing::get, Json, Router};
+use common::serve;
+use wrpc_macro::rpc;
+
+static ATTEMPTS: AtomicU32 = AtomicU32::new(0);
+
+// Has to match the port in the `#[rpc]` URLs below
+const PORT: u16 = 38305;
+
+#[rpc(get("http://127.0.0.1:38305/api/flaky"), returns(String), retry(3, 10))]
+pub async fn flaky() -> Result<Json<String>, StatusCode> {
+    match ATTEMPTS.fetch_add(1, Ordering::SeqCst) {
+        0 | 1 => Err(StatusCode::SERVICE_UNAVAILABLE),
+        attempt => Ok(Json(format!("attempt {attempt}"))),
+    }
+}
+
+#[tokio::test]
+async fn retry_works() {
+    let router = Router::new().route("/api/flaky", get(flaky));
+    serve(PORT, router);
+
+    assert_eq!(call_flaky().await.unwrap(), "attempt 2");
+}
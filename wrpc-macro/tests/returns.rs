@@ -0,0 +1,73 @@
+//! Round trip tests for `returns(...)` overrides of handlers whose return type
+//! doesn't name the body, i.e. `impl IntoResponse` and status code tuples.
+
+mod common;
+
+use axum::{
+    extract::Path,
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{get, post},
+    Json, Router,
+};
+use common::serve;
+use serde::{Deserialize, Serialize};
+use wrpc_macro::rpc;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct User {
+    id: u32,
+    name: String,
+}
+
+// Has to match the port in the `#[rpc]` URLs below
+const PORT: u16 = 38324;
+
+#[rpc(get("http://127.0.0.1:38324/api/users"), returns(Vec<User>))]
+pub async fn list_users() -> impl IntoResponse {
+    Json(vec![User {
+        id: 1,
+        name: "hello".to_string(),
+    }])
+}
+
+#[rpc(get("http://127.0.0.1:38324/api/user/:id"), returns(Option<User>))]
+pub async fn find_user(Path(id): Path<u32>) -> impl IntoResponse {
+    let user = (id == 1).then(|| User {
+        id,
+        name: "hello".to_string(),
+    });
+    Json(user)
+}
+
+#[rpc(post("http://127.0.0.1:38324/api/user"), returns(status_json(User)))]
+pub async fn create_user(Json(name): Json<String>) -> (StatusCode, Json<User>) {
+    (StatusCode::CREATED, Json(User { id: 2, name }))
+}
+
+#[tokio::test]
+async fn return_overrides_work() {
+    let router = Router::new()
+        .route("/api/users", get(list_users))
+        .route("/api/user/:id", get(find_user))
+        .route("/api/user", post(create_user));
+    serve(PORT, router);
+
+    let hello = User {
+        id: 1,
+        name: "hello".to_string(),
+    };
+    assert_eq!(call_list_users().await.unwrap(), [hello]);
+    assert_eq!(
+        call_find_user(1).await.unwrap().map(|user| user.id),
+        Some(1)
+    );
+    assert_eq!(call_find_user(2).await.unwrap(), None);
+    assert_eq!(
+        call_create_user(&"world".to_string()).await.unwrap(),
+        User {
+            id: 2,
+            name: "world".to_string(),
+        }
+    );
+}
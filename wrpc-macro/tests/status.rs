@@ -0,0 +1,47 @@
+//! Round trip test for `returns_with_status`, telling a `201 Created` apart
+//! from a `200 OK`.
+
+mod common;
+
+use axum::{extract::Path, http::StatusCode, routing::put, Json, Router};
+use common::serve;
+use serde::{Deserialize, Serialize};
+use wrpc_macro::rpc;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Team {
+    name: String,
+}
+
+// Has to match the port in the `#[rpc]` URLs below
+const PORT: u16 = 38321;
+
+#[rpc(
+    put("http://127.0.0.1:38321/api/team/:name"),
+    returns(Team),
+    returns_with_status
+)]
+pub async fn upsert_team(Path(name): Path<String>) -> (StatusCode, Json<Team>) {
+    // Only `core` exists already
+    let status = if name == "core" {
+        StatusCode::OK
+    } else {
+        StatusCode::CREATED
+    };
+    (status, Json(Team { name }))
+}
+
+#[tokio::test]
+async fn returns_with_status_works() {
+    let router = Router::new().route("/api/team/:name", put(upsert_team));
+    serve(PORT, router);
+
+    let (status, team) = call_upsert_team("core").await.unwrap();
+    assert_eq!(status, wrpc::StatusCode(200));
+    assert_eq!(team.name, "core");
+
+    let (status, team) = call_upsert_team("web").await.unwrap();
+    assert_eq!(status, wrpc::StatusCode(201));
+    assert!(status.is_success());
+    assert_eq!(team.name, "web");
+}
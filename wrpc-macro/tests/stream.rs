@@ -0,0 +1,44 @@
+//! Round trip test for `returns(stream)`, reading a large body chunk by
+//! chunk.
+
+mod common;
+
+use std::{future::poll_fn, pin::pin};
+
+use axum::{routing::get, Router};
+use common::serve;
+use wrpc::Stream;
+use wrpc_macro::rpc;
+
+const SIZE: usize = 4 * 1024 * 1024;
+
+// Has to match the port in the `#[rpc]` URLs below
+const PORT: u16 = 38314;
+
+#[rpc(get("http://127.0.0.1:38314/api/download"), returns(stream))]
+pub async fn download() -> Vec<u8> {
+    (0..SIZE).map(|i| i as u8).collect()
+}
+
+#[tokio::test]
+async fn stream_works() {
+    let router = Router::new().route("/api/download", get(download));
+    serve(PORT, router);
+
+    // Spawning the read requires both the client future and the stream to
+    // be `Send`
+    let (body, chunks) = tokio::spawn(async {
+        let mut stream = pin!(call_download().await.unwrap());
+        let mut body = Vec::new();
+        let mut chunks = 0;
+        while let Some(chunk) = poll_fn(|cx| stream.as_mut().poll_next(cx)).await {
+            body.extend_from_slice(&chunk.unwrap());
+            chunks += 1;
+        }
+        (body, chunks)
+    })
+    .await
+    .unwrap();
+    assert!(chunks > 1);
+    assert_eq!(body, (0..SIZE).map(|i| i as u8).collect::<Vec<_>>());
+}
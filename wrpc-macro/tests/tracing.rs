@@ -0,0 +1,62 @@
+//! Round trip test for the `tracing` feature, making sure the instrumented
+//! clients still pass results and errors through.
+#![cfg(feature = "tracing")]
+
+mod common;
+
+use axum::{extract::Path, http::StatusCode, response::IntoResponse, routing::get, Json, Router};
+use common::serve;
+use serde::{Deserialize, Serialize};
+use wrpc_macro::rpc;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct User {
+    id: u32,
+    name: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct ApiError {
+    message: String,
+}
+
+// Has to match the port in the `#[rpc]` URLs below
+const PORT: u16 = 38331;
+
+#[rpc(get("http://127.0.0.1:38331/api/user/:id"))]
+pub async fn get_user(Path(id): Path<u32>) -> Json<User> {
+    Json(User {
+        id,
+        name: "hello".to_string(),
+    })
+}
+
+#[rpc(
+    get("http://127.0.0.1:38331/api/checked/:id"),
+    returns(User),
+    error(ApiError)
+)]
+pub async fn get_checked_user(Path(id): Path<u32>) -> impl IntoResponse {
+    let error = ApiError {
+        message: format!("no user {id}"),
+    };
+    (StatusCode::NOT_FOUND, Json(error))
+}
+
+#[tokio::test]
+async fn tracing_passes_results_through() {
+    let router = Router::new()
+        .route("/api/user/:id", get(get_user))
+        .route("/api/checked/:id", get(get_checked_user));
+    serve(PORT, router);
+
+    assert_eq!(
+        call_get_user(1).await.unwrap(),
+        User {
+            id: 1,
+            name: "hello".to_string(),
+        }
+    );
+    let err = call_get_checked_user(2).await.unwrap_err();
+    assert!(matches!(err, wrpc::Error::Api(ApiError { message }) if message == "no user 2"));
+}
@@ -0,0 +1,75 @@
+use axum::{
+    extract::{Path, Query},
+    headers::{authorization::Bearer, Authorization},
+    Json, TypedHeader,
+};
+use serde::{Deserialize, Serialize};
+use wrpc_macro::rpc;
+
+#[derive(Serialize, Deserialize)]
+pub struct User {
+    id: u32,
+    name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Pagination {
+    page: u32,
+}
+
+#[rpc(get("/api/users/:team_id"))]
+pub async fn list_users(
+    Path(team_id): Path<u32>,
+    pagination: Option<Query<Pagination>>,
+) -> Json<Vec<User>> {
+    Json(vec![User {
+        id: team_id,
+        name: pagination
+            .map_or(0, |Query(pagination)| pagination.page)
+            .to_string(),
+    }])
+}
+
+#[rpc(post("/api/users/:team_id"))]
+pub async fn create_user(
+    Path(team_id): Path<u32>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    Json(user): Json<User>,
+) -> Json<User> {
+    Json(User {
+        id: team_id,
+        name: format!("{} {}", auth.token(), user.name),
+    })
+}
+
+#[cfg(feature = "ts-export")]
+#[test]
+fn ts_export_emits_wrappers() {
+    let output = wrpc::ts::emit();
+    let (types, functions) = output.split_once("\n\n/** Appends").unwrap();
+    assert_eq!(
+        types,
+        "export type Pagination = unknown;\n\
+         export type User = unknown;"
+    );
+    let (_, functions) = functions.split_once("\n}\n\n/** `").unwrap();
+    assert_eq!(
+        functions,
+        "POST /api/users/:team_id` */\n\
+         export async function createUser(teamId: number, user: User, auth: string): Promise<User> {\n  \
+           const headers = new Headers([[\"Accept\", \"application/json\"], [\"Content-Type\", \"application/json\"]]);\n  \
+           headers.set(\"authorization\", auth);\n  \
+           const response = await wrpcFetch(\"POST\", `/api/users/${encodeURIComponent(String(teamId))}`, \"\", { headers, body: JSON.stringify(user) });\n  \
+           return await response.json();\n\
+         }\n\
+         \n\
+         /** `GET /api/users/:team_id` */\n\
+         export async function listUsers(teamId: number, pagination: Pagination | null): Promise<User[]> {\n  \
+           const query = new URLSearchParams();\n  \
+           wrpcQuery(query, pagination);\n  \
+           const headers = new Headers([[\"Accept\", \"application/json\"]]);\n  \
+           const response = await wrpcFetch(\"GET\", `/api/users/${encodeURIComponent(String(teamId))}`, query.toString(), { headers });\n  \
+           return await response.json();\n\
+         }\n"
+    );
+}
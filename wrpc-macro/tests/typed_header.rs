@@ -0,0 +1,52 @@
+//! Round trip test for `TypedHeader` arguments, against a handler echoing the
+//! raw bytes of a header value that isn't valid UTF-8.
+
+mod common;
+
+use axum::{
+    headers::{self, Header, HeaderName, HeaderValue},
+    routing::get,
+    Router, TypedHeader,
+};
+use common::serve;
+use wrpc_macro::rpc;
+
+// Has to match the port in the `#[rpc]` URL below
+const PORT: u16 = 38334;
+
+static LATIN1: HeaderName = HeaderName::from_static("x-latin1");
+
+/// A header carrying Latin-1 text, which `HeaderValue::to_str` rejects.
+pub struct Latin1(Vec<u8>);
+
+impl Header for Latin1 {
+    fn name() -> &'static HeaderName {
+        &LATIN1
+    }
+
+    fn decode<'i, I: Iterator<Item = &'i HeaderValue>>(
+        values: &mut I,
+    ) -> Result<Self, headers::Error> {
+        values
+            .next()
+            .map(|value| Latin1(value.as_bytes().to_vec()))
+            .ok_or_else(headers::Error::invalid)
+    }
+
+    fn encode<E: Extend<HeaderValue>>(&self, values: &mut E) {
+        values.extend(HeaderValue::from_bytes(&self.0).ok());
+    }
+}
+
+#[rpc(get("http://127.0.0.1:38334/api/latin1"))]
+pub async fn latin1(TypedHeader(latin1): TypedHeader<Latin1>) -> String {
+    format!("{:?}", latin1.0)
+}
+
+#[tokio::test]
+async fn typed_header_sends_raw_bytes() {
+    serve(PORT, Router::new().route("/api/latin1", get(latin1)));
+
+    let value = Latin1(b"caf\xe9".to_vec());
+    assert_eq!(call_latin1(&value).await.unwrap(), "[99, 97, 102, 233]");
+}
@@ -0,0 +1,18 @@
+// The client cfg is unknown to this crate, which is beside the point here
+#![allow(unexpected_cfgs)]
+
+use axum::Json;
+use serde::Deserialize;
+use wrpc_macro::rpc;
+
+#[derive(Deserialize)]
+pub struct Login {
+    name: String,
+}
+
+#[rpc(post("/api/login"))]
+pub async fn login(Json(login): Json<Login>) -> String {
+    login.name
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use wrpc_macro::rpc;
+
+#[rpc(get("/api/user/{id}"))]
+pub async fn get_user(axum::extract::Path(id): axum::extract::Path<u32>) -> String {
+    id.to_string()
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use wrpc_macro::rpc;
+
+#[rpc(get("/api//users"))]
+pub async fn list_users() -> String {
+    "users".to_string()
+}
+
+fn main() {}
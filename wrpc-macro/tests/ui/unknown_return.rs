@@ -0,0 +1,8 @@
+use wrpc_macro::rpc;
+
+#[rpc(get("/api/status"))]
+pub async fn status() -> axum::response::Response {
+    axum::response::IntoResponse::into_response("ok")
+}
+
+fn main() {}
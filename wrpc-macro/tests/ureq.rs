@@ -0,0 +1,73 @@
+//! Round trip test for the synchronous client functions of
+//! `native_client(ureq)`.
+
+mod common;
+
+use axum::{
+    extract::{Path, Query},
+    http::StatusCode,
+    routing::{get, post},
+    Json, Router,
+};
+use common::serve;
+use serde::{Deserialize, Serialize};
+use wrpc_macro::rpc;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct User {
+    id: u32,
+    name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Pagination {
+    page: u32,
+}
+
+// Has to match the port in the `#[rpc]` URLs below
+const PORT: u16 = 38319;
+
+#[rpc(
+    post("http://127.0.0.1:38319/api/user/:id"),
+    default_headers("X-Client=wrpc"),
+    native_client(ureq)
+)]
+pub async fn rename(
+    Path(id): Path<u32>,
+    Query(query): Query<Pagination>,
+    Json(name): Json<String>,
+) -> Json<User> {
+    Json(User {
+        id,
+        name: format!("{name} {}", query.page),
+    })
+}
+
+#[rpc(get("http://127.0.0.1:38319/api/missing"), native_client(ureq))]
+pub async fn missing() -> StatusCode {
+    StatusCode::NOT_FOUND
+}
+
+#[tokio::test]
+async fn ureq_works() {
+    let router = Router::new()
+        .route("/api/user/:id", post(rename))
+        .route("/api/missing", get(missing));
+    serve(PORT, router);
+
+    // `ureq` blocks, so it's kept off the runtime serving the requests
+    let (user, missing) = tokio::task::spawn_blocking(|| {
+        let user = call_rename(1, &Pagination { page: 2 }, &"hello".to_string());
+        (user, call_missing())
+    })
+    .await
+    .unwrap();
+    assert_eq!(
+        user.unwrap(),
+        User {
+            id: 1,
+            name: "hello 2".to_string()
+        }
+    );
+    assert!(matches!(missing, Err(wrpc::Error::Ureq(_))));
+}
@@ -0,0 +1,48 @@
+//! Round trip test for `ws(path)`, echoing messages over a socket opened by the
+//! generated connect function.
+
+mod common;
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path,
+    },
+    response::Response,
+    routing::get,
+    Router,
+};
+use common::serve;
+use futures_util::{SinkExt, StreamExt};
+use wrpc_macro::rpc;
+
+// Has to match the port in the `#[rpc]` URLs below
+const PORT: u16 = 38325;
+
+#[rpc(ws("http://127.0.0.1:38325/api/chat/:room"))]
+pub async fn chat(ws: WebSocketUpgrade, Path(room): Path<String>) -> Response {
+    ws.on_upgrade(|socket| echo(socket, room))
+}
+
+async fn echo(mut socket: WebSocket, room: String) {
+    while let Some(Ok(Message::Text(text))) = socket.recv().await {
+        let reply = Message::Text(format!("{room}: {text}"));
+        if socket.send(reply).await.is_err() {
+            break;
+        }
+    }
+}
+
+#[tokio::test]
+async fn ws_works() {
+    let router = Router::new().route("/api/chat/:room", get(chat));
+    serve(PORT, router);
+
+    let mut socket = connect_chat("lobby").await.unwrap();
+    socket
+        .send(wrpc::ws::Message::Text("hello".to_string()))
+        .await
+        .unwrap();
+    let reply = socket.next().await.unwrap().unwrap();
+    assert_eq!(reply.into_text().unwrap(), "lobby: hello");
+}